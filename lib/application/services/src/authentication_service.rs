@@ -0,0 +1,161 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+extern crate authenticators;
+extern crate data_access;
+extern crate models;
+
+use authenticators::{api_key_hasher, jwt_authenticator::JwtAuthenticator};
+use chrono::Utc;
+use tracing::{info, warn};
+use models::{api_key::ApiKey, model::Model, principal::{AuthenticationMethod, Principal, Role}};
+use uuid::Uuid;
+
+use crate::app_config::AppConfig;
+
+/// Resolves bearer tokens and api keys to the principal passed into `MutimediaManagementService` calls.
+#[derive(Clone)]
+pub struct AuthenticationService {
+    pub jwt_authenticator: Option<JwtAuthenticator>,
+    pub sql_data_access: Option<data_access::psql_data_access_async::PsqlDataAccess>,
+    // subjects of credentials without a tenant which may select any tenant per request
+    pub cross_tenant_subjects: Vec<String>,
+}
+
+impl AuthenticationService {
+    pub async fn new(app_config: &AppConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(AuthenticationService {
+            jwt_authenticator: Some(JwtAuthenticator::new(&app_config.authentication)?),
+            sql_data_access: Some(data_access::psql_data_access_async::PsqlDataAccess::new(&app_config.database).await?),
+            cross_tenant_subjects: app_config.tenancy.cross_tenant_subjects.clone(),
+        })
+    }
+
+    pub fn authenticate_bearer_token(&self, token: &str) -> Result<Principal, Box<dyn std::error::Error>> {
+        self.jwt_authenticator.as_ref().unwrap().authenticate(token)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn authenticate_api_key(&self, api_key: &str) -> Result<Principal, Box<dyn std::error::Error>> {
+        let key_prefix = api_key_hasher::parse_key_prefix(api_key).ok_or("Malformed api key")?;
+        // the tenant is only known once the key is found, hence the lookup spans all tenants
+        let stored_api_key = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .find_active_api_key_by_prefix(key_prefix)
+            .await?;
+
+        match stored_api_key {
+            Some(stored_api_key) if api_key_hasher::verify_api_key(api_key, &stored_api_key.key_hash) => {
+                let mut principal = Principal::new(&stored_api_key.subject, AuthenticationMethod::ApiKey);
+                principal.roles = stored_api_key.roles.iter().flatten().filter_map(|role| Role::parse(role)).collect();
+                principal.tenant_id = Some(stored_api_key.tenant_id);
+                Ok(principal)
+            }
+            _ => {
+                warn!("Rejected api key {}", key_prefix);
+                Err("Unknown or revoked api key".into())
+            }
+        }
+    }
+
+    /// Creates an api key for the principal. The returned plain text key cannot be retrieved again.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, name))]
+    pub async fn create_api_key(&self, principal: &Principal, name: &str)
+        -> Result<(ApiKey, String), Box<dyn std::error::Error>> {
+        let generated_api_key = api_key_hasher::generate_api_key();
+
+        let mut api_key = ApiKey::new();
+        api_key.id = Uuid::new_v4();
+        api_key.name = String::from(name);
+        api_key.subject = principal.subject.clone();
+        api_key.key_prefix = generated_api_key.key_prefix;
+        api_key.key_hash = generated_api_key.key_hash;
+        api_key.date_time_created = Utc::now();
+        api_key.roles = principal.roles.iter().map(|role| Some(String::from(role.as_str()))).collect();
+        // keys are bound to the tenant they were created in
+        api_key.tenant_id = String::from(principal.resolved_tenant_id());
+
+        let api_key = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .for_tenant(principal.resolved_tenant_id())
+            .insert_api_key(&api_key)
+            .await?;
+        info!("Created api key {} on behalf of {}", api_key.key_prefix, principal.subject);
+        Ok((api_key, generated_api_key.api_key))
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, api_key_id = %api_key_id))]
+    pub async fn revoke_api_key(&self, principal: &Principal, api_key_id: &Uuid)
+        -> Result<Option<ApiKey>, Box<dyn std::error::Error>> {
+        let api_key = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .for_tenant(principal.resolved_tenant_id())
+            .revoke_api_key_by_id(api_key_id, &principal.subject)
+            .await?;
+        Ok(api_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[tokio::test]
+    async fn test_authentication_service() -> Result<(), Box<dyn std::error::Error>> {
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/app-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let jwt_authenticator = JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length");
+        let authentication_service = AuthenticationService {
+            jwt_authenticator: Some(jwt_authenticator.clone()),
+            sql_data_access: Some(data_access::psql_data_access_async::PsqlDataAccess::new(&AppConfig::load(None)?.database).await?),
+            cross_tenant_subjects: Vec::new(),
+        };
+
+        // Bearer token
+        let token = jwt_authenticator.mint_hs256_token("user-1", &[Role::Uploader], 300)?;
+        let principal = authentication_service.authenticate_bearer_token(&token)?;
+        assert_eq!(principal.subject, "user-1");
+
+        // Api key
+        let (api_key, plain_text_api_key) = authentication_service.create_api_key(&principal, "ci upload").await?;
+        let api_key_principal = authentication_service.authenticate_api_key(&plain_text_api_key).await?;
+        assert_eq!(api_key_principal.subject, "user-1");
+        assert_eq!(api_key_principal.authentication_method, AuthenticationMethod::ApiKey);
+        assert_eq!(api_key_principal.roles, vec![Role::Uploader]);
+        assert_eq!(api_key_principal.resolved_tenant_id(), principal.resolved_tenant_id());
+        assert!(authentication_service.authenticate_api_key(&(plain_text_api_key.clone() + "0")).await.is_err());
+
+        let revoke_result = authentication_service.revoke_api_key(&principal, &api_key.id).await?;
+        assert!(revoke_result.is_some());
+        assert!(authentication_service.authenticate_api_key(&plain_text_api_key).await.is_err());
+        Ok(())
+    }
+}
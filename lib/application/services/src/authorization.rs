@@ -0,0 +1,227 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::container_grant::{ContainerGrant, PERMISSION_EDIT};
+use models::container_meta::ContainerMeta;
+use models::principal::{Principal, Role};
+use models::tenant;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    View,
+    Edit,
+    Share,
+}
+
+/// Returned when the caller may see a container but not perform the requested operation.
+#[derive(Debug)]
+pub struct AuthorizationError {
+    pub message: String,
+}
+
+impl AuthorizationError {
+    pub fn new(message: &str) -> Self {
+        AuthorizationError { message: String::from(message) }
+    }
+}
+
+impl fmt::Display for AuthorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AuthorizationError {}
+
+/// Binds the principal to the tenant requested e.g. by a header. Credentials naming a tenant are bound to it,
+/// credentials without one stay in the default tenant unless their subject is one of `cross_tenant_subjects`.
+/// A request for any other tenant is rejected.
+pub fn resolve_tenant(principal: &mut Principal, requested_tenant_id: Option<&str>, cross_tenant_subjects: &[String])
+    -> Result<(), AuthorizationError> {
+    let requested_tenant_id = match requested_tenant_id {
+        Some(requested_tenant_id) => requested_tenant_id,
+        None => return Ok(()),
+    };
+    if !tenant::is_valid_tenant_id(requested_tenant_id) {
+        return Err(AuthorizationError::new("Invalid tenant"));
+    }
+    match &principal.tenant_id {
+        Some(tenant_id) if tenant_id != requested_tenant_id => {
+            Err(AuthorizationError::new("The credential is bound to another tenant"))
+        }
+        Some(_) => Ok(()),
+        None if cross_tenant_subjects.contains(&principal.subject) => {
+            principal.tenant_id = Some(String::from(requested_tenant_id));
+            Ok(())
+        }
+        None if requested_tenant_id == tenant::DEFAULT_TENANT_ID => Ok(()),
+        None => Err(AuthorizationError::new("The credential may not act on behalf of other tenants")),
+    }
+}
+
+/// Containers of other tenants are never accessible, not even to admins. Admins may do everything
+/// within their tenant. Owners need the uploader role to edit and share their own containers,
+/// grantees need the editor role on top of an edit grant. Viewing requires the viewer role and
+/// either ownership or any grant.
+pub fn is_authorized(
+    principal: &Principal,
+    container_meta: &ContainerMeta,
+    container_grants: &[ContainerGrant],
+    permission: Permission,
+) -> bool {
+    if container_meta.tenant_id != principal.resolved_tenant_id() {
+        return false;
+    }
+    if principal.has_role(Role::Admin) {
+        return true;
+    }
+    if !principal.has_role(Role::Viewer) {
+        return false;
+    }
+
+    let is_owner = !container_meta.owner_id.is_empty() && container_meta.owner_id == principal.subject;
+    let container_grant = container_grants.iter().find(|container_grant| {
+        container_grant.container_meta_id == container_meta.id && container_grant.grantee == principal.subject
+    });
+    match permission {
+        Permission::View => is_owner || container_grant.is_some(),
+        Permission::Edit => {
+            (is_owner && principal.has_role(Role::Uploader))
+                || (container_grant.is_some_and(|container_grant| container_grant.permission == PERMISSION_EDIT)
+                    && principal.has_role(Role::Editor))
+        }
+        Permission::Share => is_owner && principal.has_role(Role::Uploader),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use authenticators::jwt_authenticator::JwtAuthenticator;
+    use models::container_grant::PERMISSION_VIEW;
+    use models::model::Model;
+    use models::principal::AuthenticationMethod;
+    use uuid::Uuid;
+
+    fn principal(subject: &str, roles: Vec<Role>) -> Principal {
+        let mut principal = Principal::new(subject, AuthenticationMethod::Jwt);
+        principal.roles = roles;
+        principal
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_is_authorized() {
+        let mut container_meta = ContainerMeta::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.owner_id = String::from("owner");
+        container_meta.tenant_id = String::from(tenant::DEFAULT_TENANT_ID);
+
+        let mut view_grant = ContainerGrant::new();
+        view_grant.container_meta_id = container_meta.id;
+        view_grant.grantee = String::from("viewer");
+        view_grant.permission = String::from(PERMISSION_VIEW);
+        let mut edit_grant = ContainerGrant::new();
+        edit_grant.container_meta_id = container_meta.id;
+        edit_grant.grantee = String::from("editor");
+        edit_grant.permission = String::from(PERMISSION_EDIT);
+        let container_grants = vec![view_grant, edit_grant];
+
+        let owner = principal("owner", vec![Role::Uploader]);
+        assert!(is_authorized(&owner, &container_meta, &container_grants, Permission::View));
+        assert!(is_authorized(&owner, &container_meta, &container_grants, Permission::Edit));
+        assert!(is_authorized(&owner, &container_meta, &container_grants, Permission::Share));
+
+        // ownership alone is not enough without a role
+        let owner_without_role = principal("owner", Vec::new());
+        assert!(!is_authorized(&owner_without_role, &container_meta, &container_grants, Permission::View));
+
+        let viewer = principal("viewer", vec![Role::Editor]);
+        assert!(is_authorized(&viewer, &container_meta, &container_grants, Permission::View));
+        assert!(!is_authorized(&viewer, &container_meta, &container_grants, Permission::Edit));
+
+        let editor = principal("editor", vec![Role::Editor]);
+        assert!(is_authorized(&editor, &container_meta, &container_grants, Permission::Edit));
+        assert!(!is_authorized(&editor, &container_meta, &container_grants, Permission::Share));
+        let editor_with_viewer_role = principal("editor", vec![Role::Viewer]);
+        assert!(!is_authorized(&editor_with_viewer_role, &container_meta, &container_grants, Permission::Edit));
+
+        let stranger = principal("stranger", vec![Role::Editor]);
+        assert!(!is_authorized(&stranger, &container_meta, &container_grants, Permission::View));
+
+        let admin = principal("admin", vec![Role::Admin]);
+        assert!(is_authorized(&admin, &container_meta, &container_grants, Permission::Share));
+        let mut admin_of_other_tenant = principal("admin", vec![Role::Admin]);
+        admin_of_other_tenant.tenant_id = Some(String::from("brand-b"));
+        assert!(!is_authorized(&admin_of_other_tenant, &container_meta, &container_grants, Permission::View));
+
+        // containers without owner are only visible to admins
+        container_meta.owner_id = String::from("");
+        let empty_subject = principal("", vec![Role::Uploader]);
+        assert!(!is_authorized(&empty_subject, &container_meta, &container_grants, Permission::View));
+    }
+
+    #[test]
+    fn test_resolve_tenant() {
+        let cross_tenant_subjects = vec![String::from("platform-admin")];
+        let mut unbound = principal("user-1", vec![Role::Viewer]);
+        assert!(resolve_tenant(&mut unbound, None, &cross_tenant_subjects).is_ok());
+        assert_eq!(unbound.resolved_tenant_id(), tenant::DEFAULT_TENANT_ID);
+        assert!(resolve_tenant(&mut unbound, Some(tenant::DEFAULT_TENANT_ID), &cross_tenant_subjects).is_ok());
+        assert!(resolve_tenant(&mut unbound, Some("brand-a"), &cross_tenant_subjects).is_err());
+        assert_eq!(unbound.resolved_tenant_id(), tenant::DEFAULT_TENANT_ID);
+
+        let mut cross_tenant = principal("platform-admin", vec![Role::Admin]);
+        assert!(resolve_tenant(&mut cross_tenant, Some("brand-a"), &cross_tenant_subjects).is_ok());
+        assert_eq!(cross_tenant.resolved_tenant_id(), "brand-a");
+
+        let mut bound = principal("user-2", vec![Role::Viewer]);
+        bound.tenant_id = Some(String::from("brand-a"));
+        assert!(resolve_tenant(&mut bound, Some("brand-a"), &cross_tenant_subjects).is_ok());
+        assert!(resolve_tenant(&mut bound, Some("brand-b"), &cross_tenant_subjects).is_err());
+        assert_eq!(bound.resolved_tenant_id(), "brand-a");
+
+        let mut invalid = principal("platform-admin", vec![Role::Viewer]);
+        assert!(resolve_tenant(&mut invalid, Some("../brand-a"), &cross_tenant_subjects).is_err());
+    }
+
+    #[test]
+    fn test_tenantless_admin_token_cannot_read_other_tenants() -> Result<(), Box<dyn std::error::Error>> {
+        let jwt_authenticator = JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length");
+        let token = jwt_authenticator.mint_hs256_token("brand-a-admin", &[Role::Admin], 300)?;
+        let mut admin = jwt_authenticator.authenticate(&token)?;
+        assert_eq!(admin.tenant_id, None);
+
+        let mut container_meta = ContainerMeta::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.owner_id = String::from("brand-b-owner");
+        container_meta.tenant_id = String::from("brand-b");
+
+        assert!(resolve_tenant(&mut admin, Some("brand-b"), &[String::from("platform-admin")]).is_err());
+        assert_eq!(admin.resolved_tenant_id(), tenant::DEFAULT_TENANT_ID);
+        assert!(!is_authorized(&admin, &container_meta, &[], Permission::View));
+        Ok(())
+    }
+}
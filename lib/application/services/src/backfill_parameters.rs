@@ -21,20 +21,26 @@
 // SOFTWARE.
 
 
-pub struct DownloadBlobParameters {
-    pub container_meta_id: String,
-    pub file_name: String,
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackfillParameters {
+    pub batch_size: i64,
+    pub interval_in_secs: u64,
 }
 
-impl DownloadBlobParameters {
+impl BackfillParameters {
     pub fn new() -> Self {
-        DownloadBlobParameters{
-            container_meta_id: String::from(""),
-            file_name: String::from(""),
+        BackfillParameters {
+            batch_size: 100,
+            interval_in_secs: 600,
         }
     }
+}
 
-    pub fn get_blob_name(&self) -> String {
-        String::from(&self.container_meta_id) + "/" + &self.file_name
+impl Default for BackfillParameters {
+    fn default() -> Self {
+        BackfillParameters::new()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,85 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+
+/// Part of a file requested for download, as in an HTTP `Range: bytes=` header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByteRange {
+    /// Bytes from the first offset up to and including the second one, or up to the end of the file.
+    FromTo(u64, Option<u64>),
+    /// The last bytes of the file.
+    Last(u64),
+}
+
+impl ByteRange {
+    /// Inclusive range of a file of `file_size_bytes`, the end clamped to the file. `None` if no byte of the file is requested.
+    pub fn resolve(&self, file_size_bytes: u64) -> Option<(u64, u64)> {
+        if file_size_bytes == 0 {
+            return None;
+        }
+        match *self {
+            ByteRange::FromTo(start, end) if start < file_size_bytes && end.is_none_or(|end| start <= end) => {
+                Some((start, end.map_or(file_size_bytes - 1, |end| end.min(file_size_bytes - 1))))
+            }
+            ByteRange::FromTo(..) => None,
+            ByteRange::Last(0) => None,
+            ByteRange::Last(length) => Some((file_size_bytes.saturating_sub(length), file_size_bytes - 1)),
+        }
+    }
+}
+
+/// Returned when the requested range lies outside of the file.
+#[derive(Debug)]
+pub struct RangeNotSatisfiableError {
+    pub file_size_bytes: u64,
+}
+
+impl fmt::Display for RangeNotSatisfiableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The requested range is not satisfiable for {} bytes", self.file_size_bytes)
+    }
+}
+
+impl std::error::Error for RangeNotSatisfiableError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_resolve_byte_range() {
+        assert_eq!(ByteRange::FromTo(0, Some(99)).resolve(1000), Some((0, 99)));
+        assert_eq!(ByteRange::FromTo(500, Some(5000)).resolve(1000), Some((500, 999)));
+        assert_eq!(ByteRange::FromTo(950, None).resolve(1000), Some((950, 999)));
+        assert_eq!(ByteRange::Last(100).resolve(1000), Some((900, 999)));
+        assert_eq!(ByteRange::Last(5000).resolve(1000), Some((0, 999)));
+
+        assert_eq!(ByteRange::FromTo(1000, None).resolve(1000), None);
+        assert_eq!(ByteRange::FromTo(10, Some(5)).resolve(1000), None);
+        assert_eq!(ByteRange::Last(0).resolve(1000), None);
+        assert_eq!(ByteRange::FromTo(0, None).resolve(0), None);
+    }
+}
@@ -0,0 +1,176 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::update_parameters::UpdateChapterParameters;
+use models::chapter::Chapter;
+use models::container_meta::ContainerMeta;
+use std::fmt;
+use uuid::Uuid;
+
+pub const MAX_CHAPTER_COUNT: usize = 1024;
+pub const MAX_CHAPTER_TITLE_LENGTH: usize = 256;
+
+/// Returned when the chapters of a container are not ordered, overlap or exceed the container.
+#[derive(Debug)]
+pub struct ChapterValidationError {
+    pub message: String,
+}
+
+impl ChapterValidationError {
+    pub fn new(message: &str) -> Self {
+        ChapterValidationError { message: String::from(message) }
+    }
+}
+
+impl fmt::Display for ChapterValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ChapterValidationError {}
+
+/// Builds the chapters replacing those of the container. Chapters must be given in playback order and must not overlap,
+/// an empty list removes all chapters.
+pub fn to_chapters(container_meta: &ContainerMeta, update_chapter_parameters: &[UpdateChapterParameters])
+    -> Result<Vec<Chapter>, ChapterValidationError> {
+    if update_chapter_parameters.len() > MAX_CHAPTER_COUNT {
+        return Err(ChapterValidationError::new(&format!("At most {} chapters are accepted", MAX_CHAPTER_COUNT)));
+    }
+    let mut chapters: Vec<Chapter> = Vec::with_capacity(update_chapter_parameters.len());
+    for (index, parameters) in update_chapter_parameters.iter().enumerate() {
+        let title = parameters.title.trim();
+        if title.is_empty() || title.chars().count() > MAX_CHAPTER_TITLE_LENGTH {
+            return Err(ChapterValidationError::new(&format!(
+                "The title of chapter {} must have between 1 and {} characters", index + 1, MAX_CHAPTER_TITLE_LENGTH)));
+        }
+        let end_time_in_ms = match parameters.end_time_in_ms {
+            Some(end_time_in_ms) => end_time_in_ms,
+            None => match update_chapter_parameters.get(index + 1) {
+                Some(next) => next.start_time_in_ms,
+                None => container_meta.duration_in_ms,
+            },
+        };
+        if parameters.start_time_in_ms < 0 || end_time_in_ms <= parameters.start_time_in_ms {
+            return Err(ChapterValidationError::new(&format!(
+                "Chapter {} must start at or after 0 ms and end after its start", index + 1)));
+        }
+        if end_time_in_ms > container_meta.duration_in_ms {
+            return Err(ChapterValidationError::new(&format!(
+                "Chapter {} ends after the container's duration of {} ms", index + 1, container_meta.duration_in_ms)));
+        }
+        if let Some(previous) = chapters.last() {
+            if parameters.start_time_in_ms < previous.end_time_in_ms {
+                return Err(ChapterValidationError::new(&format!(
+                    "Chapter {} starts before chapter {} ends", index + 1, index)));
+            }
+        }
+
+        let mut chapter = <Chapter as models::model::Model>::new();
+        chapter.id = Uuid::new_v4();
+        chapter.container_meta_id = container_meta.id;
+        chapter.tenant_id = container_meta.tenant_id.clone();
+        chapter.title = String::from(title);
+        chapter.start_time_in_ms = parameters.start_time_in_ms;
+        chapter.end_time_in_ms = end_time_in_ms;
+        chapters.push(chapter);
+    }
+    Ok(chapters)
+}
+
+/// Renders the chapters as a WebVTT chapters track, e.g. for a `<track kind="chapters">` element.
+pub fn to_webvtt(chapters: &[Chapter]) -> String {
+    let mut webvtt = String::from("WEBVTT\n");
+    for (index, chapter) in chapters.iter().enumerate() {
+        // a cue ends at the first blank line and its payload must not contain the timing arrow
+        let title = chapter.title.split_whitespace().collect::<Vec<&str>>().join(" ").replace("-->", "->");
+        webvtt.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            index + 1,
+            to_webvtt_timestamp(chapter.start_time_in_ms),
+            to_webvtt_timestamp(chapter.end_time_in_ms),
+            title,
+        ));
+    }
+    webvtt
+}
+
+fn to_webvtt_timestamp(time_in_ms: i64) -> String {
+    let time_in_ms = time_in_ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        time_in_ms / 3_600_000,
+        time_in_ms / 60_000 % 60,
+        time_in_ms / 1000 % 60,
+        time_in_ms % 1000,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+
+    fn update_chapter_parameters(title: &str, start_time_in_ms: i64, end_time_in_ms: Option<i64>) -> UpdateChapterParameters {
+        UpdateChapterParameters { title: String::from(title), start_time_in_ms, end_time_in_ms }
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_chapters() {
+        let mut container_meta = ContainerMeta::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.duration_in_ms = 3_725_500;
+
+        // open ends last until the next chapter or the end of the container
+        let chapters = to_chapters(&container_meta, &[
+            update_chapter_parameters(" Intro ", 0, None),
+            update_chapter_parameters("Main --> part\n2", 25_000, Some(3_600_000)),
+            update_chapter_parameters("Credits", 3_600_000, None),
+        ]).unwrap();
+        assert_eq!(chapters.iter().map(|chapter| (chapter.start_time_in_ms, chapter.end_time_in_ms)).collect::<Vec<(i64, i64)>>(),
+            vec![(0, 25_000), (25_000, 3_600_000), (3_600_000, 3_725_500)]);
+        assert!(chapters.iter().all(|chapter| chapter.container_meta_id == container_meta.id));
+        assert_eq!(chapters[0].title, "Intro");
+        assert_eq!(to_webvtt(&chapters), "WEBVTT\n\
+            \n1\n00:00:00.000 --> 00:00:25.000\nIntro\n\
+            \n2\n00:00:25.000 --> 01:00:00.000\nMain -> part 2\n\
+            \n3\n01:00:00.000 --> 01:02:05.500\nCredits\n");
+        assert!(to_chapters(&container_meta, &[]).unwrap().is_empty());
+        assert_eq!(to_webvtt(&[]), "WEBVTT\n");
+
+        // overlapping, unordered, exceeding and untitled chapters are rejected
+        assert!(to_chapters(&container_meta, &[
+            update_chapter_parameters("Intro", 0, Some(30_000)),
+            update_chapter_parameters("Main", 25_000, None),
+        ]).is_err());
+        assert!(to_chapters(&container_meta, &[
+            update_chapter_parameters("Main", 25_000, None),
+            update_chapter_parameters("Intro", 0, None),
+        ]).is_err());
+        assert!(to_chapters(&container_meta, &[update_chapter_parameters("Main", 0, Some(3_725_501))]).is_err());
+        assert!(to_chapters(&container_meta, &[update_chapter_parameters(" ", 0, None)]).is_err());
+        assert!(to_chapters(&container_meta, &[update_chapter_parameters("Intro", -1, None)]).is_err());
+    }
+}
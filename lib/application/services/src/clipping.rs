@@ -0,0 +1,71 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use parsers::mp4_clipper::{ClipRange, Mp4ClipError};
+use std::fmt;
+
+/// Returned when a clip cannot be cut without re-encoding, e.g. because of its range or the codecs of the source.
+#[derive(Debug)]
+pub struct ClipRejectedError {
+    pub code: String,
+    pub reason: String,
+}
+
+impl From<Mp4ClipError> for ClipRejectedError {
+    fn from(err: Mp4ClipError) -> Self {
+        ClipRejectedError { code: String::from(err.code), reason: err.reason }
+    }
+}
+
+impl fmt::Display for ClipRejectedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for ClipRejectedError {}
+
+/// File name of a clip, e.g. `forest-clip-1000-3000.mp4` for the clip from 1 to 3 seconds of `forest.mp4`.
+pub fn clip_file_name(original_file_name: &str, clip_range: &ClipRange) -> String {
+    let stem = original_file_name.rsplit_once('.').map_or(original_file_name, |(stem, _)| stem);
+    match stem.is_empty() {
+        true => format!("clip-{}-{}.mp4", clip_range.start_time_in_ms, clip_range.end_time_in_ms),
+        false => format!("{}-clip-{}-{}.mp4", stem, clip_range.start_time_in_ms, clip_range.end_time_in_ms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_clip_file_name() {
+        let clip_range = ClipRange { start_time_in_ms: 1000, end_time_in_ms: 3000 };
+        assert_eq!(clip_file_name("forest.mp4", &clip_range), "forest-clip-1000-3000.mp4");
+        assert_eq!(clip_file_name("forest.final.mov", &clip_range), "forest.final-clip-1000-3000.mp4");
+        assert_eq!(clip_file_name("forest", &clip_range), "forest-clip-1000-3000.mp4");
+        assert_eq!(clip_file_name("", &clip_range), "clip-1000-3000.mp4");
+    }
+}
@@ -0,0 +1,59 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+/// Outcome of checking a single dependency, `error` being `None` if it is reachable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCheck {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    pub fn new<E: std::fmt::Display>(name: &str, result: Result<(), E>) -> Self {
+        HealthCheck {
+            name: String::from(name),
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_health_check() {
+        let health_check = HealthCheck::new::<String>("database", Ok(()));
+        assert!(health_check.is_healthy());
+
+        let health_check = HealthCheck::new("blob_storage", Err("connection refused"));
+        assert!(!health_check.is_healthy());
+        assert_eq!(health_check.error, Some(String::from("connection refused")));
+    }
+}
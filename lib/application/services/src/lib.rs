@@ -22,6 +22,26 @@
 
 
 pub mod upload_parameters;
-pub mod download_parameters;
-pub mod delete_parameters;
+pub mod thumbnail_parameters;
+pub mod purge_parameters;
+pub mod backfill_parameters;
+pub mod transcoding_parameters;
+pub mod audio_analysis_parameters;
+pub mod metadata_parameters;
+pub mod quota_parameters;
+pub mod rate_limit_parameters;
+pub mod app_config;
+pub mod update_parameters;
 pub mod mutimedia_management_service;
+pub mod authentication_service;
+pub mod authorization;
+pub mod quota;
+pub mod chapters;
+pub mod clipping;
+pub mod track_export;
+pub mod versioning;
+pub mod health;
+pub mod rate_limiter;
+pub mod upload_validation;
+pub mod scanning;
+pub mod byte_range;
@@ -26,56 +26,303 @@ extern crate parsers;
 extern crate data_access;
 extern crate models;
 
-use log::info;
-use models::{schema::container_meta::date_time_created, model};
+use tracing::{info, warn};
+use models::{schema::container_meta::date_time_created, model, principal::{Principal, Role}, tenant::DEFAULT_TENANT_ID};
 use uuid::Uuid;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
 
-use crate::{upload_parameters, download_parameters, delete_parameters::{self, DeleteBlobParameters}};
+use crate::{upload_parameters, thumbnail_parameters::ThumbnailParameters, purge_parameters::PurgeParameters, backfill_parameters::BackfillParameters,
+    update_parameters::{UpdateMetaParameters, UpdateChapterParameters, ListParameters}, chapters, authorization::{self, AuthorizationError, Permission},
+    quota, quota_parameters::QuotaParameters, app_config::AppConfig, health::HealthCheck, upload_validation::UploadRejection,
+    scanning::{self, ScanUnavailableError}, transcoding_parameters::TranscodingParameters,
+    audio_analysis_parameters::AudioAnalysisParameters, metadata_parameters::MetadataParameters};
+use scanners::content_scanner::{ContentScanner, NoopContentScanner, ScanVerdict};
+use connectors::envelope_encryption::EnvelopeEncryption;
+use processors::transcoder::RenditionProfile;
+use processors::audio_analyzer::UnsupportedAudioCodecError;
+use parsers::mp4_metadata::{self, CoverArt, EmbeddedMetadata};
+use parsers::mp4_faststart;
+use parsers::mp4_clipper::{self, Mp4ClipError};
+use crate::clipping::{self, ClipRejectedError};
+use parsers::mp4_demuxer::{self, Mp4DemuxError, TrackType};
+use crate::track_export::{self, ExportedTrackFile, TrackExportRejectedError};
+use crate::versioning;
+use crate::byte_range::{ByteRange, RangeNotSatisfiableError};
+
+// rejected uploads are kept below `<tenant prefix>/quarantine/<id>/`, apart from the `<container_meta_id>/` prefixes of the catalog
+pub const QUARANTINE_PREFIX: &str = "quarantine";
+
+// values of `container_meta.transcoding_status` set by the transcoding job, new containers and new versions are `pending`
+pub const TRANSCODING_PENDING: &str = "pending";
+pub const TRANSCODING_COMPLETED: &str = "completed";
+pub const TRANSCODING_FAILED: &str = "failed";
+pub const TRANSCODING_SKIPPED: &str = "skipped";
+
+// a container along with the renditions encoded from its file
+pub type ContainerRenditions = (models::container_meta::ContainerMeta, Vec<models::rendition::Rendition>);
+
+// a downloaded container along with the bytes and, if only a part of the file was downloaded, its inclusive range
+pub type ContainerDownload = (models::container_meta::ContainerMeta, Bytes, Option<(u64, u64)>);
+
+// values of `audio_track.analysis_status` set by the audio analysis job, new audio tracks are `pending`
+pub const AUDIO_ANALYSIS_COMPLETED: &str = "completed";
+pub const AUDIO_ANALYSIS_FAILED: &str = "failed";
+pub const AUDIO_ANALYSIS_UNSUPPORTED: &str = "unsupported";
 
 #[derive(Clone)]
 pub struct MutimediaManagementService {
     pub blob_storage_connector: Option<connectors::aws_s3_bucket_connector::AwsS3BucketConnector>,
     pub mp4_parser: Option<parsers::mp4_parser::Mp4Parser>,
+    pub mp4_validator: Option<parsers::mp4_validator::Mp4Validator>,
     pub sql_data_access: Option<data_access::psql_data_access_async::PsqlDataAccess>,
+    pub thumbnail_generator: Option<processors::thumbnail_generator::ThumbnailGenerator>,
+    pub transcoder: Option<processors::transcoder::Transcoder>,
+    pub audio_analyzer: Option<processors::audio_analyzer::AudioAnalyzer>,
+    pub content_scanner: Option<Arc<dyn ContentScanner>>,
+    pub quota_parameters: Option<QuotaParameters>,
+    pub metadata_parameters: Option<MetadataParameters>,
 }
 
 impl MutimediaManagementService {
-    pub async fn new() -> Self {
-        MutimediaManagementService {
-            blob_storage_connector: Some(connectors::aws_s3_bucket_connector::AwsS3BucketConnector::new().await.unwrap()),
+    pub async fn new(app_config: &AppConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut blob_storage_connector = connectors::aws_s3_bucket_connector::AwsS3BucketConnector::new(&app_config.storage.aws_s3).await?;
+        if let Some(envelope_encryption) = EnvelopeEncryption::from_config(&app_config.storage.encryption)? {
+            blob_storage_connector = blob_storage_connector.with_envelope_encryption(envelope_encryption);
+        }
+        Ok(MutimediaManagementService {
+            blob_storage_connector: Some(blob_storage_connector),
             mp4_parser: Some(parsers::mp4_parser::Mp4Parser::new()),
-            sql_data_access: Some(data_access::psql_data_access_async::PsqlDataAccess::new().await.unwrap()),
+            mp4_validator: Some(parsers::mp4_validator::Mp4Validator::new()),
+            sql_data_access: Some(data_access::psql_data_access_async::PsqlDataAccess::new(&app_config.database).await?),
+            // thumbnails are only generated if a keyframe decoder is configured
+            thumbnail_generator: Some(&app_config.thumbnails.keyframe_decoder_program)
+                .filter(|keyframe_decoder_program| !keyframe_decoder_program.is_empty())
+                .map(|keyframe_decoder_program| processors::thumbnail_generator::ThumbnailGenerator::new(
+                    Arc::new(processors::keyframe_decoder::CommandKeyframeDecoder::with_program(keyframe_decoder_program)))),
+            transcoder: Some(processors::transcoder::Transcoder::new(
+                Arc::new(processors::transcoder::CommandVideoEncoder::new()))),
+            audio_analyzer: Some(processors::audio_analyzer::AudioAnalyzer::new()),
+            content_scanner: Some(scanning::content_scanner_from(&app_config.scanning)),
+            quota_parameters: Some(app_config.quota.clone()),
+            metadata_parameters: Some(app_config.metadata.clone()),
+        })
+    }
+
+    /// Whether posters and sprite sheets are generated, which requires a configured keyframe decoder.
+    pub fn is_thumbnail_generation_enabled(&self) -> bool {
+        self.thumbnail_generator.is_some()
+    }
+
+    /// Samples gauges which are not updated by the operations themselves, called before metrics are rendered.
+    pub fn record_metrics(&self) {
+        self.sql_data_access.as_ref().unwrap().record_pool_metrics();
+    }
+
+    /// Checks the database, the blob storage and the content scanner if configured concurrently, each bounded by `timeout`.
+    pub async fn check_readiness(&self, timeout: std::time::Duration) -> Vec<HealthCheck> {
+        let content_scanner = self.content_scanner.as_ref().unwrap().clone();
+        let (database_result, blob_storage_result, content_scanner_result) = tokio::join!(
+            tokio::time::timeout(timeout, self.sql_data_access.as_ref().unwrap().check_health()),
+            tokio::time::timeout(timeout, self.blob_storage_connector.as_ref().unwrap().check_health()),
+            tokio::time::timeout(timeout, tokio::task::spawn_blocking(move || content_scanner.check_health())),
+        );
+        let mut health_checks = vec![
+            HealthCheck::new("database", match database_result {
+                Ok(result) => result.map_err(|err| err.to_string()),
+                Err(_) => Err(String::from("timed out")),
+            }),
+            HealthCheck::new("blob_storage", match blob_storage_result {
+                Ok(result) => result.map(|_| ()).map_err(|err| format!("{:?}", err)),
+                Err(_) => Err(String::from("timed out")),
+            }),
+        ];
+        if self.content_scanner.as_ref().unwrap().name() != NoopContentScanner::NAME {
+            health_checks.push(HealthCheck::new("content_scanner", match content_scanner_result {
+                Ok(Ok(result)) => result.map_err(|err| err.to_string()),
+                Ok(Err(err)) => Err(err.to_string()),
+                Err(_) => Err(String::from("timed out")),
+            }));
         }
+        health_checks
     }
 
+    /// Restricts database access to the rows of the tenant, enforced by row level security.
+    fn sql_data_access_for(&self, tenant_id: &str) -> data_access::psql_data_access_async::PsqlDataAccess {
+        self.sql_data_access.as_ref().unwrap().for_tenant(tenant_id)
+    }
+
+    /// Blobs of the default tenant stay at the root of the bucket, so containers uploaded
+    /// before multi-tenancy remain reachable. Other tenants store blobs below `<tenant_id>/`.
+    fn blob_storage_connector_for(&self, tenant_id: &str) -> connectors::aws_s3_bucket_connector::AwsS3BucketConnector {
+        let blob_storage_connector = self.blob_storage_connector.as_ref().unwrap();
+        match tenant_id == DEFAULT_TENANT_ID {
+            true => blob_storage_connector.clone(),
+            false => blob_storage_connector.for_tenant(tenant_id),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), blob_name = %upload_file_parameters.blob_name, container_meta_id = tracing::field::Empty))]
     pub async fn upload_blob_from_file_and_create_metadata(        
         &self,
+        principal: &Principal,
         upload_file_parameters: &upload_parameters::UploadFileParameters,
         upload_meta_parameters: &upload_parameters::UploadMetaParameters) -> Result<models::container_meta::ContainerMeta, Box<dyn std::error::Error>> {
         
+        if !principal.has_role(Role::Uploader) {
+            return Err(Box::new(AuthorizationError::new("Uploading requires the uploader role")));
+        }
+        // rejected before any byte is stored. Concurrent uploads may overshoot a quota by their own size.
+        let file_size_bytes = std::fs::metadata(&upload_file_parameters.file_name)?.len() as i64;
+        self.check_quota(principal.resolved_tenant_id(), &principal.subject, &upload_file_parameters.blob_name, file_size_bytes).await?;
+
         let container_meta_id = Uuid::new_v4(); // leading element
+        tracing::Span::current().record("container_meta_id", tracing::field::display(container_meta_id));
+        info!("Uploading {} as container_meta_id {} on behalf of {}", upload_file_parameters.blob_name, container_meta_id, principal.subject);
 
-        let updated_blob_name = container_meta_id.to_string() + "/" + &upload_file_parameters.blob_name;
-        self.blob_storage_connector
-            .as_ref()
-            .unwrap()
-            .upload_blob(&updated_blob_name, &upload_file_parameters.file_name)
+        let object_key = versioning::versioned_object_key(&container_meta_id, 1, &upload_file_parameters.blob_name);
+        let (mut container_meta, embedded_metadata) = self.store_file(
+            principal, upload_file_parameters, file_size_bytes, container_meta_id, &object_key).await?;
+
+        // container (mp4, mov)
+        container_meta.title = match upload_meta_parameters.title.trim().is_empty() {
+            true => embedded_metadata.title.clone().unwrap_or_default(),
+            false => upload_meta_parameters.title.clone(),
+        };
+        container_meta.description = match upload_meta_parameters.description.trim().is_empty() {
+            true => embedded_metadata.description.clone().or(embedded_metadata.comment.clone()).unwrap_or_default(),
+            false => upload_meta_parameters.description.clone(),
+        };
+        container_meta.date_time_created = Utc::now();
+        container_meta.date_time_updated = container_meta.date_time_created;
+        container_meta.tags = match upload_meta_parameters.tags.iter().flatten().all(|tag| tag.trim().is_empty()) {
+            true => embedded_metadata.tags.iter().cloned().map(Some).collect(),
+            false => upload_meta_parameters.tags.clone(),
+        };
+        container_meta.media_created_at = embedded_metadata.creation_time;
+        container_meta.parent_container_id = upload_meta_parameters.parent_container_id;
+        container_meta.owner_id = principal.subject.clone();
+
+        self.sql_data_access_for(principal.resolved_tenant_id())
+        .insert_container_meta(&container_meta).await?;
+        self.sql_data_access_for(principal.resolved_tenant_id())
+        .insert_container_version(&versioning::to_container_version(&container_meta, &principal.subject)).await?;
+
+        let mut usage_entry = <models::usage_entry::UsageEntry as model::Model>::new();
+        usage_entry.id = Uuid::new_v4();
+        usage_entry.tenant_id = container_meta.tenant_id.clone();
+        usage_entry.subject = container_meta.owner_id.clone();
+        usage_entry.container_meta_id = container_meta_id;
+        usage_entry.bytes_delta = container_meta.file_size_bytes;
+        usage_entry.container_count_delta = 1;
+        usage_entry.duration_in_ms_delta = container_meta.duration_in_ms;
+        usage_entry.date_time_created = Utc::now();
+        self.sql_data_access_for(principal.resolved_tenant_id())
+        .insert_usage_entry(&usage_entry).await?;
+        metrics::counter!("mms_uploads_total").increment(1);
+        metrics::counter!("mms_upload_bytes_total").increment(file_size_bytes as u64);
+
+        // previews are optional, a failure must not discard an otherwise valid upload
+        if container_meta.video_track_id != Uuid::nil() {
+            if let Err(err) = self.create_thumbnails_from_file(
+                &container_meta, &upload_file_parameters.file_name, &ThumbnailParameters::new()).await {
+                warn!("Failed to create thumbnails for container_meta_id {}: {}", container_meta.id, err);
+            }
+        }
+        if let Err(err) = self.create_chapters_from_file(&container_meta, &upload_file_parameters.file_name).await {
+            warn!("Failed to store the chapters of container_meta_id {}: {}", container_meta.id, err);
+        }
+        if let Some(cover_art) = embedded_metadata.cover_art {
+            if let Err(err) = self.create_cover_thumbnail(&container_meta, cover_art).await {
+                warn!("Failed to store the cover art of container_meta_id {}: {}", container_meta.id, err);
+            }
+        }
+
+        Ok(container_meta)
+    }
+
+    // rejects a file exceeding the quota of the tenant or of the subject it is charged to, before any byte is stored
+    async fn check_quota(&self, tenant_id: &str, subject: &str, blob_name: &str, file_size_bytes: i64) -> Result<(), Box<dyn std::error::Error>> {
+        let tenant_usage = self.sql_data_access_for(tenant_id)
+            .get_usage(tenant_id, None, None)
             .await?;
-        
-        // Parse information from the MP4, MOV container and assign attributes to the tuple members `let (mut container_meta, ...) = ...`
-        let (mut container_meta, video_track, audio_track, subtitle_track) = 
-            self.mp4_parser.as_ref().unwrap().parse_from_file(&upload_file_parameters.file_name).unwrap();
+        let user_usage = self.sql_data_access_for(tenant_id)
+            .get_usage(tenant_id, Some(subject), None)
+            .await?;
+        if let Err(err) = quota::check_quota(self.quota_parameters.as_ref().unwrap(), &tenant_usage, &user_usage, file_size_bytes) {
+            warn!("Rejected upload of {} charged to {}: {}", blob_name, subject, err);
+            return Err(Box::new(err));
+        }
+        Ok(())
+    }
+
+    // scans, validates and parses the file, quarantining it if it is rejected, then stores it as `object_key` and
+    // inserts its tracks. Returns the parsed container for the caller to complete, along with the embedded metadata.
+    async fn store_file(
+        &self,
+        principal: &Principal,
+        upload_file_parameters: &upload_parameters::UploadFileParameters,
+        file_size_bytes: i64,
+        container_meta_id: Uuid,
+        object_key: &str) -> Result<(models::container_meta::ContainerMeta, EmbeddedMetadata), Box<dyn std::error::Error>> {
+        // Scan for malware between persisting the temp file and handing it over to the blob storage
+        let scan_verdict = self.scan_file(&upload_file_parameters.file_name).await?;
+        if let ScanVerdict::Infected { signature } = &scan_verdict {
+            let reason = format!("Malware signature {} found", signature);
+            self.quarantine_upload(principal, upload_file_parameters, file_size_bytes, scan_verdict.as_str(), &reason).await?;
+            return Err(Box::new(UploadRejection::InvalidContent { reason_code: String::from(scan_verdict.as_str()), reason }));
+        }
+
+        // Validate and parse the MP4, MOV container before storing it, so malformed files never reach the catalog
+        let parsed = self.mp4_validator.as_ref().unwrap().validate_file(&upload_file_parameters.file_name)
+            .map_err(|err| (String::from(err.code), err.reason))
+            .and_then(|_| self.mp4_parser.as_ref().unwrap().parse_from_file(&upload_file_parameters.file_name)
+                .map_err(|err| (String::from("unparsable"), format!("The file could not be parsed: {}", err))));
+        let (mut container_meta, video_track, audio_track, subtitle_track) = match parsed {
+            Ok(parsed) => parsed,
+            Err((reason_code, reason)) => {
+                self.quarantine_upload(principal, upload_file_parameters, file_size_bytes, &reason_code, &reason).await?;
+                return Err(Box::new(UploadRejection::InvalidContent { reason_code, reason }));
+            }
+        };
+
+        // metadata embedded by the producer prefills what the uploader left empty, a file without is still accepted
+        let embedded_metadata = mp4_metadata::read_metadata_from_file(&upload_file_parameters.file_name)
+            .unwrap_or_else(|err| {
+                warn!("Failed to read the embedded metadata of {}: {}", upload_file_parameters.blob_name, err);
+                EmbeddedMetadata::default()
+            });
+
+        // with the moov box in front of the media data players start before the download completes,
+        // a file which cannot be rewritten is stored as uploaded
+        let faststart_file_name = upload_file_parameters.file_name.clone() + ".faststart";
+        let (stored_file_name, web_optimized) = match self.faststart_file(&upload_file_parameters.file_name, &faststart_file_name).await {
+            Ok(true) => (faststart_file_name.as_str(), true),
+            Ok(false) => (upload_file_parameters.file_name.as_str(), true),
+            Err(err) => {
+                warn!("Failed to move the moov box of {} in front of the media data: {}", upload_file_parameters.blob_name, err);
+                (upload_file_parameters.file_name.as_str(), false)
+            }
+        };
+
+        let upload_result = self.blob_storage_connector_for(principal.resolved_tenant_id())
+            .upload_blob(object_key, stored_file_name)
+            .await;
+        if stored_file_name == faststart_file_name {
+            if let Err(err) = std::fs::remove_file(&faststart_file_name) {
+                warn!("Failed to delete the temporary file {}: {}", faststart_file_name, err);
+            }
+        }
+        upload_result?;
 
         // video data (h264)
         if video_track != None {
             let mut video_track_unwrapped = video_track.unwrap(); 
             video_track_unwrapped.id = Uuid::new_v4();
             video_track_unwrapped.container_meta_id = container_meta_id;
-            self.sql_data_access
-            .as_ref()
-            .unwrap()
+            video_track_unwrapped.tenant_id = String::from(principal.resolved_tenant_id());
+            self.sql_data_access_for(principal.resolved_tenant_id())
             .insert_video_track(&video_track_unwrapped).await?;
             container_meta.video_track_id = video_track_unwrapped.id;
         }
@@ -85,9 +332,8 @@ impl MutimediaManagementService {
             let mut audio_track_unwrapped = audio_track.unwrap(); 
             audio_track_unwrapped.id = Uuid::new_v4();
             audio_track_unwrapped.container_meta_id = container_meta_id;
-            self.sql_data_access
-            .as_ref()
-            .unwrap()
+            audio_track_unwrapped.tenant_id = String::from(principal.resolved_tenant_id());
+            self.sql_data_access_for(principal.resolved_tenant_id())
             .insert_audio_track(&audio_track_unwrapped).await?;
             container_meta.audio_track_id = audio_track_unwrapped.id;
         }
@@ -97,67 +343,1311 @@ impl MutimediaManagementService {
             let mut subtitle_track_unwrapped = subtitle_track.unwrap(); 
             subtitle_track_unwrapped.id = Uuid::new_v4();
             subtitle_track_unwrapped.container_meta_id = container_meta_id;
-            self.sql_data_access
-            .as_ref()
-            .unwrap()
+            subtitle_track_unwrapped.tenant_id = String::from(principal.resolved_tenant_id());
+            self.sql_data_access_for(principal.resolved_tenant_id())
             .insert_subtitlte_track(&subtitle_track_unwrapped).await?;
             container_meta.subtitle_track_id = subtitle_track_unwrapped.id;
         }
 
-        // container (mp4, mov)
         container_meta.id = container_meta_id;
-        container_meta.title = container_meta.title;
-        container_meta.description = container_meta.description;
-        container_meta.date_time_created = Utc::now();
-        container_meta.date_time_updated = container_meta.date_time_created;
-        container_meta.tags = upload_meta_parameters.tags.clone();
+        container_meta.web_optimized = web_optimized;
+        container_meta.original_file_name = upload_file_parameters.blob_name.clone();
+        container_meta.storage_backend = String::from(connectors::aws_s3_bucket_connector::AwsS3BucketConnector::STORAGE_BACKEND);
+        container_meta.object_key = String::from(object_key);
+        container_meta.tenant_id = String::from(principal.resolved_tenant_id());
+        container_meta.scan_verdict = String::from(scan_verdict.as_str());
+        Ok((container_meta, embedded_metadata))
+    }
 
-        self.sql_data_access
-        .as_ref()
-        .unwrap()
-        .insert_container_meta(&container_meta).await?;
+    // writes a copy of `source` with the moov box in front of the media data to `target`,
+    // `false` if `source` is web-optimized already and nothing was written
+    async fn faststart_file(&self, source: &str, target: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        let (source, target) = (String::from(source), String::from(target));
+        let span = tracing::Span::current();
+        let rewritten = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            mp4_faststart::faststart_file(&source, &target).map_err(|err| err.to_string())
+        })).await??;
+        if rewritten {
+            metrics::counter!("mms_faststart_rewrites_total").increment(1);
+        }
+        Ok(rewritten)
+    }
 
-        Ok(container_meta)
+    /// Stores the chapter markers embedded in the file, chapters exceeding the container are clipped.
+    async fn create_chapters_from_file(&self, container_meta: &models::container_meta::ContainerMeta, file_name: &str)
+        -> Result<Vec<models::chapter::Chapter>, Box<dyn std::error::Error>> {
+        let update_chapter_parameters: Vec<UpdateChapterParameters> = self.mp4_parser.as_ref().unwrap()
+            .read_chapters(file_name)?
+            .into_iter()
+            // drops empty chapters, e.g. markers sharing their start
+            .filter(|chapter| chapter.start_time_in_ms < chapter.end_time_in_ms.min(container_meta.duration_in_ms))
+            .map(|chapter| UpdateChapterParameters {
+                title: chapter.title,
+                start_time_in_ms: chapter.start_time_in_ms,
+                end_time_in_ms: Some(chapter.end_time_in_ms.min(container_meta.duration_in_ms)),
+            })
+            .collect();
+        if update_chapter_parameters.is_empty() {
+            return Ok(Vec::new());
+        }
+        let chapters = chapters::to_chapters(container_meta, &update_chapter_parameters)?;
+        let chapters = self.sql_data_access_for(&container_meta.tenant_id).insert_chapters(&chapters).await?;
+        info!("Successfully stored {} chapters of container_meta_id {}", chapters.len(), container_meta.id);
+        Ok(chapters)
+    }
+
+    /// Stores the cover art embedded in the file as the `cover.jpg` or `cover.png` thumbnail.
+    async fn create_cover_thumbnail(&self, container_meta: &models::container_meta::ContainerMeta, cover_art: CoverArt)
+        -> Result<models::thumbnail::Thumbnail, Box<dyn std::error::Error>> {
+        let name = match cover_art.mime_type.as_str() {
+            "image/png" => "cover.png",
+            _ => "cover.jpg",
+        };
+        let mut thumbnail = <models::thumbnail::Thumbnail as model::Model>::new();
+        thumbnail.id = Uuid::new_v4();
+        thumbnail.container_meta_id = container_meta.id;
+        thumbnail.tenant_id = container_meta.tenant_id.clone();
+        thumbnail.name = String::from(name);
+        thumbnail.blob_name = container_meta.id.to_string() + "/thumbnails/" + name;
+        thumbnail.mime_type = cover_art.mime_type;
+        thumbnail.width = cover_art.width as i32;
+        thumbnail.height = cover_art.height as i32;
+
+        self.blob_storage_connector_for(&container_meta.tenant_id)
+            .upload_bytes(&thumbnail.blob_name, cover_art.bytes)
+            .await?;
+        let thumbnail = self.sql_data_access_for(&container_meta.tenant_id).insert_thumbnail(&thumbnail).await?;
+        info!("Successfully stored the cover art of container_meta_id {}", container_meta.id);
+        Ok(thumbnail)
+    }
+
+    /// Runs the content scanner on a blocking thread. Without a verdict the upload is refused rather than published unscanned.
+    async fn scan_file(&self, file_name: &str) -> Result<ScanVerdict, Box<dyn std::error::Error>> {
+        let content_scanner = self.content_scanner.as_ref().unwrap().clone();
+        let scanner = content_scanner.name();
+        let file_name = String::from(file_name);
+        let span = tracing::Span::current();
+        let started = std::time::Instant::now();
+        let result = tokio::task::spawn_blocking(move || span.in_scope(|| content_scanner.scan_file(&file_name))).await?;
+        metrics::histogram!("mms_content_scan_duration_seconds", "scanner" => scanner).record(started.elapsed().as_secs_f64());
+        let verdict = match &result {
+            Ok(scan_verdict) => scan_verdict.as_str(),
+            Err(_) => "error",
+        };
+        metrics::counter!("mms_content_scans_total", "scanner" => scanner, "verdict" => verdict).increment(1);
+        result.map_err(|err| {
+            warn!("Content scanner {} failed: {}", scanner, err);
+            Box::new(ScanUnavailableError::new(&format!("The content scanner failed: {}", err))) as Box<dyn std::error::Error>
+        })
+    }
+
+    /// Stores a rejected upload below the quarantine prefix and records why it was rejected.
+    async fn quarantine_upload(
+        &self,
+        principal: &Principal,
+        upload_file_parameters: &upload_parameters::UploadFileParameters,
+        file_size_bytes: i64,
+        reason_code: &str,
+        reason: &str) -> Result<models::quarantined_upload::QuarantinedUpload, Box<dyn std::error::Error>> {
+        let mut quarantined_upload = <models::quarantined_upload::QuarantinedUpload as model::Model>::new();
+        quarantined_upload.id = Uuid::new_v4();
+        quarantined_upload.tenant_id = String::from(principal.resolved_tenant_id());
+        quarantined_upload.subject = principal.subject.clone();
+        quarantined_upload.original_file_name = upload_file_parameters.blob_name.clone();
+        quarantined_upload.object_key = format!("{}/{}/{}", QUARANTINE_PREFIX, quarantined_upload.id, upload_file_parameters.blob_name);
+        quarantined_upload.file_size_bytes = file_size_bytes;
+        quarantined_upload.reason_code = String::from(reason_code);
+        quarantined_upload.reason = String::from(reason);
+        quarantined_upload.date_time_created = Utc::now();
+        warn!("Quarantining upload of {} on behalf of {} as {}: {}",
+            upload_file_parameters.blob_name, principal.subject, quarantined_upload.object_key, reason);
+
+        self.blob_storage_connector_for(principal.resolved_tenant_id())
+            .upload_blob(&quarantined_upload.object_key, &upload_file_parameters.file_name)
+            .await?;
+        let quarantined_upload = self.sql_data_access_for(principal.resolved_tenant_id())
+            .insert_quarantined_upload(&quarantined_upload).await?;
+        metrics::counter!("mms_quarantined_uploads_total", "reason_code" => String::from(reason_code)).increment(1);
+        Ok(quarantined_upload)
+    }
+
+    /// Lists the uploads of the principal's tenant rejected by content validation, restricted to admins.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id()))]
+    pub async fn retrieve_quarantined_uploads(&self, principal: &Principal, list_parameters: &ListParameters)
+        -> Result<Vec<models::quarantined_upload::QuarantinedUpload>, Box<dyn std::error::Error>> {
+        if !principal.has_role(Role::Admin) {
+            return Err(Box::new(AuthorizationError::new("Listing quarantined uploads requires the admin role")));
+        }
+        let quarantined_uploads = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_quarantined_uploads(principal.resolved_tenant_id(), list_parameters.limit, list_parameters.offset)
+            .await?;
+        Ok(quarantined_uploads)
+    }
+
+    #[tracing::instrument(skip_all, fields(container_meta_id = %container_meta.id))]
+    pub async fn create_thumbnails_from_file(
+        &self,
+        container_meta: &models::container_meta::ContainerMeta,
+        file_name: &str,
+        thumbnail_parameters: &ThumbnailParameters) -> Result<Vec<models::thumbnail::Thumbnail>, Box<dyn std::error::Error>> {
+
+        let thumbnail_generator = match self.thumbnail_generator.as_ref() {
+            Some(thumbnail_generator) => thumbnail_generator.clone(),
+            None => {
+                info!("No keyframe decoder configured, skipping thumbnails of container_meta_id {}", container_meta.id);
+                return Ok(Vec::new());
+            }
+        };
+        let duration_in_ms = container_meta.duration_in_ms as u64;
+        let poster_time_in_ms = thumbnail_parameters.poster_time_in_ms.min(duration_in_ms);
+        let sprite_interval_in_ms = processors::thumbnail_generator::sprite_interval_in_ms(
+            duration_in_ms, thumbnail_parameters.sprite_interval_in_ms, thumbnail_parameters.sprite_max_frames);
+        let sprite_columns = thumbnail_parameters.sprite_columns;
+        let sprite_tile_width = thumbnail_parameters.sprite_tile_width;
+
+        // decoding is CPU bound and must not block the async runtime
+        let file_name = String::from(file_name);
+        let span = tracing::Span::current();
+        let (poster, sprite_sheet) = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            let poster = thumbnail_generator.generate_poster(&file_name, poster_time_in_ms)
+                .map_err(|err| err.to_string())?;
+            let sprite_sheet = thumbnail_generator.generate_sprite_sheet(
+                &file_name, duration_in_ms, sprite_interval_in_ms, sprite_columns, sprite_tile_width, "sprite.jpg")
+                .map_err(|err| err.to_string())?;
+            Ok::<_, String>((poster, sprite_sheet))
+        })).await??;
+
+        let thumbnail_files = vec![
+            ("poster.jpg", "image/jpeg", poster.width, poster.height, poster.time_in_ms, poster.jpeg_bytes),
+            ("poster.webp", "image/webp", poster.width, poster.height, poster.time_in_ms, poster.webp_bytes),
+            ("sprite.jpg", "image/jpeg", sprite_sheet.width, sprite_sheet.height, 0, sprite_sheet.jpeg_bytes),
+            ("thumbnails.vtt", "text/vtt", sprite_sheet.tile_width, sprite_sheet.tile_height, 0, sprite_sheet.web_vtt.into_bytes()),
+        ];
+
+        let mut thumbnails = Vec::with_capacity(thumbnail_files.len());
+        for (name, mime_type, width, height, time_offset_in_ms, bytes) in thumbnail_files {
+            let mut thumbnail = <models::thumbnail::Thumbnail as model::Model>::new();
+            thumbnail.id = Uuid::new_v4();
+            thumbnail.container_meta_id = container_meta.id;
+            thumbnail.tenant_id = container_meta.tenant_id.clone();
+            thumbnail.name = String::from(name);
+            thumbnail.blob_name = container_meta.id.to_string() + "/thumbnails/" + name;
+            thumbnail.mime_type = String::from(mime_type);
+            thumbnail.width = width as i32;
+            thumbnail.height = height as i32;
+            thumbnail.time_offset_in_ms = time_offset_in_ms as i64;
+
+            self.blob_storage_connector_for(&container_meta.tenant_id)
+                .upload_bytes(&thumbnail.blob_name, bytes)
+                .await?;
+            thumbnails.push(
+                self.sql_data_access_for(&container_meta.tenant_id)
+                .insert_thumbnail(&thumbnail).await?);
+        }
+
+        info!("Successfully created {} thumbnails for container_meta_id {}", thumbnails.len(), container_meta.id);
+        Ok(thumbnails)
     }
 
-    pub async fn retrieve_bytes_from_blob_by_name(&self, download_blob_parameters: &download_parameters::DownloadBlobParameters) 
-        -> Result<Bytes, Box<dyn std::error::Error>> {
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_thumbnails_by_container_meta_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<Vec<models::thumbnail::Thumbnail>>, Box<dyn std::error::Error>> {
+        // thumbnails of soft deleted containers are hidden as well
+        let container_meta = self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await?;
+        if container_meta.is_none() {
+            return Ok(None);
+        }
+        info!("Retrieving thumbnails of container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+
+        let thumbnails = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_thumbnails_by_container_meta_id(container_meta_id)
+            .await?;
+        Ok(Some(thumbnails))
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, name))]
+    pub async fn retrieve_thumbnail_bytes_by_name(&self, principal: &Principal, container_meta_id: &Uuid, name: &str)
+        -> Result<Option<(models::thumbnail::Thumbnail, Bytes)>, Box<dyn std::error::Error>> {
+        let thumbnail = self.retrieve_thumbnails_by_container_meta_id(principal, container_meta_id)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .find(|thumbnail| thumbnail.name == name);
+
+        match thumbnail {
+            Some(thumbnail) => {
+                let get_object_output =
+                    self.blob_storage_connector_for(principal.resolved_tenant_id()).get_object(&thumbnail.blob_name).await?;
+                let bytes = get_object_output
+                    .body
+                    .collect()
+                    .await?
+                    .into_bytes();
+                Ok(Some((thumbnail, bytes)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_renditions_by_container_meta_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<ContainerRenditions>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        info!("Retrieving renditions of container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+
+        let renditions = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_renditions_by_container_meta_id(container_meta_id)
+            .await?;
+        Ok(Some((container_meta, renditions)))
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, name))]
+    pub async fn retrieve_rendition_bytes_by_name(&self, principal: &Principal, container_meta_id: &Uuid, name: &str)
+        -> Result<Option<(models::rendition::Rendition, Bytes)>, Box<dyn std::error::Error>> {
+        let rendition = self.retrieve_renditions_by_container_meta_id(principal, container_meta_id)
+            .await?
+            .and_then(|(_, renditions)| renditions.into_iter().find(|rendition| rendition.name == name));
+
+        match rendition {
+            Some(rendition) => {
+                let get_object_output =
+                    self.blob_storage_connector_for(principal.resolved_tenant_id()).get_object(&rendition.blob_name).await?;
+                let bytes = get_object_output
+                    .body
+                    .collect()
+                    .await?
+                    .into_bytes();
+                Ok(Some((rendition, bytes)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_chapters_by_container_meta_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<Vec<models::chapter::Chapter>>, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await?.is_none() {
+            return Ok(None);
+        }
+        info!("Retrieving chapters of container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+
+        let chapters = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_chapters_by_container_meta_id(container_meta_id)
+            .await?;
+        Ok(Some(chapters))
+    }
+
+    /// Replaces all chapters of a container, fails with a `ChapterValidationError` if they overlap or exceed the container.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn update_chapters_by_container_meta_id(
+        &self,
+        principal: &Principal,
+        container_meta_id: &Uuid,
+        update_chapter_parameters: &[UpdateChapterParameters],
+    ) -> Result<Option<Vec<models::chapter::Chapter>>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        info!("Updating chapters of container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+
+        let chapters = chapters::to_chapters(&container_meta, update_chapter_parameters)?;
+        self.sql_data_access_for(principal.resolved_tenant_id())
+            .delete_chapters_by_container_meta_id(container_meta_id)
+            .await?;
+        if chapters.is_empty() {
+            return Ok(Some(chapters));
+        }
+        let chapters = self.sql_data_access_for(principal.resolved_tenant_id())
+            .insert_chapters(&chapters)
+            .await?;
+        Ok(Some(chapters))
+    }
+
+    /// Retrieves the audio track of a container including its loudness and waveform, `None` if either does not exist.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, audio_track_id = %audio_track_id))]
+    pub async fn retrieve_audio_track_by_id(&self, principal: &Principal, container_meta_id: &Uuid, audio_track_id: &Uuid)
+        -> Result<Option<models::track::AudioTrack>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        if container_meta.audio_track_id == Uuid::nil() || container_meta.audio_track_id != *audio_track_id {
+            return Ok(None);
+        }
+        info!("Retrieving audio track {} of container_meta_id {} on behalf of {}", audio_track_id, container_meta_id, principal.subject);
+
+        let audio_track = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_audio_track_by_id(audio_track_id)
+            .await?;
+        Ok(Some(audio_track))
+    }
+
+    /// Downloads the uploaded file by the object key persisted on the container, or a previous `version` of it. The returned
+    /// container describes the downloaded version, `None` if the container or the version does not exist. With a `range`
+    /// only the requested part is downloaded, files of unknown size are downloaded completely.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, version))]
+    pub async fn retrieve_bytes_from_blob_by_id(
+        &self,
+        principal: &Principal,
+        container_meta_id: &Uuid,
+        version: Option<i32>,
+        range: Option<ByteRange>) -> Result<Option<ContainerDownload>, Box<dyn std::error::Error>> {
+        let mut container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        if let Some(version) = version.filter(|version| *version != container_meta.version) {
+            match self.sql_data_access_for(principal.resolved_tenant_id()).find_container_version(container_meta_id, version).await? {
+                Some(container_version) => versioning::apply_container_version(&mut container_meta, &container_version),
+                None => return Ok(None),
+            }
+        }
+        if container_meta.storage_backend != connectors::aws_s3_bucket_connector::AwsS3BucketConnector::STORAGE_BACKEND {
+            return Err(format!("Unsupported storage backend {} of container_meta_id {}",
+                container_meta.storage_backend, container_meta_id).into());
+        }
+        info!("Downloading container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+
+        if let Some(range) = range.filter(|_| container_meta.file_size_bytes > 0) {
+            let file_size_bytes = container_meta.file_size_bytes as u64;
+            let (start, end) = range.resolve(file_size_bytes).ok_or(RangeNotSatisfiableError { file_size_bytes })?;
+            let bytes = self.blob_storage_connector_for(&container_meta.tenant_id)
+                .get_object_range(&container_meta.object_key, start, end)
+                .await?;
+            return Ok(Some((container_meta, bytes, Some((start, end)))));
+        }
         let get_object_output = 
-            self.blob_storage_connector.as_ref().unwrap().get_object(
-                &download_blob_parameters.get_blob_name()).await?; // blob name example: <077cd041-45be-4699-8f54-c5c42c8298a3>/<sample.txt>
+            self.blob_storage_connector_for(&container_meta.tenant_id).get_object(
+                &container_meta.object_key).await?; // blob name example: <077cd041-45be-4699-8f54-c5c42c8298a3>/<sample.txt>
         let bytes = get_object_output
             .body
             .collect()
-            .await
-            .unwrap()
+            .await?
             .into_bytes(); 
-        Ok(bytes)
+        Ok(Some((container_meta, bytes, None)))
     }    
 
-    pub async fn delete_blob_and_created_metadata_by_id(&self, delete_blob_parameters: &DeleteBlobParameters) -> Result<(), Box<dyn std::error::Error>> {
-        let blob_name = delete_blob_parameters.get_blob_name();
-        self.blob_storage_connector.as_ref().unwrap().delete_blob(&delete_blob_parameters.get_blob_name()).await?; // delete blob
-        self.blob_storage_connector.as_ref().unwrap().delete_blob(&delete_blob_parameters.container_meta_id).await?; // delete folder
-        let uuid_from_str = Uuid::parse_str(&delete_blob_parameters.container_meta_id).unwrap();
+    /// Hard deletes every blob below the container prefix together with tracks, thumbnails and the container itself.
+    /// Deleting a container which is already (partially) deleted succeeds, so failed deletions can simply be retried.
+    #[tracing::instrument(skip_all, fields(tenant_id, container_meta_id = %container_meta_id))]
+    pub async fn delete_blob_and_created_metadata_by_id(&self, tenant_id: &str, container_meta_id: &Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        // blobs first, the remaining rows keep the container discoverable for a retry
+        self.blob_storage_connector_for(tenant_id)
+            .delete_blobs_by_prefix(&(container_meta_id.to_string() + "/"))
+            .await?; // blob name example: <077cd041-45be-4699-8f54-c5c42c8298a3>/<sample.txt>
 
-        // retrieve container_meta by container metaid
-        let container_meta = 
-            self.sql_data_access.as_ref().unwrap().get_container_meta_by_id(&uuid_from_str).await.unwrap();
-        
+        // the ledger is balanced against its own entries of the container, so a retry does not credit twice
+        if let Some(container_meta) = self.sql_data_access_for(tenant_id)
+            .find_container_meta_by_id_including_deleted(container_meta_id)
+            .await? {
+            let usage = self.sql_data_access_for(tenant_id)
+                .get_usage(tenant_id, Some(&container_meta.owner_id), Some(container_meta_id))
+                .await?;
+            if !usage.is_empty() {
+                let mut usage_entry = <models::usage_entry::UsageEntry as model::Model>::new();
+                usage_entry.id = Uuid::new_v4();
+                usage_entry.tenant_id = String::from(tenant_id);
+                usage_entry.subject = container_meta.owner_id.clone();
+                usage_entry.container_meta_id = *container_meta_id;
+                usage_entry.bytes_delta = -usage.bytes;
+                usage_entry.container_count_delta = -usage.container_count;
+                usage_entry.duration_in_ms_delta = -usage.duration_in_ms;
+                usage_entry.date_time_created = Utc::now();
+                self.sql_data_access_for(tenant_id).insert_usage_entry(&usage_entry).await?;
+            }
+        }
+
+        self.sql_data_access_for(tenant_id).delete_tracks_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_thumbnails_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_renditions_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_chapters_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_container_versions_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_container_grants_by_container_meta_id(container_meta_id).await?;
+        self.sql_data_access_for(tenant_id).delete_container_meta_by_id(container_meta_id).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn soft_delete_container_by_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, false).await?.is_none() {
+            return Ok(None);
+        }
+        info!("Soft deleting container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+        let container_meta = self.sql_data_access_for(principal.resolved_tenant_id())
+            .soft_delete_container_meta_by_id(container_meta_id)
+            .await?;
+        Ok(container_meta)
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn restore_container_by_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, true).await?.is_none() {
+            return Ok(None);
+        }
+        info!("Restoring container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+        let container_meta = self.sql_data_access_for(principal.resolved_tenant_id())
+            .restore_container_meta_by_id(container_meta_id)
+            .await?;
+        Ok(container_meta)
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_container_meta_by_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await
+    }
+
+    /// Lists the containers of the principal's tenant it owns or which are shared with it, admins see all of them.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id()))]
+    pub async fn retrieve_container_metas(&self, principal: &Principal, list_parameters: &ListParameters)
+        -> Result<Vec<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        if !principal.has_role(Role::Viewer) {
+            return Ok(Vec::new());
+        }
+        let container_metas = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_container_metas_visible_to(
+                principal.resolved_tenant_id(),
+                &principal.subject,
+                principal.has_role(Role::Admin),
+                list_parameters.limit,
+                list_parameters.offset)
+            .await?;
+        Ok(container_metas)
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn update_container_meta_by_id(
+        &self,
+        principal: &Principal,
+        container_meta_id: &Uuid,
+        update_meta_parameters: &UpdateMetaParameters) -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        let mut container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+
+        if let Some(title) = &update_meta_parameters.title {
+            container_meta.title = title.clone();
+        }
+        if let Some(description) = &update_meta_parameters.description {
+            container_meta.description = description.clone();
+        }
+        if let Some(tags) = &update_meta_parameters.tags {
+            container_meta.tags = tags.clone();
+        }
+        container_meta.date_time_updated = Utc::now();
+
+        let is_metadata_updated = update_meta_parameters.title.is_some()
+            || update_meta_parameters.description.is_some()
+            || update_meta_parameters.tags.is_some();
+        if self.metadata_parameters.as_ref().unwrap().write_back && is_metadata_updated {
+            // the rewritten file is stored under a new key and the catalog switches to it in one transaction, so a failure
+            // leaves the container pointing to its previous file, which is only deleted once nothing refers to it anymore
+            let previous_object_key = container_meta.object_key.clone();
+            let (object_key, file_size_bytes) = self.write_back_metadata(&container_meta).await?;
+            let mut usage_entry = <models::usage_entry::UsageEntry as model::Model>::new();
+            usage_entry.id = Uuid::new_v4();
+            usage_entry.tenant_id = container_meta.tenant_id.clone();
+            usage_entry.subject = container_meta.owner_id.clone();
+            usage_entry.container_meta_id = container_meta.id;
+            usage_entry.bytes_delta = file_size_bytes - container_meta.file_size_bytes;
+            usage_entry.date_time_created = Utc::now();
+            container_meta.object_key = object_key;
+            container_meta.file_size_bytes = file_size_bytes;
+            let container_meta = match self.sql_data_access_for(principal.resolved_tenant_id())
+                .update_rewritten_container_file_by_id(container_meta_id, &container_meta, &usage_entry)
+                .await {
+                Ok(container_meta) => container_meta,
+                Err(err) => {
+                    if let Err(err) = self.blob_storage_connector_for(&container_meta.tenant_id).delete_blob(&container_meta.object_key).await {
+                        warn!("Failed to delete the rewritten blob {}: {}", container_meta.object_key, err);
+                    }
+                    return Err(err.into());
+                }
+            };
+            if let Err(err) = self.blob_storage_connector_for(&container_meta.tenant_id).delete_blob(&previous_object_key).await {
+                warn!("Failed to delete the previous blob {}: {}", previous_object_key, err);
+            }
+            info!("Updated container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+            return Ok(Some(container_meta));
+        }
+
+        let container_meta = self.sql_data_access_for(principal.resolved_tenant_id())
+            .update_container_meta_by_id(container_meta_id, &container_meta)
+            .await?;
+        info!("Updated container_meta_id {} on behalf of {}", container_meta_id, principal.subject);
+        Ok(Some(container_meta))
+    }
+
+    /// Stores a new version of the container's file and points the container to it, keeping its id, title, description,
+    /// tags and grants. Tracks are parsed from the new file, previous versions stay stored so the container can be rolled back.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, blob_name = %upload_file_parameters.blob_name))]
+    pub async fn replace_container_file_by_id(
+        &self,
+        principal: &Principal,
+        container_meta_id: &Uuid,
+        upload_file_parameters: &upload_parameters::UploadFileParameters) -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        // previous versions keep their bytes, so the new file is charged to the owner in full
+        let file_size_bytes = std::fs::metadata(&upload_file_parameters.file_name)?.len() as i64;
+        self.check_quota(&container_meta.tenant_id, &container_meta.owner_id, &upload_file_parameters.blob_name, file_size_bytes).await?;
+
+        // numbered behind the latest version rather than the current one, which is older after a rollback
+        let version = self.sql_data_access_for(&container_meta.tenant_id)
+            .get_container_versions_by_container_meta_id(container_meta_id)
+            .await?
+            .first()
+            .map_or(container_meta.version, |container_version| container_version.version) + 1;
+        info!("Replacing the file of container_meta_id {} with {} as version {} on behalf of {}",
+            container_meta_id, upload_file_parameters.blob_name, version, principal.subject);
+
+        let object_key = versioning::versioned_object_key(container_meta_id, version, &upload_file_parameters.blob_name);
+        let (mut stored_container_meta, _) = self.store_file(
+            principal, upload_file_parameters, file_size_bytes, *container_meta_id, &object_key).await?;
+        stored_container_meta.version = version;
+        let container_version = self.sql_data_access_for(&container_meta.tenant_id)
+            .insert_container_version(&versioning::to_container_version(&stored_container_meta, &principal.subject))
+            .await?;
+        let container_meta = self.switch_container_version(container_meta, &container_version, file_size_bytes).await?;
+        metrics::counter!("mms_container_versions_total").increment(1);
+        metrics::counter!("mms_upload_bytes_total").increment(file_size_bytes as u64);
+
+        self.refresh_previews_from_file(&container_meta, &upload_file_parameters.file_name).await;
+        Ok(Some(container_meta))
+    }
+
+    /// Points the container back to a previous version of its file. Later versions are kept, so a rollback can be
+    /// undone by another one. `None` if the container or the version does not exist.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, version))]
+    pub async fn rollback_container_by_id(&self, principal: &Principal, container_meta_id: &Uuid, version: i32)
+        -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::Edit, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        let container_version = match self.sql_data_access_for(&container_meta.tenant_id)
+            .find_container_version(container_meta_id, version)
+            .await? {
+            Some(container_version) => container_version,
+            None => return Ok(None),
+        };
+        if container_meta.version == version {
+            return Ok(Some(container_meta));
+        }
+        info!("Rolling container_meta_id {} back from version {} to {} on behalf of {}",
+            container_meta_id, container_meta.version, version, principal.subject);
+
+        let container_meta = self.switch_container_version(container_meta, &container_version, 0).await?;
+        metrics::counter!("mms_container_rollbacks_total").increment(1);
+
+        let work_dir = std::env::temp_dir().join(format!("rollback-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        match self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&container_meta.object_key).await {
+            Ok(get_object_output) => {
+                let bytes = get_object_output.body.collect().await?.into_bytes();
+                self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, &file_name).await?;
+                drop(bytes);
+                self.refresh_previews_from_file(&container_meta, &file_name).await;
+            }
+            Err(err) => warn!("Failed to download version {} of container_meta_id {} for its previews: {}", version, container_meta.id, err),
+        }
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+        }
+        Ok(Some(container_meta))
+    }
+
+    /// Lists the versions of the container's file, the latest first.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_container_versions_by_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<Vec<models::container_version::ContainerVersion>>, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await?.is_none() {
+            return Ok(None);
+        }
+        let container_versions = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_container_versions_by_container_meta_id(container_meta_id)
+            .await?;
+        Ok(Some(container_versions))
+    }
+
+    // points the container to `container_version` and books `bytes_delta` and the change of duration on its owner.
+    // Renditions were encoded from the previous file, they are dropped and the container is queued for transcoding again.
+    async fn switch_container_version(
+        &self,
+        mut container_meta: models::container_meta::ContainerMeta,
+        container_version: &models::container_version::ContainerVersion,
+        bytes_delta: i64) -> Result<models::container_meta::ContainerMeta, Box<dyn std::error::Error>> {
+        let previous_duration_in_ms = container_meta.duration_in_ms;
+        versioning::apply_container_version(&mut container_meta, container_version);
+        container_meta.transcoding_status = String::from(TRANSCODING_PENDING);
+        container_meta.date_time_updated = Utc::now();
+        let container_meta = self.sql_data_access_for(&container_meta.tenant_id)
+            .update_container_file_by_id(&container_meta.id, &container_meta)
+            .await?;
+
+        let mut usage_entry = <models::usage_entry::UsageEntry as model::Model>::new();
+        usage_entry.id = Uuid::new_v4();
+        usage_entry.tenant_id = container_meta.tenant_id.clone();
+        usage_entry.subject = container_meta.owner_id.clone();
+        usage_entry.container_meta_id = container_meta.id;
+        usage_entry.bytes_delta = bytes_delta;
+        usage_entry.duration_in_ms_delta = container_meta.duration_in_ms - previous_duration_in_ms;
+        usage_entry.date_time_created = Utc::now();
+        self.sql_data_access_for(&container_meta.tenant_id).insert_usage_entry(&usage_entry).await?;
+
+        self.sql_data_access_for(&container_meta.tenant_id).delete_renditions_by_container_meta_id(&container_meta.id).await?;
+        info!("Switched container_meta_id {} to version {}", container_meta.id, container_meta.version);
+        Ok(container_meta)
+    }
+
+    // replaces the thumbnails and cover art by those of the file the container points to now. Its chapters are only
+    // imported if the container has none, as stored chapters may have been edited. Previews are optional, failures are logged.
+    async fn refresh_previews_from_file(&self, container_meta: &models::container_meta::ContainerMeta, file_name: &str) {
+        if let Err(err) = self.sql_data_access_for(&container_meta.tenant_id).delete_thumbnails_by_container_meta_id(&container_meta.id).await {
+            warn!("Failed to delete the thumbnails of container_meta_id {}: {}", container_meta.id, err);
+            return;
+        }
+        // the new file may lack previews of the previous one, e.g. its cover art
+        let thumbnails_prefix = container_meta.id.to_string() + "/thumbnails/";
+        if let Err(err) = self.blob_storage_connector_for(&container_meta.tenant_id).delete_blobs_by_prefix(&thumbnails_prefix).await {
+            warn!("Failed to delete the thumbnail blobs of container_meta_id {}: {}", container_meta.id, err);
+            return;
+        }
         if container_meta.video_track_id != Uuid::nil() {
-            self.sql_data_access.as_ref().unwrap().delete_video_track_by_id(&container_meta.video_track_id).await?;
+            if let Err(err) = self.create_thumbnails_from_file(container_meta, file_name, &ThumbnailParameters::new()).await {
+                warn!("Failed to create thumbnails for container_meta_id {}: {}", container_meta.id, err);
+            }
+        }
+        match mp4_metadata::read_metadata_from_file(file_name) {
+            Ok(EmbeddedMetadata { cover_art: Some(cover_art), .. }) => {
+                if let Err(err) = self.create_cover_thumbnail(container_meta, cover_art).await {
+                    warn!("Failed to store the cover art of container_meta_id {}: {}", container_meta.id, err);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Failed to read the embedded metadata of container_meta_id {}: {}", container_meta.id, err),
+        }
+        match self.sql_data_access_for(&container_meta.tenant_id).get_chapters_by_container_meta_id(&container_meta.id).await {
+            Ok(chapters) if chapters.is_empty() => {
+                if let Err(err) = self.create_chapters_from_file(container_meta, file_name).await {
+                    warn!("Failed to store the chapters of container_meta_id {}: {}", container_meta.id, err);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Failed to retrieve the chapters of container_meta_id {}: {}", container_meta.id, err),
         }
-        if container_meta.audio_track_id != Uuid::nil() {
-            self.sql_data_access.as_ref().unwrap().delete_audio_track_by_id(&container_meta.audio_track_id).await?;
+    }
+
+    /// Cuts a clip from the stored file without re-encoding and stores it as a new container owned by the caller,
+    /// linked to its source by `parent_container_id`. Fails with a `ClipRejectedError` if the range or codecs don't allow it.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn create_clip_by_id(&self, principal: &Principal, container_meta_id: &Uuid, clip_parameters: &upload_parameters::ClipParameters)
+        -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        info!("Clipping {} to {} ms of container_meta_id {} on behalf of {}",
+            clip_parameters.start_time_in_ms, clip_parameters.end_time_in_ms, container_meta_id, principal.subject);
+
+        let work_dir = std::env::temp_dir().join(format!("clip-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let result = self.create_clip_in(principal, &container_meta, clip_parameters, &work_dir).await;
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
         }
-        if container_meta.subtitle_track_id != Uuid::nil() {
-            self.sql_data_access.as_ref().unwrap().delete_subtitle_track_by_id(&container_meta.subtitle_track_id).await?;
+        result.map(Some)
+    }
+
+    async fn create_clip_in(
+        &self,
+        principal: &Principal,
+        container_meta: &models::container_meta::ContainerMeta,
+        clip_parameters: &upload_parameters::ClipParameters,
+        work_dir: &std::path::Path,
+    ) -> Result<models::container_meta::ContainerMeta, Box<dyn std::error::Error>> {
+        let get_object_output = self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&container_meta.object_key).await?;
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        let source_file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        let clip_file_name = work_dir.join("clip.mp4").to_string_lossy().to_string();
+        self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, &source_file_name).await?;
+        drop(bytes);
+
+        let (start_time_in_ms, end_time_in_ms) = (clip_parameters.start_time_in_ms, clip_parameters.end_time_in_ms);
+        let clip_file_name_clone = clip_file_name.clone();
+        let span = tracing::Span::current();
+        let clip_range = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            // keeps rejections distinguishable from failures for the caller
+            mp4_clipper::clip_file(&source_file_name, &clip_file_name_clone, start_time_in_ms, end_time_in_ms)
+                .map_err(|err| match err.downcast::<Mp4ClipError>() {
+                    Ok(clip_error) => Box::new(ClipRejectedError::from(*clip_error)) as Box<dyn std::error::Error + Send + Sync>,
+                    Err(err) => err.to_string().into(),
+                })
+        })).await?.map_err(|err| err as Box<dyn std::error::Error>)?;
+        metrics::counter!("mms_clips_total").increment(1);
+
+        let mut upload_file_parameters = upload_parameters::UploadFileParameters::new();
+        upload_file_parameters.file_name = clip_file_name;
+        upload_file_parameters.blob_name = clipping::clip_file_name(&container_meta.original_file_name, &clip_range);
+        let mut upload_meta_parameters = upload_parameters::UploadMetaParameters::new();
+        upload_meta_parameters.title = match clip_parameters.title.trim().is_empty() {
+            true => container_meta.title.clone(),
+            false => clip_parameters.title.trim().to_string(),
+        };
+        upload_meta_parameters.description = container_meta.description.clone();
+        upload_meta_parameters.tags = container_meta.tags.clone();
+        upload_meta_parameters.parent_container_id = Some(container_meta.id);
+        self.upload_blob_from_file_and_create_metadata(principal, &upload_file_parameters, &upload_meta_parameters).await
+    }
+
+    /// Demuxes the video, audio or subtitle track of a container into a standalone file, `None` if either does not exist.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, track_id = %track_id))]
+    pub async fn export_track_by_id(&self, principal: &Principal, container_meta_id: &Uuid, track_id: &Uuid)
+        -> Result<Option<ExportedTrackFile>, Box<dyn std::error::Error>> {
+        let container_meta = match self.authorize_container_by_id(principal, container_meta_id, Permission::View, false).await? {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+        let sql_data_access = self.sql_data_access_for(&container_meta.tenant_id);
+        let (track_type, mp4_track_id) = match *track_id {
+            track_id if track_id == Uuid::nil() => return Ok(None),
+            track_id if track_id == container_meta.video_track_id =>
+                (TrackType::Video, sql_data_access.get_video_track_by_id(&track_id).await?.mp4_track_id),
+            track_id if track_id == container_meta.audio_track_id =>
+                (TrackType::Audio, sql_data_access.get_audio_track_by_id(&track_id).await?.mp4_track_id),
+            track_id if track_id == container_meta.subtitle_track_id =>
+                (TrackType::Subtitle, sql_data_access.get_subtitle_track_by_id(&track_id).await?.mp4_track_id),
+            _ => return Ok(None),
+        };
+        // tracks stored before their MP4 track id was recorded are exported as the first track of their type
+        let mp4_track_id = u32::try_from(mp4_track_id).ok().filter(|mp4_track_id| *mp4_track_id > 0);
+        if container_meta.storage_backend != connectors::aws_s3_bucket_connector::AwsS3BucketConnector::STORAGE_BACKEND {
+            return Err(format!("Unsupported storage backend {} of container_meta_id {}",
+                container_meta.storage_backend, container_meta_id).into());
         }
+        info!("Exporting track {} of container_meta_id {} on behalf of {}", track_id, container_meta_id, principal.subject);
 
-        self.sql_data_access.as_ref().unwrap().delete_container_meta_by_id(&uuid_from_str).await?;
+        // the directory is handed over to the exported file, which deletes it once it has been streamed
+        let work_dir = std::env::temp_dir().join(format!("export-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let result = self.export_track_in(&container_meta, track_type, mp4_track_id, work_dir.clone()).await;
+        if result.is_err() {
+            if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+                warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+            }
+        }
+        result.map(Some)
+    }
+
+    async fn export_track_in(
+        &self,
+        container_meta: &models::container_meta::ContainerMeta,
+        track_type: TrackType,
+        mp4_track_id: Option<u32>,
+        work_dir: std::path::PathBuf) -> Result<ExportedTrackFile, Box<dyn std::error::Error>> {
+        let source_file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        let target_path = work_dir.join("track");
+        self.blob_storage_connector_for(&container_meta.tenant_id)
+            .download_blob(&container_meta.object_key, &source_file_name)
+            .await?;
+
+        let target_file_name = target_path.to_string_lossy().to_string();
+        let span = tracing::Span::current();
+        let exported_track = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            // keeps rejections distinguishable from failures for the caller
+            let exported_track = mp4_demuxer::export_track_to_file(&source_file_name, &target_file_name, track_type, mp4_track_id)
+                .map_err(|err| match err.downcast::<Mp4DemuxError>() {
+                    Ok(demux_error) => Box::new(TrackExportRejectedError::from(*demux_error)) as Box<dyn std::error::Error + Send + Sync>,
+                    Err(err) => err.to_string().into(),
+                });
+            // only the exported track is kept for streaming
+            if let Err(err) = std::fs::remove_file(&source_file_name) {
+                warn!("Failed to delete the temporary file {}: {}", source_file_name, err);
+            }
+            exported_track
+        })).await?.map_err(|err| err as Box<dyn std::error::Error>)?;
+        metrics::counter!("mms_track_exports_total", "track_type" => track_type.to_string().to_lowercase()).increment(1);
+
+        Ok(ExportedTrackFile::new(
+            track_export::exported_track_file_name(&container_meta.original_file_name, track_type, exported_track.extension),
+            String::from(exported_track.mime_type),
+            target_path,
+            work_dir,
+        )?)
+    }
+
+    // stores a copy of the file with the title, description and keywords of the container embedded under a new key,
+    // returns the key and the size of the copy
+    async fn write_back_metadata(&self, container_meta: &models::container_meta::ContainerMeta) -> Result<(String, i64), Box<dyn std::error::Error>> {
+        let work_dir = std::env::temp_dir().join(format!("metadata-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let result = self.write_back_metadata_in(container_meta, &work_dir).await;
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+        }
+        result
+    }
+
+    async fn write_back_metadata_in(&self, container_meta: &models::container_meta::ContainerMeta, work_dir: &std::path::Path)
+        -> Result<(String, i64), Box<dyn std::error::Error>> {
+        let get_object_output = self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&container_meta.object_key).await?;
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        let source_file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        let target_file_name = work_dir.join("target.mp4").to_string_lossy().to_string();
+        self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, &source_file_name).await?;
+        drop(bytes);
+
+        let title = container_meta.title.clone();
+        let description = container_meta.description.clone();
+        let tags: Vec<String> = container_meta.tags.iter().flatten().cloned().collect();
+        let target_file_name_clone = target_file_name.clone();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || span.in_scope(|| {
+            mp4_metadata::write_metadata_to_file(&source_file_name, &target_file_name_clone, &title, &description, &tags)
+                .map_err(|err| err.to_string())
+        })).await??;
+
+        let file_size_bytes = std::fs::metadata(&target_file_name)?.len() as i64;
+        let file_name = container_meta.object_key.rsplit('/').next().unwrap_or_default();
+        let object_key = versioning::rewritten_object_key(&container_meta.id, container_meta.version, file_name);
+        self.blob_storage_connector_for(&container_meta.tenant_id)
+            .upload_blob(&object_key, &target_file_name)
+            .await?;
+        info!("Successfully wrote back the metadata of container_meta_id {}", container_meta.id);
+        Ok((object_key, file_size_bytes))
+    }
+
+    /// Shares the container with the grantee, `permission` being `view` or `edit`.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, grantee))]
+    pub async fn share_container_by_id(&self, principal: &Principal, container_meta_id: &Uuid, grantee: &str, permission: &str)
+        -> Result<Option<models::container_grant::ContainerGrant>, Box<dyn std::error::Error>> {
+        if permission != models::container_grant::PERMISSION_VIEW && permission != models::container_grant::PERMISSION_EDIT {
+            return Err(format!("Unknown permission {}", permission).into());
+        }
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::Share, false).await?.is_none() {
+            return Ok(None);
+        }
+
+        let mut container_grant = <models::container_grant::ContainerGrant as model::Model>::new();
+        container_grant.id = Uuid::new_v4();
+        container_grant.container_meta_id = *container_meta_id;
+        container_grant.grantee = String::from(grantee);
+        container_grant.permission = String::from(permission);
+        container_grant.granted_by = principal.subject.clone();
+        container_grant.tenant_id = String::from(principal.resolved_tenant_id());
+        container_grant.date_time_created = Utc::now();
+        let container_grant = self.sql_data_access_for(principal.resolved_tenant_id()).upsert_container_grant(&container_grant).await?;
+        Ok(Some(container_grant))
+    }
+
+    /// Returns whether a grant was removed.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id, grantee))]
+    pub async fn unshare_container_by_id(&self, principal: &Principal, container_meta_id: &Uuid, grantee: &str)
+        -> Result<bool, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::Share, false).await?.is_none() {
+            return Ok(false);
+        }
+        let deleted = self.sql_data_access_for(principal.resolved_tenant_id()).delete_container_grant(container_meta_id, grantee).await?;
+        Ok(deleted)
+    }
+
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id(), container_meta_id = %container_meta_id))]
+    pub async fn retrieve_container_grants_by_id(&self, principal: &Principal, container_meta_id: &Uuid)
+        -> Result<Option<Vec<models::container_grant::ContainerGrant>>, Box<dyn std::error::Error>> {
+        if self.authorize_container_by_id(principal, container_meta_id, Permission::Share, false).await?.is_none() {
+            return Ok(None);
+        }
+        let container_grants = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_container_grants_by_container_meta_id(container_meta_id)
+            .await?;
+        Ok(Some(container_grants))
+    }
+
+    /// Containers the principal may not view are reported as missing, so their existence is not revealed.
+    /// Visible containers lacking the requested permission yield an `AuthorizationError`.
+    async fn authorize_container_by_id(
+        &self,
+        principal: &Principal,
+        container_meta_id: &Uuid,
+        permission: Permission,
+        include_deleted: bool) -> Result<Option<models::container_meta::ContainerMeta>, Box<dyn std::error::Error>> {
+        let container_meta = match include_deleted {
+            true => self.sql_data_access_for(principal.resolved_tenant_id()).find_container_meta_by_id_including_deleted(container_meta_id).await?,
+            false => self.sql_data_access_for(principal.resolved_tenant_id()).find_container_meta_by_id(container_meta_id).await?,
+        };
+        let container_meta = match container_meta {
+            Some(container_meta) => container_meta,
+            None => return Ok(None),
+        };
+
+        let container_grants = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_container_grants_by_container_meta_id(container_meta_id)
+            .await?;
+        if !authorization::is_authorized(principal, &container_meta, &container_grants, Permission::View) {
+            return Ok(None);
+        }
+        if !authorization::is_authorized(principal, &container_meta, &container_grants, permission) {
+            warn!("Denied {:?} on container_meta_id {} to {}", permission, container_meta_id, principal.subject);
+            return Err(Box::new(AuthorizationError::new("Operation not permitted on this container")));
+        }
+        Ok(Some(container_meta))
+    }
+
+    /// Returns the usage of the principal's tenant and of the principal itself.
+    #[tracing::instrument(skip_all, fields(subject = %principal.subject, tenant_id = principal.resolved_tenant_id()))]
+    pub async fn retrieve_usage(&self, principal: &Principal)
+        -> Result<(models::usage_entry::Usage, models::usage_entry::Usage), Box<dyn std::error::Error>> {
+        let tenant_usage = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_usage(principal.resolved_tenant_id(), None, None)
+            .await?;
+        let user_usage = self.sql_data_access_for(principal.resolved_tenant_id())
+            .get_usage(principal.resolved_tenant_id(), Some(&principal.subject), None)
+            .await?;
+        Ok((tenant_usage, user_usage))
+    }
+
+    /// Hard deletes blobs, tracks and thumbnails of all containers soft deleted longer than the retention ago.
+    /// Containers failing to purge are kept and retried on the next run.
+    #[tracing::instrument(skip_all)]
+    pub async fn purge_deleted_containers(&self, purge_parameters: &PurgeParameters) -> Result<usize, Box<dyn std::error::Error>> {
+        let deleted_before = Utc::now() - chrono::Duration::days(purge_parameters.retention_in_days);
+        // spans all tenants, each container is then deleted within its own tenant
+        let container_metas = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .get_container_metas_deleted_before(&deleted_before)
+            .await?;
+
+        let mut purged = 0;
+        for container_meta in container_metas.iter() {
+            match self.delete_blob_and_created_metadata_by_id(&container_meta.tenant_id, &container_meta.id).await {
+                Ok(()) => {
+                    info!("Successfully purged container_meta_id {}", container_meta.id);
+                    purged += 1;
+                }
+                Err(err) => warn!("Failed to purge container_meta_id {}: {}", container_meta.id, err),
+            }
+        }
+
+        info!("Successfully purged {} of {} containers deleted before {}", purged, container_metas.len(), deleted_before);
+        Ok(purged)
+    }
+
+    /// Recomputes file size, durations and timescales of containers uploaded before they were stored precisely.
+    /// Containers failing for a transient reason keep their flag and are retried on the next run.
+    #[tracing::instrument(skip_all)]
+    pub async fn backfill_container_metadata(&self, backfill_parameters: &BackfillParameters) -> Result<usize, Box<dyn std::error::Error>> {
+        let container_metas = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .get_container_metas_needing_backfill(backfill_parameters.batch_size)
+            .await?;
+
+        let mut backfilled = 0;
+        for container_meta in container_metas.iter() {
+            match self.backfill_container_meta(container_meta).await {
+                Ok(()) => backfilled += 1,
+                Err(err) => warn!("Failed to backfill container_meta_id {}: {}", container_meta.id, err),
+            }
+        }
+
+        info!("Successfully backfilled {} of {} containers", backfilled, container_metas.len());
+        Ok(backfilled)
+    }
+
+    async fn backfill_container_meta(&self, container_meta: &models::container_meta::ContainerMeta) -> Result<(), Box<dyn std::error::Error>> {
+        let work_dir = std::env::temp_dir().join(format!("backfill-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let result = self.backfill_container_meta_in(container_meta, &work_dir).await;
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+        }
+        result
+    }
+
+    async fn backfill_container_meta_in(&self, container_meta: &models::container_meta::ContainerMeta, work_dir: &std::path::Path)
+        -> Result<(), Box<dyn std::error::Error>> {
+        // containers uploaded before the object key was persisted hold their file next to the thumbnails folder
+        let mut object_key = container_meta.object_key.clone();
+        if object_key.is_empty() {
+            let thumbnails_prefix = container_meta.id.to_string() + "/thumbnails/";
+            object_key = self.blob_storage_connector_for(&container_meta.tenant_id)
+                .list_blob_names(&(container_meta.id.to_string() + "/"))
+                .await?
+                .into_iter()
+                .find(|blob_name| !blob_name.starts_with(&thumbnails_prefix))
+                .unwrap_or_default();
+        }
+        if object_key.is_empty() {
+            // nothing to recompute from, keep the migrated values instead of retrying forever
+            warn!("No blob found for container_meta_id {}, keeping the migrated values", container_meta.id);
+            self.sql_data_access_for(&container_meta.tenant_id).update_backfilled_container_meta_by_id(&container_meta.id, container_meta).await?;
+            return Ok(());
+        }
+
+        let get_object_output = self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&object_key).await?;
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        let file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, &file_name).await?;
+        let mp4_parser = self.mp4_parser.as_ref().unwrap().clone();
+        let span = tracing::Span::current();
+        let (parsed_container_meta, video_track, audio_track, subtitle_track) = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            mp4_parser.parse_from_file(&file_name).map_err(|err| err.to_string())
+        })).await??;
+
+        if let Some(parsed_track) = video_track.filter(|_| container_meta.video_track_id != Uuid::nil()) {
+            let mut track = self.sql_data_access_for(&container_meta.tenant_id).get_video_track_by_id(&container_meta.video_track_id).await?;
+            track.duration_in_ms = parsed_track.duration_in_ms;
+            track.timescale = parsed_track.timescale;
+            track.mp4_track_id = parsed_track.mp4_track_id;
+            self.sql_data_access_for(&container_meta.tenant_id).update_video_track_by_id(&track.id, &track).await?;
+        }
+        if let Some(parsed_track) = audio_track.filter(|_| container_meta.audio_track_id != Uuid::nil()) {
+            let mut track = self.sql_data_access_for(&container_meta.tenant_id).get_audio_track_by_id(&container_meta.audio_track_id).await?;
+            track.duration_in_ms = parsed_track.duration_in_ms;
+            track.timescale = parsed_track.timescale;
+            track.mp4_track_id = parsed_track.mp4_track_id;
+            self.sql_data_access_for(&container_meta.tenant_id).update_audio_track_by_id(&track.id, &track).await?;
+        }
+        if let Some(parsed_track) = subtitle_track.filter(|_| container_meta.subtitle_track_id != Uuid::nil()) {
+            let mut track = self.sql_data_access_for(&container_meta.tenant_id).get_subtitle_track_by_id(&container_meta.subtitle_track_id).await?;
+            track.duration_in_ms = parsed_track.duration_in_ms;
+            track.timescale = parsed_track.timescale;
+            track.mp4_track_id = parsed_track.mp4_track_id;
+            self.sql_data_access_for(&container_meta.tenant_id).update_subtitle_track_by_id(&track.id, &track).await?;
+        }
+
+        let mut backfilled_container_meta = parsed_container_meta;
+        backfilled_container_meta.file_size_bytes = bytes.len() as i64;
+        backfilled_container_meta.web_optimized = mp4_faststart::is_faststart(&mut std::io::Cursor::new(&bytes), bytes.len() as u64)
+            .unwrap_or(false);
+        backfilled_container_meta.original_file_name = match container_meta.original_file_name.is_empty() {
+            true => object_key.rsplit('/').next().unwrap_or_default().to_string(),
+            false => container_meta.original_file_name.clone(),
+        };
+        backfilled_container_meta.object_key = object_key;
+        self.sql_data_access_for(&container_meta.tenant_id)
+            .update_backfilled_container_meta_by_id(&container_meta.id, &backfilled_container_meta)
+            .await?;
         Ok(())
     }
 
+    /// Produces the rendition ladder of the oldest pending containers. Containers the encoder fails on are
+    /// marked `failed`, containers failing for a transient reason stay pending and are retried on the next run.
+    #[tracing::instrument(skip_all)]
+    pub async fn transcode_pending_containers(&self, transcoding_parameters: &TranscodingParameters) -> Result<usize, Box<dyn std::error::Error>> {
+        let container_metas = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .get_container_metas_pending_transcoding(transcoding_parameters.batch_size)
+            .await?;
+
+        let mut transcoded = 0;
+        for container_meta in container_metas.iter() {
+            let started = std::time::Instant::now();
+            let transcoding_status = match self.transcode_container_meta(container_meta, &transcoding_parameters.ladder).await {
+                Ok(transcoding_status) => transcoding_status,
+                Err(err) => {
+                    warn!("Failed to transcode container_meta_id {}: {}", container_meta.id, err);
+                    continue;
+                }
+            };
+            self.sql_data_access_for(&container_meta.tenant_id)
+                .update_transcoding_status_by_id(&container_meta.id, transcoding_status)
+                .await?;
+            metrics::histogram!("mms_transcoding_duration_seconds").record(started.elapsed().as_secs_f64());
+            metrics::counter!("mms_transcodings_total", "status" => transcoding_status).increment(1);
+            if transcoding_status == TRANSCODING_COMPLETED {
+                transcoded += 1;
+            }
+        }
+
+        info!("Successfully transcoded {} of {} containers", transcoded, container_metas.len());
+        Ok(transcoded)
+    }
+
+    // returns the status to persist, errors leave the container pending
+    async fn transcode_container_meta(&self, container_meta: &models::container_meta::ContainerMeta, ladder: &[RenditionProfile])
+        -> Result<&'static str, Box<dyn std::error::Error>> {
+        if container_meta.video_track_id == Uuid::nil() {
+            return Ok(TRANSCODING_SKIPPED);
+        }
+        let video_track = self.sql_data_access_for(&container_meta.tenant_id).get_video_track_by_id(&container_meta.video_track_id).await?;
+        let (source_width, source_height) = (video_track.width.max(0) as u32, video_track.height.max(0) as u32);
+        if processors::transcoder::Transcoder::plan_ladder(source_width, source_height, ladder).is_empty() {
+            info!("No rendition of the ladder fits container_meta_id {} with {}x{}", container_meta.id, source_width, source_height);
+            return Ok(TRANSCODING_SKIPPED);
+        }
+
+        let work_dir = std::env::temp_dir().join(format!("transcode-{}-{}", container_meta.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let result = self.transcode_container_meta_in(container_meta, source_width, source_height, ladder, &work_dir).await;
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+        }
+        result
+    }
+
+    async fn transcode_container_meta_in(
+        &self,
+        container_meta: &models::container_meta::ContainerMeta,
+        source_width: u32,
+        source_height: u32,
+        ladder: &[RenditionProfile],
+        work_dir: &std::path::Path) -> Result<&'static str, Box<dyn std::error::Error>> {
+        let get_object_output = self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&container_meta.object_key).await?;
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        let source_file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, &source_file_name).await?;
+        drop(bytes);
+
+        // encoding is CPU bound and must not block the async runtime
+        let transcoder = self.transcoder.as_ref().unwrap().clone();
+        let target_dir = work_dir.to_string_lossy().to_string();
+        let ladder_clone = ladder.to_vec();
+        let span = tracing::Span::current();
+        let encoded_renditions = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            transcoder.transcode(&source_file_name, &target_dir, source_width, source_height, &ladder_clone)
+                .map_err(|err| err.to_string())
+        })).await?;
+        let encoded_renditions = match encoded_renditions {
+            Ok(encoded_renditions) => encoded_renditions,
+            Err(err) => {
+                warn!("Encoder failed on container_meta_id {}: {}", container_meta.id, err);
+                return Ok(TRANSCODING_FAILED);
+            }
+        };
+
+        // renditions of an interrupted earlier run are replaced
+        self.sql_data_access_for(&container_meta.tenant_id).delete_renditions_by_container_meta_id(&container_meta.id).await?;
+        for encoded_rendition in encoded_renditions.iter() {
+            let mut rendition = <models::rendition::Rendition as model::Model>::new();
+            rendition.id = Uuid::new_v4();
+            rendition.container_meta_id = container_meta.id;
+            rendition.tenant_id = container_meta.tenant_id.clone();
+            rendition.name = encoded_rendition.profile.name.clone();
+            rendition.blob_name = container_meta.id.to_string() + "/renditions/" + &encoded_rendition.profile.name + ".mp4";
+            rendition.mime_type = String::from("video/mp4");
+            rendition.width = encoded_rendition.width as i32;
+            rendition.height = encoded_rendition.height as i32;
+            rendition.video_bit_rate_kbps = encoded_rendition.profile.video_bit_rate_kbps as i32;
+            rendition.audio_bit_rate_kbps = encoded_rendition.profile.audio_bit_rate_kbps as i32;
+            rendition.file_size_bytes = encoded_rendition.file_size_bytes as i64;
+            rendition.date_time_created = Utc::now();
+
+            self.blob_storage_connector_for(&container_meta.tenant_id)
+                .upload_blob(&rendition.blob_name, &encoded_rendition.file_name)
+                .await?;
+            self.sql_data_access_for(&container_meta.tenant_id).insert_rendition(&rendition).await?;
+        }
+
+        info!("Successfully created {} renditions for container_meta_id {}", encoded_renditions.len(), container_meta.id);
+        Ok(TRANSCODING_COMPLETED)
+    }
+
+    /// Measures the loudness and generates the waveform of pending audio tracks. Tracks the decoder fails on are
+    /// marked `failed`. Only AAC is decoded, codecs without a pure Rust decoder such as Opus are marked `unsupported`
+    /// for good. Tracks failing for a transient reason stay pending and are retried on the next run.
+    #[tracing::instrument(skip_all)]
+    pub async fn analyze_pending_audio_tracks(&self, audio_analysis_parameters: &AudioAnalysisParameters) -> Result<usize, Box<dyn std::error::Error>> {
+        let audio_tracks = self.sql_data_access
+            .as_ref()
+            .unwrap()
+            .get_audio_tracks_pending_analysis(audio_analysis_parameters.batch_size)
+            .await?;
+
+        let pending = audio_tracks.len();
+        let mut analyzed = 0;
+        for audio_track in audio_tracks.into_iter() {
+            let started = std::time::Instant::now();
+            let (audio_track_id, tenant_id) = (audio_track.id, audio_track.tenant_id.clone());
+            let analyzed_audio_track = match self.analyze_audio_track(audio_track, audio_analysis_parameters.waveform_pixels).await {
+                Ok(analyzed_audio_track) => analyzed_audio_track,
+                Err(err) => {
+                    warn!("Failed to analyze audio track {}: {}", audio_track_id, err);
+                    continue;
+                }
+            };
+            self.sql_data_access_for(&tenant_id)
+                .update_audio_analysis_by_id(&audio_track_id, &analyzed_audio_track)
+                .await?;
+            metrics::histogram!("mms_audio_analysis_duration_seconds").record(started.elapsed().as_secs_f64());
+            metrics::counter!("mms_audio_analyses_total", "status" => analyzed_audio_track.analysis_status.clone()).increment(1);
+            if analyzed_audio_track.analysis_status == AUDIO_ANALYSIS_COMPLETED {
+                analyzed += 1;
+            }
+        }
+
+        info!("Successfully analyzed {} of {} audio tracks", analyzed, pending);
+        Ok(analyzed)
+    }
+
+    // returns the audio track to persist, errors leave the audio track pending
+    async fn analyze_audio_track(&self, audio_track: models::track::AudioTrack, waveform_pixels: usize)
+        -> Result<models::track::AudioTrack, Box<dyn std::error::Error>> {
+        let container_meta = self.sql_data_access_for(&audio_track.tenant_id)
+            .get_container_meta_by_id(&audio_track.container_meta_id)
+            .await?;
+        let work_dir = std::env::temp_dir().join(format!("audio-analysis-{}-{}", audio_track.id, Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir)?;
+        let source_file_name = work_dir.join("source.mp4").to_string_lossy().to_string();
+        let result = self.analyze_audio_track_in(&container_meta, audio_track, waveform_pixels, &source_file_name).await;
+        if let Err(err) = std::fs::remove_dir_all(&work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", work_dir.display(), err);
+        }
+        result
+    }
+
+    async fn analyze_audio_track_in(
+        &self,
+        container_meta: &models::container_meta::ContainerMeta,
+        mut audio_track: models::track::AudioTrack,
+        waveform_pixels: usize,
+        source_file_name: &str) -> Result<models::track::AudioTrack, Box<dyn std::error::Error>> {
+        let get_object_output = self.blob_storage_connector_for(&container_meta.tenant_id).get_object(&container_meta.object_key).await?;
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        self.blob_storage_connector_for(&container_meta.tenant_id).write_bytes_to_file(&bytes, source_file_name).await?;
+        drop(bytes);
+
+        // decoding is CPU bound and must not block the async runtime
+        let audio_analyzer = self.audio_analyzer.as_ref().unwrap().clone();
+        let source_file_name = source_file_name.to_string();
+        let span = tracing::Span::current();
+        let audio_analysis = tokio::task::spawn_blocking(move || span.in_scope(|| {
+            audio_analyzer.analyze_file(&source_file_name, waveform_pixels)
+                .and_then(|audio_analysis| Ok((audio_analysis.waveform.to_json()?, audio_analysis.loudness)))
+                .map_err(|err| (err.is::<UnsupportedAudioCodecError>(), err.to_string()))
+        })).await?;
+
+        match audio_analysis {
+            Ok((waveform, loudness)) => {
+                audio_track.analysis_status = String::from(AUDIO_ANALYSIS_COMPLETED);
+                audio_track.integrated_loudness_lufs = loudness.integrated_loudness_lufs;
+                audio_track.true_peak_dbtp = Some(loudness.true_peak_dbtp);
+                audio_track.loudness_range_lu = Some(loudness.loudness_range_lu);
+                audio_track.waveform = Some(waveform);
+            }
+            Err((unsupported, err)) => {
+                warn!("Decoder failed on audio track {} of container_meta_id {}: {}", audio_track.id, container_meta.id, err);
+                audio_track.analysis_status = String::from(match unsupported {
+                    true => AUDIO_ANALYSIS_UNSUPPORTED,
+                    false => AUDIO_ANALYSIS_FAILED,
+                });
+            }
+        }
+        Ok(audio_track)
+    }
+
     // /// [Too complex] method for updating metadata
     // pub async fn update_metadata_by_id(&self, id: &Uuid, model_type: &models::ModelType) -> Result<Option<models::ModelType>, Box<dyn std::error::Error>>
     // {
@@ -249,10 +1739,21 @@ impl MutimediaManagementService {
 mod tests {
     use models::{schema::{audio_track::container_meta_id, subtitle_track}, container_meta::ContainerMeta, model::Model};
 
-    use crate::{upload_parameters::{UploadFileParameters, UploadMetaParameters}, download_parameters::DownloadBlobParameters, delete_parameters::DeleteBlobParameters};
+    use crate::{upload_parameters::{UploadFileParameters, UploadMetaParameters}, backfill_parameters::BackfillParameters};
 
     use super::*;
 
+    // copies the source instead of encoding it
+    struct FakeVideoEncoder;
+
+    impl processors::transcoder::VideoEncoder for FakeVideoEncoder {
+        fn encode(&self, source_file_name: &str, target_file_name: &str, _: u32, _: u32, _: &RenditionProfile)
+            -> Result<(), Box<dyn std::error::Error>> {
+            std::fs::copy(source_file_name, target_file_name)?;
+            Ok(())
+        }
+    }
+
     // In order to run the test execute: `RUST_LOG=info cargo test`
     #[tokio::test]
     pub async fn test_psql_data_access_methods_for_track() -> Result<(), Box<dyn std::error::Error>>{
@@ -261,7 +1762,7 @@ mod tests {
         let env_file_path = "./assets/app-secrets.dev.cfg";
         dotenv::from_path(env_file_path).ok();
 
-        let mut multi_media_management_service = MutimediaManagementService::new().await;
+        let mut multi_media_management_service = MutimediaManagementService::new(&AppConfig::load(None)?).await?;
 
         let mut upload_file_parameters = UploadFileParameters::new();
         upload_file_parameters.blob_name = String::from("nature2.mp4");
@@ -271,12 +1772,53 @@ mod tests {
         upload_meta_parameters.title = String::from("Sample MP4 container file #001");
         upload_meta_parameters.description = String::from("A Sample MP4 container file");
         upload_meta_parameters.tags = vec![Some(String::from("nature")), Some(String::from("adventure"))];
+
+        // tokens are minted locally instead of requesting them from an identity provider
+        let jwt_authenticator = authenticators::jwt_authenticator::JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length");
+        let token = jwt_authenticator.mint_hs256_token("user-1", &[Role::Uploader], 300)?;
+        let principal = jwt_authenticator.authenticate(&token)?;
         
         // [C]reate
         // Retrieve metadata
-        let create_result = multi_media_management_service.upload_blob_from_file_and_create_metadata(&upload_file_parameters, &upload_meta_parameters).await;
+        let create_result = multi_media_management_service.upload_blob_from_file_and_create_metadata(&principal, &upload_file_parameters, &upload_meta_parameters).await;
         assert!(create_result.is_ok());
         let create_result_unwrapped = create_result.unwrap();
+        assert!(create_result_unwrapped.duration_in_ms > 0);
+        assert!(!create_result_unwrapped.needs_backfill);
+        let (_, user_usage) = multi_media_management_service.retrieve_usage(&principal).await?;
+        assert!(user_usage.bytes >= create_result_unwrapped.file_size_bytes);
+
+        // Backfill
+        let backfill_result = multi_media_management_service.backfill_container_metadata(&BackfillParameters::new()).await;
+        assert!(backfill_result.is_ok());
+
+        // Transcoding
+        multi_media_management_service.transcoder = Some(processors::transcoder::Transcoder::new(Arc::new(FakeVideoEncoder)));
+        let mut transcoding_parameters = TranscodingParameters::new();
+        transcoding_parameters.ladder = vec![RenditionProfile::new("tiny", 64, 64, 100, 32)];
+        transcoding_parameters.batch_size = 1000;
+        let transcode_result = multi_media_management_service.transcode_pending_containers(&transcoding_parameters).await;
+        assert!(transcode_result.is_ok());
+        let (transcoded_container_meta, renditions) = multi_media_management_service
+            .retrieve_renditions_by_container_meta_id(&principal, &create_result_unwrapped.id).await?.unwrap();
+        assert_eq!(transcoded_container_meta.transcoding_status, TRANSCODING_COMPLETED);
+        assert_eq!(renditions.len(), 1);
+        let (_, rendition_bytes) = multi_media_management_service
+            .retrieve_rendition_bytes_by_name(&principal, &create_result_unwrapped.id, "tiny").await?.unwrap();
+        assert_eq!(rendition_bytes.len() as i64, create_result_unwrapped.file_size_bytes);
+
+        // Audio analysis of the AAC track
+        let mut audio_analysis_parameters = AudioAnalysisParameters::new();
+        audio_analysis_parameters.batch_size = 1000;
+        let analyze_result = multi_media_management_service.analyze_pending_audio_tracks(&audio_analysis_parameters).await;
+        assert!(analyze_result.is_ok());
+        let audio_track = multi_media_management_service
+            .retrieve_audio_track_by_id(&principal, &create_result_unwrapped.id, &create_result_unwrapped.audio_track_id).await?.unwrap();
+        assert_eq!(audio_track.analysis_status, AUDIO_ANALYSIS_COMPLETED);
+        assert!(audio_track.integrated_loudness_lufs.is_some());
+        assert!(audio_track.waveform.unwrap().starts_with("{\"version\":2"));
+        assert!(multi_media_management_service
+            .retrieve_audio_track_by_id(&principal, &create_result_unwrapped.id, &Uuid::new_v4()).await?.is_none());
 
         // [R]ead
         let mut get_container_meta_result = 
@@ -297,14 +1839,20 @@ mod tests {
         assert!(get_subtitle_track_result.is_err());
 
         // Download
-        let mut download_blob_parameters = DownloadBlobParameters::new();
-        download_blob_parameters.container_meta_id = create_result_unwrapped.id.to_string();
-        download_blob_parameters.file_name = upload_file_parameters.blob_name;
-        let download_result = multi_media_management_service.retrieve_bytes_from_blob_by_name(&download_blob_parameters).await;
+        let download_result = multi_media_management_service.retrieve_bytes_from_blob_by_id(&principal, &create_result_unwrapped.id, None, None).await;
         assert!(download_result.is_ok());
-        let bytes = download_result.unwrap();
+        let (downloaded_container_meta, bytes, _) = download_result.unwrap().unwrap();
+        assert_eq!(downloaded_container_meta.original_file_name, upload_file_parameters.blob_name);
+        assert_eq!(downloaded_container_meta.mime_type, "video/mp4");
+        assert_eq!(downloaded_container_meta.object_key, create_result_unwrapped.id.to_string() + "/" + &upload_file_parameters.blob_name);
 
         assert!(bytes.len() > 0);
+        let range_download_result = multi_media_management_service
+            .retrieve_bytes_from_blob_by_id(&principal, &create_result_unwrapped.id, None, Some(ByteRange::FromTo(100, Some(199))))
+            .await;
+        let (_, range_bytes, range) = range_download_result.unwrap().unwrap();
+        assert_eq!(range, Some((100, 199)));
+        assert_eq!(range_bytes, bytes.slice(100..200));
         let download_file_path = "temp/nature2-copy.mp4";
         let write_bytes_to_file_result = 
             multi_media_management_service.blob_storage_connector.as_ref().unwrap()
@@ -338,13 +1886,32 @@ mod tests {
                 println!("Received an unknown variant");
             }
         }
+        multi_media_management_service = MutimediaManagementService::new(&AppConfig::load(None)?).await?;
+
+        // Soft delete and restore
+        let soft_delete_result = multi_media_management_service.soft_delete_container_by_id(&principal, &create_result_unwrapped.id).await;
+        assert!(soft_delete_result.unwrap().is_some());
+        get_container_meta_result = 
+            multi_media_management_service.retrieve_metadata_by_id::<models::container_meta::ContainerMeta>(&create_result_unwrapped.id).await;
+        assert!(get_container_meta_result.is_err());
+        let restore_result = multi_media_management_service.restore_container_by_id(&principal, &create_result_unwrapped.id).await;
+        assert!(restore_result.unwrap().is_some());
+
         // [D]elete
-        multi_media_management_service = MutimediaManagementService::new().await;
-        let mut delete_blob_parameters = DeleteBlobParameters::new();
-        delete_blob_parameters.container_meta_id = create_result_unwrapped.id.to_string();
-        delete_blob_parameters.file_name = download_blob_parameters.file_name;
-        let delete_result = multi_media_management_service.delete_blob_and_created_metadata_by_id(&delete_blob_parameters).await;
+        let delete_result = multi_media_management_service.delete_blob_and_created_metadata_by_id(principal.resolved_tenant_id(), &create_result_unwrapped.id).await;
         assert!(delete_result.is_ok());
+        // deleting again is a no-op
+        let delete_result = multi_media_management_service.delete_blob_and_created_metadata_by_id(principal.resolved_tenant_id(), &create_result_unwrapped.id).await;
+        assert!(delete_result.is_ok());
+        let (_, user_usage_after_delete) = multi_media_management_service.retrieve_usage(&principal).await?;
+        assert_eq!(user_usage_after_delete.bytes, user_usage.bytes - create_result_unwrapped.file_size_bytes);
+
+        // quotas reject uploads before they are stored
+        let mut quota_parameters = QuotaParameters::new();
+        quota_parameters.max_bytes_per_user = Some(user_usage_after_delete.bytes);
+        multi_media_management_service.quota_parameters = Some(quota_parameters);
+        let create_result = multi_media_management_service.upload_blob_from_file_and_create_metadata(&principal, &upload_file_parameters, &upload_meta_parameters).await;
+        assert!(create_result.unwrap_err().is::<quota::QuotaExceededError>());
 
         Ok(())
     }
@@ -21,20 +21,26 @@
 // SOFTWARE.
 
 
-pub struct DeleteBlobParameters {
-    pub container_meta_id: String,
-    pub file_name: String,
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PurgeParameters {
+    pub retention_in_days: i64,
+    pub interval_in_secs: u64,
 }
 
-impl DeleteBlobParameters {
+impl PurgeParameters {
     pub fn new() -> Self {
-        DeleteBlobParameters{
-            container_meta_id: String::from(""),
-            file_name: String::from(""),
+        PurgeParameters {
+            retention_in_days: 30,
+            interval_in_secs: 3600,
         }
     }
+}
 
-    pub fn get_blob_name(&self) -> String {
-        String::from(&self.container_meta_id) + "/" + &self.file_name
+impl Default for PurgeParameters {
+    fn default() -> Self {
+        PurgeParameters::new()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,101 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+use crate::quota_parameters::QuotaParameters;
+use models::usage_entry::Usage;
+use std::fmt;
+
+/// Returned when storing a file would exceed a quota of the caller or its tenant.
+#[derive(Debug)]
+pub struct QuotaExceededError {
+    pub message: String,
+}
+
+impl QuotaExceededError {
+    pub fn new(message: &str) -> Self {
+        QuotaExceededError { message: String::from(message) }
+    }
+}
+
+impl fmt::Display for QuotaExceededError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for QuotaExceededError {}
+
+/// Checks whether one more container of `additional_bytes` fits into the quotas.
+pub fn check_quota(
+    quota_parameters: &QuotaParameters,
+    tenant_usage: &Usage,
+    user_usage: &Usage,
+    additional_bytes: i64,
+) -> Result<(), QuotaExceededError> {
+    let limits = [
+        ("tenant storage", tenant_usage.bytes + additional_bytes, quota_parameters.max_bytes_per_tenant),
+        ("tenant container", tenant_usage.container_count + 1, quota_parameters.max_containers_per_tenant),
+        ("user storage", user_usage.bytes + additional_bytes, quota_parameters.max_bytes_per_user),
+        ("user container", user_usage.container_count + 1, quota_parameters.max_containers_per_user),
+    ];
+    for (name, required, limit) in limits {
+        if let Some(limit) = limit {
+            if required > limit {
+                return Err(QuotaExceededError::new(&format!(
+                    "The {} quota of {} would be exceeded ({} required)", name, limit, required)));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_check_quota() {
+        let tenant_usage = Usage { bytes: 900, container_count: 9, duration_in_ms: 0 };
+        let user_usage = Usage { bytes: 100, container_count: 1, duration_in_ms: 0 };
+
+        // unlimited by default
+        assert!(check_quota(&QuotaParameters::new(), &tenant_usage, &user_usage, i64::MAX / 2).is_ok());
+
+        let mut quota_parameters = QuotaParameters::new();
+        quota_parameters.max_bytes_per_tenant = Some(1000);
+        assert!(check_quota(&quota_parameters, &tenant_usage, &user_usage, 100).is_ok());
+        assert!(check_quota(&quota_parameters, &tenant_usage, &user_usage, 101).is_err());
+
+        quota_parameters.max_containers_per_tenant = Some(9);
+        let err = check_quota(&quota_parameters, &tenant_usage, &user_usage, 1).unwrap_err();
+        assert!(err.message.contains("tenant container"));
+
+        let mut quota_parameters = QuotaParameters::new();
+        quota_parameters.max_bytes_per_user = Some(150);
+        assert!(check_quota(&quota_parameters, &tenant_usage, &user_usage, 50).is_ok());
+        assert!(check_quota(&quota_parameters, &tenant_usage, &user_usage, 51).is_err());
+    }
+}
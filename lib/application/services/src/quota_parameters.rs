@@ -0,0 +1,51 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotaParameters {
+    // `None` leaves the respective dimension unlimited
+    pub max_bytes_per_tenant: Option<i64>,
+    pub max_containers_per_tenant: Option<i64>,
+    pub max_bytes_per_user: Option<i64>,
+    pub max_containers_per_user: Option<i64>,
+}
+
+impl QuotaParameters {
+    pub fn new() -> Self {
+        QuotaParameters {
+            max_bytes_per_tenant: None,
+            max_containers_per_tenant: None,
+            max_bytes_per_user: None,
+            max_containers_per_user: None,
+        }
+    }
+}
+
+impl Default for QuotaParameters {
+    fn default() -> Self {
+        QuotaParameters::new()
+    }
+}
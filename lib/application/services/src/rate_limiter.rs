@@ -0,0 +1,128 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::rate_limit_parameters::RateLimitParameters;
+
+// idle clients whose buckets refilled completely are forgotten once this many are tracked
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket per client key, e.g. the principal or the client address.
+/// Each request takes a token, tokens are refilled continuously at `requests_per_minute` up to `burst`.
+pub struct RateLimiter {
+    parameters: RateLimitParameters,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(parameters: &RateLimitParameters) -> Self {
+        RateLimiter {
+            parameters: parameters.clone(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.parameters.requests_per_minute > 0
+    }
+
+    /// Takes a token of `key`, or returns how long the client has to wait for the next one.
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let capacity = self.parameters.burst.max(1) as f64;
+        let tokens_per_sec = self.parameters.requests_per_minute as f64 / 60.0;
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_KEYS && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.saturating_duration_since(bucket.refilled_at).as_secs_f64() * tokens_per_sec < capacity
+            });
+        }
+        let bucket = buckets.entry(String::from(key)).or_insert(TokenBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * tokens_per_sec).min(capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / tokens_per_sec))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_rate_limiter() {
+        let mut parameters = RateLimitParameters::new();
+        parameters.requests_per_minute = 60;
+        parameters.burst = 2;
+        let rate_limiter = RateLimiter::new(&parameters);
+        let now = Instant::now();
+
+        // The burst is available at once, after which a token is refilled every second
+        assert!(rate_limiter.check_at("alice", now).is_ok());
+        assert!(rate_limiter.check_at("alice", now).is_ok());
+        let retry_after = rate_limiter.check_at("alice", now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(1));
+        assert!(rate_limiter.check_at("alice", now + Duration::from_millis(500)).is_err());
+        assert!(rate_limiter.check_at("alice", now + Duration::from_secs(1)).is_ok());
+
+        // Clients are limited independently
+        assert!(rate_limiter.check_at("bob", now).is_ok());
+
+        // Refilling stops at the burst
+        let later = now + Duration::from_secs(60);
+        assert!(rate_limiter.check_at("alice", later).is_ok());
+        assert!(rate_limiter.check_at("alice", later).is_ok());
+        assert!(rate_limiter.check_at("alice", later).is_err());
+
+        // A rate of 0 disables the limit
+        let rate_limiter = RateLimiter::new(&RateLimitParameters::new());
+        assert!(!rate_limiter.is_enabled());
+        for _ in 0..10 {
+            assert!(rate_limiter.check_at("alice", now).is_ok());
+        }
+    }
+}
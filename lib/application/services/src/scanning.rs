@@ -0,0 +1,77 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::sync::Arc;
+
+use scanners::{clamav_scanner::ClamAvScanner, content_scanner::{ContentScanner, NoopContentScanner}};
+
+use crate::app_config::ScanningConfig;
+
+/// The content scanner failed to reach a verdict, hence the upload was neither stored nor quarantined.
+#[derive(Debug)]
+pub struct ScanUnavailableError {
+    message: String,
+}
+
+impl ScanUnavailableError {
+    pub fn new(message: &str) -> Self {
+        ScanUnavailableError {
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for ScanUnavailableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ScanUnavailableError {}
+
+/// The scanner selected by `scanning.backend`, which has been validated with the rest of the configuration.
+pub fn content_scanner_from(scanning_config: &ScanningConfig) -> Arc<dyn ContentScanner> {
+    match scanning_config.backend.as_str() {
+        ClamAvScanner::NAME => Arc::new(ClamAvScanner::new(&scanning_config.clamav)),
+        _ => Arc::new(NoopContentScanner::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scanners::clamav_scanner::ClamAvConfig;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_content_scanner_from() {
+        let mut scanning_config = ScanningConfig::default();
+        assert_eq!(content_scanner_from(&scanning_config).name(), NoopContentScanner::NAME);
+
+        scanning_config.backend = String::from(ClamAvScanner::NAME);
+        scanning_config.clamav = ClamAvConfig::default();
+        assert_eq!(content_scanner_from(&scanning_config).name(), ClamAvScanner::NAME);
+    }
+}
@@ -0,0 +1,48 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+pub struct ThumbnailParameters {
+    pub poster_time_in_ms: u64,
+    pub sprite_interval_in_ms: u64, // widened for long videos to stay within sprite_max_frames
+    pub sprite_max_frames: u32,
+    pub sprite_columns: u32,
+    pub sprite_tile_width: u32,
+}
+
+impl ThumbnailParameters {
+    pub fn new() -> Self {
+        ThumbnailParameters {
+            poster_time_in_ms: 5000,
+            sprite_interval_in_ms: 10000,
+            sprite_max_frames: 100,
+            sprite_columns: 10,
+            sprite_tile_width: 160,
+        }
+    }
+}
+
+impl Default for ThumbnailParameters {
+    fn default() -> Self {
+        ThumbnailParameters::new()
+    }
+}
@@ -0,0 +1,143 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use bytes::Bytes;
+use futures_core::Stream;
+use parsers::mp4_demuxer::{Mp4DemuxError, TrackType};
+use std::fmt;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_util::io::ReaderStream;
+use tracing::warn;
+
+/// Returned when a track cannot be exported without re-encoding it, e.g. because of its codec.
+#[derive(Debug)]
+pub struct TrackExportRejectedError {
+    pub code: String,
+    pub reason: String,
+}
+
+impl From<Mp4DemuxError> for TrackExportRejectedError {
+    fn from(err: Mp4DemuxError) -> Self {
+        TrackExportRejectedError { code: String::from(err.code), reason: err.reason }
+    }
+}
+
+impl fmt::Display for TrackExportRejectedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for TrackExportRejectedError {}
+
+/// A single track demuxed from a stored container into a temporary directory, which is deleted once it is dropped.
+pub struct ExportedTrackFile {
+    pub file_name: String,
+    pub mime_type: String,
+    pub file_size_bytes: u64,
+    path: PathBuf,
+    work_dir: PathBuf,
+}
+
+impl ExportedTrackFile {
+    /// Takes ownership of `work_dir` holding the demuxed track at `path`.
+    pub fn new(file_name: String, mime_type: String, path: PathBuf, work_dir: PathBuf) -> Result<Self, std::io::Error> {
+        let file_size_bytes = std::fs::metadata(&path)?.len();
+        Ok(ExportedTrackFile { file_name, mime_type, file_size_bytes, path, work_dir })
+    }
+
+    /// Streams the file in chunks, the temporary directory is deleted when the stream is dropped.
+    pub async fn into_stream(self) -> Result<ExportedTrackStream, std::io::Error> {
+        let file = tokio::fs::File::open(&self.path).await?;
+        Ok(ExportedTrackStream { reader: ReaderStream::new(file), _exported_track_file: self })
+    }
+}
+
+impl Drop for ExportedTrackFile {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.work_dir) {
+            warn!("Failed to delete the temporary directory {}: {}", self.work_dir.display(), err);
+        }
+    }
+}
+
+pub struct ExportedTrackStream {
+    reader: ReaderStream<tokio::fs::File>,
+    // dropped after the reader, deleting the exported file
+    _exported_track_file: ExportedTrackFile,
+}
+
+impl Stream for ExportedTrackStream {
+    type Item = Result<Bytes, std::io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.reader).poll_next(cx)
+    }
+}
+
+/// File name of an exported track, e.g. `forest-audio.m4a` for the audio track of `forest.mp4`.
+pub fn exported_track_file_name(original_file_name: &str, track_type: TrackType, extension: &str) -> String {
+    let stem = original_file_name.rsplit_once('.').map_or(original_file_name, |(stem, _)| stem);
+    match stem.is_empty() {
+        true => format!("{}.{}", track_type.to_string().to_lowercase(), extension),
+        false => format!("{}-{}.{}", stem, track_type.to_string().to_lowercase(), extension),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_exported_track_file_name() {
+        assert_eq!(exported_track_file_name("forest.mp4", TrackType::Audio, "m4a"), "forest-audio.m4a");
+        assert_eq!(exported_track_file_name("forest.final.mov", TrackType::Video, "mp4"), "forest.final-video.mp4");
+        assert_eq!(exported_track_file_name("forest", TrackType::Subtitle, "vtt"), "forest-subtitle.vtt");
+        assert_eq!(exported_track_file_name("", TrackType::Audio, "m4a"), "audio.m4a");
+    }
+
+    #[tokio::test]
+    async fn test_exported_track_stream() {
+        let work_dir = std::env::temp_dir().join(format!("export-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir).unwrap();
+        let path = work_dir.join("track");
+        std::fs::write(&path, vec![7u8; 20000]).unwrap();
+
+        let exported_track_file = ExportedTrackFile::new(
+            String::from("forest-audio.m4a"), String::from("audio/mp4"), path, work_dir.clone()).unwrap();
+        assert_eq!(exported_track_file.file_size_bytes, 20000);
+        let mut stream = exported_track_file.into_stream().await.unwrap();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(bytes, vec![7u8; 20000]);
+        assert!(work_dir.exists());
+        drop(stream);
+        assert!(!work_dir.exists());
+    }
+}
@@ -0,0 +1,73 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+/// Only the set fields are updated.
+pub struct UpdateMetaParameters {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<Option<String>>>,
+}
+
+impl UpdateMetaParameters {
+    pub fn new() -> Self {
+        UpdateMetaParameters {
+            title: None,
+            description: None,
+            tags: None,
+        }
+    }
+}
+
+impl Default for UpdateMetaParameters {
+    fn default() -> Self {
+        UpdateMetaParameters::new()
+    }
+}
+
+/// A chapter without an end lasts until the next chapter starts or the container ends.
+pub struct UpdateChapterParameters {
+    pub title: String,
+    pub start_time_in_ms: i64,
+    pub end_time_in_ms: Option<i64>,
+}
+
+pub struct ListParameters {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl ListParameters {
+    pub fn new() -> Self {
+        ListParameters {
+            limit: 50,
+            offset: 0,
+        }
+    }
+}
+
+impl Default for ListParameters {
+    fn default() -> Self {
+        ListParameters::new()
+    }
+}
@@ -57,6 +57,8 @@ pub struct UploadMetaParameters {
     pub title: String,
     pub description: String,
     pub tags: Vec<Option<String>>,
+    // set for clips, linking them to the container they were cut from
+    pub parent_container_id: Option<Uuid>,
 }
 
 impl UploadMetaParameters {
@@ -65,6 +67,30 @@ impl UploadMetaParameters {
             title: String::from(""), 
             description: String::from(""), 
             tags: Vec::new(), 
+            parent_container_id: None,
         }
     }
+}
+
+/// The clip is widened to the surrounding keyframes, an empty title is replaced by the one of the source.
+pub struct ClipParameters {
+    pub start_time_in_ms: u64,
+    pub end_time_in_ms: u64,
+    pub title: String,
+}
+
+impl ClipParameters {
+    pub fn new() -> Self {
+        ClipParameters {
+            start_time_in_ms: 0,
+            end_time_in_ms: 0,
+            title: String::from(""),
+        }
+    }
+}
+
+impl Default for ClipParameters {
+    fn default() -> Self {
+        ClipParameters::new()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::path::Path;
+
+use crate::app_config::UploadConfig;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+// text fields such as the title are buffered in memory
+pub const MAX_FIELD_SIZE_BYTES: usize = 64 * 1024;
+
+/// Reason for refusing an upload before its bytes are stored.
+#[derive(Debug, PartialEq)]
+pub enum UploadRejection {
+    TooManyFiles { max_files_per_request: usize },
+    FileTooLarge { max_file_size_bytes: usize },
+    RequestTooLarge { max_request_size_bytes: usize },
+    UnsupportedMediaType { mime_type: String },
+    InvalidFileName,
+    FieldTooLarge { name: String, max_field_size_bytes: usize },
+    // the file failed content validation or parsing and was quarantined
+    InvalidContent { reason_code: String, reason: String },
+}
+
+impl UploadRejection {
+    /// Stable identifier for API clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            UploadRejection::TooManyFiles { .. } => "too_many_files",
+            UploadRejection::FileTooLarge { .. } => "file_too_large",
+            UploadRejection::RequestTooLarge { .. } => "request_too_large",
+            UploadRejection::UnsupportedMediaType { .. } => "unsupported_media_type",
+            UploadRejection::InvalidFileName => "invalid_file_name",
+            UploadRejection::FieldTooLarge { .. } => "field_too_large",
+            UploadRejection::InvalidContent { .. } => "invalid_content",
+        }
+    }
+}
+
+impl fmt::Display for UploadRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UploadRejection::TooManyFiles { max_files_per_request } => {
+                write!(f, "At most {} files may be uploaded per request", max_files_per_request)
+            }
+            UploadRejection::FileTooLarge { max_file_size_bytes } => {
+                write!(f, "Files must not exceed {} bytes", max_file_size_bytes)
+            }
+            UploadRejection::RequestTooLarge { max_request_size_bytes } => {
+                write!(f, "Requests must not exceed {} bytes", max_request_size_bytes)
+            }
+            UploadRejection::UnsupportedMediaType { mime_type } => {
+                write!(f, "Files of type {} are not accepted", mime_type)
+            }
+            UploadRejection::InvalidFileName => write!(f, "Files must have a name"),
+            UploadRejection::FieldTooLarge { name, max_field_size_bytes } => {
+                write!(f, "The {} field must not exceed {} bytes", name, max_field_size_bytes)
+            }
+            UploadRejection::InvalidContent { reason_code, reason } => {
+                write!(f, "The file was rejected ({}): {}", reason_code, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UploadRejection {}
+
+/// Strips directories from a client supplied file name, so it can't escape the temp dir.
+pub fn sanitize_file_name(file_name: &str) -> Result<String, UploadRejection> {
+    Path::new(&file_name.replace('\\', "/"))
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .filter(|file_name| !file_name.is_empty())
+        .ok_or(UploadRejection::InvalidFileName)
+}
+
+/// The declared content type of a multipart field, falling back to the file extension for clients
+/// such as curl sending `application/octet-stream` for unknown types.
+pub fn resolve_mime_type(content_type: Option<&str>, file_name: &str) -> String {
+    match content_type.map(|content_type| content_type.trim().to_lowercase()) {
+        Some(content_type) if !content_type.is_empty() && content_type != OCTET_STREAM => content_type,
+        _ => {
+            let extension = Path::new(file_name)
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase());
+            String::from(match extension.as_deref() {
+                Some("mp4") => "video/mp4",
+                Some("m4v") => "video/x-m4v",
+                Some("m4a") => "audio/mp4",
+                Some("mov") => "video/quicktime",
+                _ => OCTET_STREAM,
+            })
+        }
+    }
+}
+
+pub fn check_file_count(upload_config: &UploadConfig, file_count: usize) -> Result<(), UploadRejection> {
+    match file_count > upload_config.max_files_per_request {
+        true => Err(UploadRejection::TooManyFiles { max_files_per_request: upload_config.max_files_per_request }),
+        false => Ok(()),
+    }
+}
+
+pub fn check_mime_type(upload_config: &UploadConfig, mime_type: &str) -> Result<(), UploadRejection> {
+    match upload_config.allowed_mime_types.iter().any(|allowed| allowed.eq_ignore_ascii_case(mime_type)) {
+        true => Ok(()),
+        false => Err(UploadRejection::UnsupportedMediaType { mime_type: String::from(mime_type) }),
+    }
+}
+
+pub fn check_file_size(upload_config: &UploadConfig, file_size_bytes: usize) -> Result<(), UploadRejection> {
+    match file_size_bytes > upload_config.max_file_size_bytes {
+        true => Err(UploadRejection::FileTooLarge { max_file_size_bytes: upload_config.max_file_size_bytes }),
+        false => Ok(()),
+    }
+}
+
+pub fn check_field_size(name: &str, field_size_bytes: usize) -> Result<(), UploadRejection> {
+    match field_size_bytes > MAX_FIELD_SIZE_BYTES {
+        true => Err(UploadRejection::FieldTooLarge { name: String::from(name), max_field_size_bytes: MAX_FIELD_SIZE_BYTES }),
+        false => Ok(()),
+    }
+}
+
+pub fn check_request_size(upload_config: &UploadConfig, request_size_bytes: usize) -> Result<(), UploadRejection> {
+    match request_size_bytes > upload_config.max_request_size_bytes {
+        true => Err(UploadRejection::RequestTooLarge { max_request_size_bytes: upload_config.max_request_size_bytes }),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_upload_validation() {
+        let upload_config = UploadConfig {
+            max_files_per_request: 2,
+            max_file_size_bytes: 100,
+            max_request_size_bytes: 150,
+            ..Default::default()
+        };
+
+        assert_eq!(sanitize_file_name("../../etc/passwd"), Ok(String::from("passwd")));
+        assert_eq!(sanitize_file_name("C:\\videos\\clip.mp4"), Ok(String::from("clip.mp4")));
+        assert_eq!(sanitize_file_name(".."), Err(UploadRejection::InvalidFileName));
+
+        assert_eq!(resolve_mime_type(Some("video/mp4"), "clip.bin"), "video/mp4");
+        assert_eq!(resolve_mime_type(Some("application/octet-stream"), "clip.MOV"), "video/quicktime");
+        assert_eq!(resolve_mime_type(None, "song.m4a"), "audio/mp4");
+        assert_eq!(resolve_mime_type(None, "notes.txt"), OCTET_STREAM);
+
+        assert!(check_file_count(&upload_config, 2).is_ok());
+        assert_eq!(check_file_count(&upload_config, 3).unwrap_err().code(), "too_many_files");
+        assert!(check_mime_type(&upload_config, "Video/MP4").is_ok());
+        assert_eq!(check_mime_type(&upload_config, "image/png").unwrap_err().code(), "unsupported_media_type");
+        assert!(check_file_size(&upload_config, 100).is_ok());
+        assert_eq!(check_file_size(&upload_config, 101).unwrap_err().code(), "file_too_large");
+        assert!(check_request_size(&upload_config, 150).is_ok());
+        assert_eq!(check_request_size(&upload_config, 151).unwrap_err().code(), "request_too_large");
+        assert!(check_field_size("title", MAX_FIELD_SIZE_BYTES).is_ok());
+        assert_eq!(check_field_size("title", MAX_FIELD_SIZE_BYTES + 1).unwrap_err().code(), "field_too_large");
+
+        let rejection = UploadRejection::InvalidContent {
+            reason_code: String::from("polyglot"),
+            reason: String::from("The file contains a PDF header"),
+        };
+        assert_eq!(rejection.code(), "invalid_content");
+        assert_eq!(rejection.to_string(), "The file was rejected (polyglot): The file contains a PDF header");
+    }
+}
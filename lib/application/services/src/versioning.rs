@@ -0,0 +1,127 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use chrono::Utc;
+use models::container_meta::ContainerMeta;
+use models::container_version::ContainerVersion;
+use uuid::Uuid;
+
+/// Object key of a version of a container's file. The upload keeps the `<container_meta_id>/<file name>` key it always
+/// had, later versions are stored below `<container_meta_id>/versions/<version>/`, all within the container prefix.
+pub fn versioned_object_key(container_meta_id: &Uuid, version: i32, file_name: &str) -> String {
+    match version {
+        1 => format!("{}/{}", container_meta_id, file_name),
+        _ => format!("{}/versions/{}/{}", container_meta_id, version, file_name),
+    }
+}
+
+/// Object key of a rewrite of the file of a version. Every rewrite gets its own key, so the previous file stays
+/// readable until the catalog points to the rewrite.
+pub fn rewritten_object_key(container_meta_id: &Uuid, version: i32, file_name: &str) -> String {
+    format!("{}/versions/{}/{}/{}", container_meta_id, version, Uuid::new_v4(), file_name)
+}
+
+/// Records the file the container currently points to as a version uploaded by `created_by`.
+pub fn to_container_version(container_meta: &ContainerMeta, created_by: &str) -> ContainerVersion {
+    ContainerVersion {
+        id: Uuid::new_v4(),
+        container_meta_id: container_meta.id,
+        tenant_id: container_meta.tenant_id.clone(),
+        version: container_meta.version,
+        video_track_id: container_meta.video_track_id,
+        audio_track_id: container_meta.audio_track_id,
+        subtitle_track_id: container_meta.subtitle_track_id,
+        file_size_bytes: container_meta.file_size_bytes,
+        duration_in_ms: container_meta.duration_in_ms,
+        original_file_name: container_meta.original_file_name.clone(),
+        mime_type: container_meta.mime_type.clone(),
+        major_brand: container_meta.major_brand.clone(),
+        compatible_brands: container_meta.compatible_brands.clone(),
+        storage_backend: container_meta.storage_backend.clone(),
+        object_key: container_meta.object_key.clone(),
+        scan_verdict: container_meta.scan_verdict.clone(),
+        web_optimized: container_meta.web_optimized,
+        created_by: String::from(created_by),
+        date_time_created: Utc::now(),
+    }
+}
+
+/// Points the container to the file of `container_version`, keeping title, description, tags, owner and grants.
+pub fn apply_container_version(container_meta: &mut ContainerMeta, container_version: &ContainerVersion) {
+    container_meta.version = container_version.version;
+    container_meta.video_track_id = container_version.video_track_id;
+    container_meta.audio_track_id = container_version.audio_track_id;
+    container_meta.subtitle_track_id = container_version.subtitle_track_id;
+    container_meta.file_size_bytes = container_version.file_size_bytes;
+    container_meta.duration_in_ms = container_version.duration_in_ms;
+    container_meta.original_file_name = container_version.original_file_name.clone();
+    container_meta.mime_type = container_version.mime_type.clone();
+    container_meta.major_brand = container_version.major_brand.clone();
+    container_meta.compatible_brands = container_version.compatible_brands.clone();
+    container_meta.storage_backend = container_version.storage_backend.clone();
+    container_meta.object_key = container_version.object_key.clone();
+    container_meta.scan_verdict = container_version.scan_verdict.clone();
+    container_meta.web_optimized = container_version.web_optimized;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_versioning() {
+        let container_meta_id = Uuid::new_v4();
+        assert_eq!(versioned_object_key(&container_meta_id, 1, "forest.mp4"), format!("{}/forest.mp4", container_meta_id));
+        assert_eq!(versioned_object_key(&container_meta_id, 3, "forest.mp4"), format!("{}/versions/3/forest.mp4", container_meta_id));
+
+        let mut container_meta = ContainerMeta::new();
+        container_meta.id = container_meta_id;
+        container_meta.tenant_id = String::from("tenant-a");
+        container_meta.title = String::from("Forest");
+        container_meta.video_track_id = Uuid::new_v4();
+        container_meta.file_size_bytes = 100000;
+        container_meta.original_file_name = String::from("forest.mp4");
+        container_meta.object_key = versioned_object_key(&container_meta_id, 1, "forest.mp4");
+        let container_version = to_container_version(&container_meta, "user-1");
+        assert_eq!(container_version.container_meta_id, container_meta_id);
+        assert_eq!(container_version.version, 1);
+        assert_eq!(container_version.created_by, "user-1");
+
+        container_meta.version = 2;
+        container_meta.video_track_id = Uuid::new_v4();
+        container_meta.file_size_bytes = 200000;
+        container_meta.original_file_name = String::from("forest-final.mp4");
+        container_meta.object_key = versioned_object_key(&container_meta_id, 2, "forest-final.mp4");
+
+        apply_container_version(&mut container_meta, &container_version);
+        assert_eq!(container_meta.version, 1);
+        assert_eq!(container_meta.video_track_id, container_version.video_track_id);
+        assert_eq!(container_meta.file_size_bytes, 100000);
+        assert_eq!(container_meta.original_file_name, "forest.mp4");
+        assert_eq!(container_meta.object_key, format!("{}/forest.mp4", container_meta_id));
+        assert_eq!(container_meta.title, "Forest");
+    }
+}
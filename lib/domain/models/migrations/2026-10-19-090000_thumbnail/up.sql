@@ -0,0 +1,12 @@
+-- Create the 'thumbnail' table
+CREATE TABLE thumbnail (
+  id UUID DEFAULT uuid_generate_v4 (),
+  container_meta_id UUID NOT NULL,
+  name VARCHAR NOT NULL,
+  blob_name VARCHAR NOT NULL,
+  mime_type VARCHAR NOT NULL,
+  width INTEGER NOT NULL,
+  height INTEGER NOT NULL,
+  time_offset_in_ms BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
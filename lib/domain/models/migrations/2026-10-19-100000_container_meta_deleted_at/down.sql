@@ -0,0 +1,2 @@
+DROP INDEX container_meta_deleted_at_idx;
+ALTER TABLE container_meta DROP COLUMN deleted_at;
@@ -0,0 +1,3 @@
+-- Soft deleted containers are kept until purged after the configured retention
+ALTER TABLE container_meta ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX container_meta_deleted_at_idx ON container_meta (deleted_at) WHERE deleted_at IS NOT NULL;
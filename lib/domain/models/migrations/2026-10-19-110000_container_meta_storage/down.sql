@@ -0,0 +1,6 @@
+ALTER TABLE container_meta DROP COLUMN object_key;
+ALTER TABLE container_meta DROP COLUMN storage_backend;
+ALTER TABLE container_meta DROP COLUMN compatible_brands;
+ALTER TABLE container_meta DROP COLUMN major_brand;
+ALTER TABLE container_meta DROP COLUMN mime_type;
+ALTER TABLE container_meta DROP COLUMN original_file_name;
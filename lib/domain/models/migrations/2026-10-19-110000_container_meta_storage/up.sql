@@ -0,0 +1,7 @@
+-- Persist where and what the uploaded file is, so a container can be downloaded or deleted by its id alone
+ALTER TABLE container_meta ADD COLUMN original_file_name VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE container_meta ADD COLUMN mime_type VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE container_meta ADD COLUMN major_brand VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE container_meta ADD COLUMN compatible_brands TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE container_meta ADD COLUMN storage_backend VARCHAR NOT NULL DEFAULT 'aws_s3';
+ALTER TABLE container_meta ADD COLUMN object_key VARCHAR NOT NULL DEFAULT '';
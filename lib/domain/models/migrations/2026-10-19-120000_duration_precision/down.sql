@@ -0,0 +1,13 @@
+ALTER TABLE subtitle_track DROP COLUMN timescale;
+ALTER TABLE subtitle_track DROP COLUMN duration_in_ms;
+ALTER TABLE audio_track DROP COLUMN timescale;
+ALTER TABLE audio_track DROP COLUMN duration_in_ms;
+ALTER TABLE video_track DROP COLUMN timescale;
+ALTER TABLE video_track DROP COLUMN duration_in_ms;
+
+DROP INDEX container_meta_needs_backfill_idx;
+ALTER TABLE container_meta DROP COLUMN needs_backfill;
+ALTER TABLE container_meta ADD COLUMN duration DOUBLE PRECISION NOT NULL DEFAULT 0;
+UPDATE container_meta SET duration = duration_in_ms / 1000.0;
+ALTER TABLE container_meta DROP COLUMN duration_in_ms;
+ALTER TABLE container_meta RENAME COLUMN file_size_bytes TO file_size_in_kb;
@@ -0,0 +1,16 @@
+-- `file_size_in_kb` always held bytes and `duration` whole seconds
+ALTER TABLE container_meta RENAME COLUMN file_size_in_kb TO file_size_bytes;
+ALTER TABLE container_meta ADD COLUMN duration_in_ms BIGINT NOT NULL DEFAULT 0;
+UPDATE container_meta SET duration_in_ms = ROUND(duration * 1000);
+ALTER TABLE container_meta DROP COLUMN duration;
+-- existing rows are recomputed from their stored blobs by the backfill job
+ALTER TABLE container_meta ADD COLUMN needs_backfill BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE container_meta ALTER COLUMN needs_backfill SET DEFAULT FALSE;
+CREATE INDEX container_meta_needs_backfill_idx ON container_meta (id) WHERE needs_backfill;
+
+ALTER TABLE video_track ADD COLUMN duration_in_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE video_track ADD COLUMN timescale INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE audio_track ADD COLUMN duration_in_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE audio_track ADD COLUMN timescale INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE subtitle_track ADD COLUMN duration_in_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE subtitle_track ADD COLUMN timescale INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- Create the 'api_key' table, only the SHA-256 hash of a key is stored
+CREATE TABLE api_key (
+  id UUID DEFAULT uuid_generate_v4 (),
+  name VARCHAR NOT NULL,
+  subject VARCHAR NOT NULL,
+  key_prefix VARCHAR NOT NULL UNIQUE,
+  key_hash VARCHAR NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  revoked_at TIMESTAMP WITH TIME ZONE,
+  PRIMARY KEY (id)
+);
@@ -0,0 +1,4 @@
+DROP TABLE container_grant;
+ALTER TABLE api_key DROP COLUMN roles;
+DROP INDEX container_meta_owner_id_idx;
+ALTER TABLE container_meta DROP COLUMN owner_id;
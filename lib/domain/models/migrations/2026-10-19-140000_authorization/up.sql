@@ -0,0 +1,18 @@
+-- Containers uploaded before ownership was recorded have no owner and are only visible to admins
+ALTER TABLE container_meta ADD COLUMN owner_id VARCHAR NOT NULL DEFAULT '';
+CREATE INDEX container_meta_owner_id_idx ON container_meta (owner_id);
+
+ALTER TABLE api_key ADD COLUMN roles TEXT[] NOT NULL DEFAULT '{}';
+
+-- Create the 'container_grant' table sharing a container with another subject
+CREATE TABLE container_grant (
+  id UUID DEFAULT uuid_generate_v4 (),
+  container_meta_id UUID NOT NULL,
+  grantee VARCHAR NOT NULL,
+  permission VARCHAR NOT NULL CHECK (permission IN ('view', 'edit')),
+  granted_by VARCHAR NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (id),
+  UNIQUE (container_meta_id, grantee)
+);
+CREATE INDEX container_grant_grantee_idx ON container_grant (grantee);
@@ -0,0 +1,29 @@
+DROP POLICY api_key_tenant_isolation ON api_key;
+ALTER TABLE api_key NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE api_key DISABLE ROW LEVEL SECURITY;
+DROP POLICY container_grant_tenant_isolation ON container_grant;
+ALTER TABLE container_grant NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE container_grant DISABLE ROW LEVEL SECURITY;
+DROP POLICY thumbnail_tenant_isolation ON thumbnail;
+ALTER TABLE thumbnail NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE thumbnail DISABLE ROW LEVEL SECURITY;
+DROP POLICY subtitle_track_tenant_isolation ON subtitle_track;
+ALTER TABLE subtitle_track NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE subtitle_track DISABLE ROW LEVEL SECURITY;
+DROP POLICY audio_track_tenant_isolation ON audio_track;
+ALTER TABLE audio_track NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE audio_track DISABLE ROW LEVEL SECURITY;
+DROP POLICY video_track_tenant_isolation ON video_track;
+ALTER TABLE video_track NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE video_track DISABLE ROW LEVEL SECURITY;
+DROP POLICY container_meta_tenant_isolation ON container_meta;
+ALTER TABLE container_meta NO FORCE ROW LEVEL SECURITY;
+ALTER TABLE container_meta DISABLE ROW LEVEL SECURITY;
+DROP INDEX container_meta_tenant_id_idx;
+ALTER TABLE api_key DROP COLUMN tenant_id;
+ALTER TABLE container_grant DROP COLUMN tenant_id;
+ALTER TABLE thumbnail DROP COLUMN tenant_id;
+ALTER TABLE subtitle_track DROP COLUMN tenant_id;
+ALTER TABLE audio_track DROP COLUMN tenant_id;
+ALTER TABLE video_track DROP COLUMN tenant_id;
+ALTER TABLE container_meta DROP COLUMN tenant_id;
@@ -0,0 +1,56 @@
+-- Existing rows belong to the 'default' tenant, new rows have to name their tenant explicitly
+ALTER TABLE container_meta ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE container_meta ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE video_track ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE video_track ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE audio_track ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE audio_track ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE subtitle_track ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE subtitle_track ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE thumbnail ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE thumbnail ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE container_grant ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE container_grant ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE api_key ADD COLUMN tenant_id VARCHAR NOT NULL DEFAULT 'default';
+ALTER TABLE api_key ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX container_meta_tenant_id_idx ON container_meta (tenant_id);
+
+-- Row level security restricts every statement to the tenant stored in the 'app.tenant_id' setting
+-- of the connection. Authentication and background jobs, which work across tenants, set
+-- 'app.bypass_tenant_isolation' instead. FORCE applies the policies to the table owner as well,
+-- superusers and roles with BYPASSRLS are never restricted.
+ALTER TABLE container_meta ENABLE ROW LEVEL SECURITY;
+ALTER TABLE container_meta FORCE ROW LEVEL SECURITY;
+CREATE POLICY container_meta_tenant_isolation ON container_meta
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE video_track ENABLE ROW LEVEL SECURITY;
+ALTER TABLE video_track FORCE ROW LEVEL SECURITY;
+CREATE POLICY video_track_tenant_isolation ON video_track
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE audio_track ENABLE ROW LEVEL SECURITY;
+ALTER TABLE audio_track FORCE ROW LEVEL SECURITY;
+CREATE POLICY audio_track_tenant_isolation ON audio_track
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE subtitle_track ENABLE ROW LEVEL SECURITY;
+ALTER TABLE subtitle_track FORCE ROW LEVEL SECURITY;
+CREATE POLICY subtitle_track_tenant_isolation ON subtitle_track
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE thumbnail ENABLE ROW LEVEL SECURITY;
+ALTER TABLE thumbnail FORCE ROW LEVEL SECURITY;
+CREATE POLICY thumbnail_tenant_isolation ON thumbnail
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE container_grant ENABLE ROW LEVEL SECURITY;
+ALTER TABLE container_grant FORCE ROW LEVEL SECURITY;
+CREATE POLICY container_grant_tenant_isolation ON container_grant
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
+ALTER TABLE api_key ENABLE ROW LEVEL SECURITY;
+ALTER TABLE api_key FORCE ROW LEVEL SECURITY;
+CREATE POLICY api_key_tenant_isolation ON api_key
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
@@ -0,0 +1,25 @@
+-- Create the 'usage_ledger' table recording every change of the stored bytes, containers and durations
+CREATE TABLE usage_ledger (
+  id UUID DEFAULT uuid_generate_v4 (),
+  tenant_id VARCHAR NOT NULL,
+  subject VARCHAR NOT NULL,
+  container_meta_id UUID NOT NULL,
+  bytes_delta BIGINT NOT NULL,
+  container_count_delta BIGINT NOT NULL,
+  duration_in_ms_delta BIGINT NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX usage_ledger_tenant_id_subject_idx ON usage_ledger (tenant_id, subject);
+CREATE INDEX usage_ledger_container_meta_id_idx ON usage_ledger (container_meta_id);
+
+-- Containers stored so far, including soft deleted ones whose blobs still exist, open the ledger
+SELECT set_config('app.bypass_tenant_isolation', 'on', true);
+INSERT INTO usage_ledger (tenant_id, subject, container_meta_id, bytes_delta, container_count_delta, duration_in_ms_delta, date_time_created)
+  SELECT tenant_id, owner_id, id, file_size_bytes, 1, duration_in_ms, now() FROM container_meta;
+
+ALTER TABLE usage_ledger ENABLE ROW LEVEL SECURITY;
+ALTER TABLE usage_ledger FORCE ROW LEVEL SECURITY;
+CREATE POLICY usage_ledger_tenant_isolation ON usage_ledger
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
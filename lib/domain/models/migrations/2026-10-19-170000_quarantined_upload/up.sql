@@ -0,0 +1,20 @@
+-- Create the 'quarantined_upload' table recording why an upload was rejected and where its file was kept
+CREATE TABLE quarantined_upload (
+  id UUID DEFAULT uuid_generate_v4 (),
+  tenant_id VARCHAR NOT NULL,
+  subject VARCHAR NOT NULL,
+  original_file_name VARCHAR NOT NULL,
+  object_key VARCHAR NOT NULL,
+  file_size_bytes BIGINT NOT NULL,
+  reason_code VARCHAR NOT NULL,
+  reason VARCHAR NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX quarantined_upload_tenant_id_date_time_created_idx ON quarantined_upload (tenant_id, date_time_created);
+
+ALTER TABLE quarantined_upload ENABLE ROW LEVEL SECURITY;
+ALTER TABLE quarantined_upload FORCE ROW LEVEL SECURITY;
+CREATE POLICY quarantined_upload_tenant_isolation ON quarantined_upload
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
@@ -0,0 +1 @@
+ALTER TABLE container_meta DROP COLUMN scan_verdict;
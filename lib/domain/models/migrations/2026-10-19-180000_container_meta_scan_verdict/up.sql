@@ -0,0 +1,2 @@
+-- Verdict of the content scanner, containers uploaded before scanning was introduced were never scanned
+ALTER TABLE container_meta ADD COLUMN scan_verdict VARCHAR NOT NULL DEFAULT 'not_scanned';
@@ -0,0 +1,2 @@
+DROP TABLE rendition;
+ALTER TABLE container_meta DROP COLUMN transcoding_status;
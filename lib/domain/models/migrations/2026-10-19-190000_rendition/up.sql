@@ -0,0 +1,27 @@
+-- Progress of the rendition ladder, containers uploaded before transcoding was introduced are transcoded as well
+ALTER TABLE container_meta ADD COLUMN transcoding_status VARCHAR NOT NULL DEFAULT 'pending';
+CREATE INDEX container_meta_transcoding_status_idx ON container_meta (transcoding_status) WHERE transcoding_status = 'pending';
+
+-- Create the 'rendition' table recording the transcoded copies of a container
+CREATE TABLE rendition (
+  id UUID DEFAULT uuid_generate_v4 (),
+  container_meta_id UUID NOT NULL,
+  tenant_id VARCHAR NOT NULL,
+  name VARCHAR NOT NULL,
+  blob_name VARCHAR NOT NULL,
+  mime_type VARCHAR NOT NULL,
+  width INTEGER NOT NULL,
+  height INTEGER NOT NULL,
+  video_bit_rate_kbps INTEGER NOT NULL,
+  audio_bit_rate_kbps INTEGER NOT NULL,
+  file_size_bytes BIGINT NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX rendition_container_meta_id_idx ON rendition (container_meta_id);
+
+ALTER TABLE rendition ENABLE ROW LEVEL SECURITY;
+ALTER TABLE rendition FORCE ROW LEVEL SECURITY;
+CREATE POLICY rendition_tenant_isolation ON rendition
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
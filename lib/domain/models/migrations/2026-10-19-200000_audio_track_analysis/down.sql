@@ -0,0 +1,5 @@
+ALTER TABLE audio_track DROP COLUMN waveform;
+ALTER TABLE audio_track DROP COLUMN loudness_range_lu;
+ALTER TABLE audio_track DROP COLUMN true_peak_dbtp;
+ALTER TABLE audio_track DROP COLUMN integrated_loudness_lufs;
+ALTER TABLE audio_track DROP COLUMN analysis_status;
@@ -0,0 +1,7 @@
+-- Loudness according to EBU R128 and the scrubber waveform, audio tracks uploaded before the analysis was introduced are analyzed as well
+ALTER TABLE audio_track ADD COLUMN analysis_status VARCHAR NOT NULL DEFAULT 'pending';
+ALTER TABLE audio_track ADD COLUMN integrated_loudness_lufs DOUBLE PRECISION;
+ALTER TABLE audio_track ADD COLUMN true_peak_dbtp DOUBLE PRECISION;
+ALTER TABLE audio_track ADD COLUMN loudness_range_lu DOUBLE PRECISION;
+ALTER TABLE audio_track ADD COLUMN waveform TEXT;
+CREATE INDEX audio_track_analysis_status_idx ON audio_track (analysis_status) WHERE analysis_status = 'pending';
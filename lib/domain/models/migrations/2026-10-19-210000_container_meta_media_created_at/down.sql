@@ -0,0 +1 @@
+ALTER TABLE container_meta DROP COLUMN media_created_at;
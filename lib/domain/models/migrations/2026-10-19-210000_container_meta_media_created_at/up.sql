@@ -0,0 +1,2 @@
+-- Creation time embedded in uploaded files by their producer, unknown for containers uploaded before it was read
+ALTER TABLE container_meta ADD COLUMN media_created_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,17 @@
+-- Create the 'chapter' table recording the chapter markers of a container
+CREATE TABLE chapter (
+  id UUID DEFAULT uuid_generate_v4 (),
+  container_meta_id UUID NOT NULL,
+  tenant_id VARCHAR NOT NULL,
+  title VARCHAR NOT NULL,
+  start_time_in_ms BIGINT NOT NULL,
+  end_time_in_ms BIGINT NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE INDEX chapter_container_meta_id_idx ON chapter (container_meta_id);
+
+ALTER TABLE chapter ENABLE ROW LEVEL SECURITY;
+ALTER TABLE chapter FORCE ROW LEVEL SECURITY;
+CREATE POLICY chapter_tenant_isolation ON chapter
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
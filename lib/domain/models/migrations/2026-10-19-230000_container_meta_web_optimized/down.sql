@@ -0,0 +1 @@
+ALTER TABLE container_meta DROP COLUMN web_optimized;
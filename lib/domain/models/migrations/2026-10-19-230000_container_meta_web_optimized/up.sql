@@ -0,0 +1,2 @@
+-- Whether the stored file starts with its moov box, unknown and hence false for containers uploaded before it was recorded
+ALTER TABLE container_meta ADD COLUMN web_optimized BOOLEAN NOT NULL DEFAULT false;
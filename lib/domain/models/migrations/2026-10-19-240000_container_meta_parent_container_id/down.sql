@@ -0,0 +1 @@
+ALTER TABLE container_meta DROP COLUMN parent_container_id;
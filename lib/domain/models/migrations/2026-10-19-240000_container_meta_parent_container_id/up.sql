@@ -0,0 +1,3 @@
+-- Container a clip was cut from, NULL for uploads
+ALTER TABLE container_meta ADD COLUMN parent_container_id UUID;
+CREATE INDEX container_meta_parent_container_id_idx ON container_meta (parent_container_id) WHERE parent_container_id IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE container_version;
+ALTER TABLE container_meta DROP COLUMN version;
@@ -0,0 +1,42 @@
+-- Version of the file currently stored for a container, counting from 1 for its upload
+ALTER TABLE container_meta ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Create the 'container_version' table recording every file stored for a container, the current one included
+CREATE TABLE container_version (
+  id UUID DEFAULT uuid_generate_v4 (),
+  container_meta_id UUID NOT NULL,
+  tenant_id VARCHAR NOT NULL,
+  version INTEGER NOT NULL,
+  video_track_id UUID NOT NULL,
+  audio_track_id UUID NOT NULL,
+  subtitle_track_id UUID NOT NULL,
+  file_size_bytes BIGINT NOT NULL,
+  duration_in_ms BIGINT NOT NULL,
+  original_file_name VARCHAR NOT NULL,
+  mime_type VARCHAR NOT NULL,
+  major_brand VARCHAR NOT NULL,
+  compatible_brands TEXT[] NOT NULL,
+  storage_backend VARCHAR NOT NULL,
+  object_key VARCHAR NOT NULL,
+  scan_verdict VARCHAR NOT NULL,
+  web_optimized BOOLEAN NOT NULL,
+  created_by VARCHAR NOT NULL,
+  date_time_created TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (id)
+);
+CREATE UNIQUE INDEX container_version_container_meta_id_version_idx ON container_version (container_meta_id, version);
+
+-- Containers stored so far, including soft deleted ones, start with their upload as version 1
+SELECT set_config('app.bypass_tenant_isolation', 'on', true);
+INSERT INTO container_version (container_meta_id, tenant_id, version, video_track_id, audio_track_id, subtitle_track_id,
+    file_size_bytes, duration_in_ms, original_file_name, mime_type, major_brand, compatible_brands, storage_backend,
+    object_key, scan_verdict, web_optimized, created_by, date_time_created)
+  SELECT id, tenant_id, 1, video_track_id, audio_track_id, subtitle_track_id, file_size_bytes, duration_in_ms,
+    original_file_name, mime_type, major_brand, compatible_brands, storage_backend, object_key, scan_verdict,
+    web_optimized, owner_id, date_time_created FROM container_meta;
+
+ALTER TABLE container_version ENABLE ROW LEVEL SECURITY;
+ALTER TABLE container_version FORCE ROW LEVEL SECURITY;
+CREATE POLICY container_version_tenant_isolation ON container_version
+  USING (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on')
+  WITH CHECK (tenant_id = current_setting('app.tenant_id', true) OR current_setting('app.bypass_tenant_isolation', true) = 'on');
@@ -0,0 +1,3 @@
+ALTER TABLE subtitle_track DROP COLUMN mp4_track_id;
+ALTER TABLE audio_track DROP COLUMN mp4_track_id;
+ALTER TABLE video_track DROP COLUMN mp4_track_id;
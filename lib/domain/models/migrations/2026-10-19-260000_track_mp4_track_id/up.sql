@@ -0,0 +1,4 @@
+-- Id of the track within the MP4 file, unknown and hence 0 for tracks stored before it was recorded
+ALTER TABLE video_track ADD COLUMN mp4_track_id INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE audio_track ADD COLUMN mp4_track_id INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE subtitle_track ADD COLUMN mp4_track_id INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,86 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::model::Model;
+use crate::schema::api_key;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Only the SHA-256 hash of a key is stored, `key_prefix` identifies the key without revealing it.
+/// `roles` are copied from the creating principal, so a key never grants more than its creator held.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = api_key)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub subject: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub date_time_created: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub roles: Vec<Option<String>>,
+    pub tenant_id: String,
+}
+
+impl Model for ApiKey {
+    fn new() -> Self {
+        ApiKey {
+            id: Uuid::nil(),
+            name: String::from(""),
+            subject: String::from(""),
+            key_prefix: String::from(""),
+            key_hash: String::from(""),
+            date_time_created: Utc::now(),
+            revoked_at: None,
+            roles: Vec::new(),
+            tenant_id: String::from(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_api_key() {
+        let mut api_key = ApiKey::new();
+        api_key.id = Uuid::new_v4();
+        api_key.name = String::from("ci upload");
+        api_key.subject = String::from("user-1");
+        api_key.key_prefix = String::from("0a1b2c3d");
+        api_key.key_hash = String::from("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+        api_key.roles = vec![Some(String::from("uploader"))];
+
+        assert_eq!(api_key.name, "ci upload");
+        assert_eq!(api_key.subject, "user-1");
+        assert_eq!(api_key.key_prefix, "0a1b2c3d");
+        assert_eq!(api_key.key_hash.len(), 64);
+        assert_eq!(api_key.revoked_at, None);
+        assert_eq!(api_key.roles.len(), 1);
+    }
+}
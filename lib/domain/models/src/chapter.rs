@@ -0,0 +1,79 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::model::Model;
+use crate::schema::chapter;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Named section of a container, e.g. read from the chapter markers of the uploaded file.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = chapter)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Chapter {
+    pub id: Uuid,
+    pub container_meta_id: Uuid,
+    pub tenant_id: String,
+    pub title: String,
+    pub start_time_in_ms: i64,
+    pub end_time_in_ms: i64,
+}
+
+impl Model for Chapter {
+    fn new() -> Self {
+        Chapter {
+            id: Uuid::nil(),
+            container_meta_id: Uuid::nil(),
+            tenant_id: String::from(""),
+            title: String::from(""),
+            start_time_in_ms: 0,
+            end_time_in_ms: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_chapter() {
+        let container_meta_id = Uuid::new_v4();
+
+        let mut chapter = Chapter::new();
+        chapter.id = Uuid::new_v4();
+        chapter.container_meta_id = container_meta_id;
+        chapter.tenant_id = String::from("tenant-a");
+        chapter.title = String::from("Intro");
+        chapter.start_time_in_ms = 0;
+        chapter.end_time_in_ms = 25000;
+
+        assert_eq!(chapter.container_meta_id, container_meta_id);
+        assert_eq!(chapter.tenant_id, String::from("tenant-a"));
+        assert_eq!(chapter.title, String::from("Intro"));
+        assert_eq!(chapter.start_time_in_ms, 0);
+        assert_eq!(chapter.end_time_in_ms, 25000);
+    }
+}
@@ -0,0 +1,79 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::model::Model;
+use crate::schema::container_grant;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+pub const PERMISSION_VIEW: &str = "view";
+pub const PERMISSION_EDIT: &str = "edit";
+
+/// Shares a container with another subject, `permission` being either `view` or `edit`.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = container_grant)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContainerGrant {
+    pub id: Uuid,
+    pub container_meta_id: Uuid,
+    pub grantee: String,
+    pub permission: String,
+    pub granted_by: String,
+    pub date_time_created: DateTime<Utc>,
+    pub tenant_id: String,
+}
+
+impl Model for ContainerGrant {
+    fn new() -> Self {
+        ContainerGrant {
+            id: Uuid::nil(),
+            container_meta_id: Uuid::nil(),
+            grantee: String::from(""),
+            permission: String::from(PERMISSION_VIEW),
+            granted_by: String::from(""),
+            date_time_created: Utc::now(),
+            tenant_id: String::from(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_container_grant() {
+        let mut container_grant = ContainerGrant::new();
+        container_grant.id = Uuid::new_v4();
+        container_grant.container_meta_id = Uuid::new_v4();
+        container_grant.grantee = String::from("user-2");
+        container_grant.granted_by = String::from("user-1");
+
+        assert_eq!(container_grant.grantee, "user-2");
+        assert_eq!(container_grant.granted_by, "user-1");
+        assert_eq!(container_grant.permission, PERMISSION_VIEW);
+    }
+}
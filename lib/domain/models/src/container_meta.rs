@@ -45,8 +45,30 @@ pub struct ContainerMeta {
     pub video_track_id: Uuid,
     pub audio_track_id: Uuid,
     pub subtitle_track_id: Uuid,
-    pub file_size_in_kb: i64,
-    pub duration: f64,
+    pub file_size_bytes: i64,
+    pub duration_in_ms: i64,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub original_file_name: String,
+    pub mime_type: String,
+    pub major_brand: String,
+    pub compatible_brands: Vec<Option<String>>,
+    pub storage_backend: String,
+    pub object_key: String,
+    pub needs_backfill: bool,
+    pub owner_id: String,
+    pub tenant_id: String,
+    // verdict of the content scanner, e.g. `clean`, or `not_scanned` if scanning is disabled
+    pub scan_verdict: String,
+    // `pending` until the rendition ladder is `completed`, `failed` or `skipped` for containers without video
+    pub transcoding_status: String,
+    // creation time embedded in the file, e.g. by the camera, rather than the time of the upload
+    pub media_created_at: Option<DateTime<Utc>>,
+    // the moov box precedes the media data, so progressive playback starts before the download completes
+    pub web_optimized: bool,
+    // container this one was clipped from, `None` for uploads
+    pub parent_container_id: Option<Uuid>,
+    // version of the stored file, counting from 1 for the upload, see `container_version`
+    pub version: i32,
 }
 
 impl Model for ContainerMeta {
@@ -61,8 +83,24 @@ impl Model for ContainerMeta {
             video_track_id: Uuid::nil(),
             audio_track_id: Uuid::nil(),
             subtitle_track_id: Uuid::nil(),
-            file_size_in_kb: 0,
-            duration: 0.0,
+            file_size_bytes: 0,
+            duration_in_ms: 0,
+            deleted_at: None,
+            original_file_name: String::from(""),
+            mime_type: String::from(""),
+            major_brand: String::from(""),
+            compatible_brands: Vec::new(),
+            storage_backend: String::from(""),
+            object_key: String::from(""),
+            needs_backfill: false,
+            owner_id: String::from(""),
+            tenant_id: String::from(""),
+            scan_verdict: String::from("not_scanned"),
+            transcoding_status: String::from("pending"),
+            media_created_at: None,
+            web_optimized: false,
+            parent_container_id: None,
+            version: 1,
         }
     }
 }
@@ -85,6 +123,8 @@ mod tests {
         video_track.height = 720;
         video_track.bit_rate =  1835;
         video_track.frame_rate = 30;
+        video_track.duration_in_ms = 200230;
+        video_track.timescale = 90000;
 
 
         let mut audio_track = AudioTrack::new();
@@ -119,8 +159,15 @@ mod tests {
         container_meta.video_track_id = video_track.id;
         container_meta.audio_track_id = audio_track.id;
         container_meta.subtitle_track_id = subtitle_track.id;
-        container_meta.file_size_in_kb = 100000;
-        container_meta.duration = 200.23;
+        container_meta.file_size_bytes = 100000;
+        container_meta.duration_in_ms = 200230;
+        container_meta.original_file_name = String::from("simple_container.mov");
+        container_meta.mime_type = String::from("video/quicktime");
+        container_meta.major_brand = String::from("qt  ");
+        container_meta.compatible_brands = vec![Some(String::from("qt  "))];
+        container_meta.storage_backend = String::from("aws_s3");
+        container_meta.owner_id = String::from("user-1");
+        container_meta.object_key = container_meta.id.to_string() + "/simple_container.mov";
 
         assert_eq!(container_meta.date_time_created, current_date_time);
         assert_eq!(container_meta.date_time_updated, current_date_time);
@@ -130,7 +177,17 @@ mod tests {
         assert_eq!(container_meta.video_track_id, video_track.id);
         assert_eq!(container_meta.audio_track_id, audio_track.id);
         assert_eq!(container_meta.subtitle_track_id, subtitle_track.id);
-        assert_eq!(container_meta.file_size_in_kb, 100000);
-        assert_eq!(container_meta.duration, 200.23);
+        assert_eq!(container_meta.file_size_bytes, 100000);
+        assert_eq!(container_meta.duration_in_ms, 200230);
+        assert_eq!(container_meta.duration_in_ms, video_track.duration_in_ms);
+        assert!(!container_meta.needs_backfill);
+        assert_eq!(container_meta.owner_id, "user-1");
+        assert_eq!(container_meta.deleted_at, None);
+        assert_eq!(container_meta.original_file_name, "simple_container.mov");
+        assert_eq!(container_meta.mime_type, "video/quicktime");
+        assert_eq!(container_meta.major_brand, "qt  ");
+        assert_eq!(container_meta.compatible_brands.len(), 1);
+        assert_eq!(container_meta.storage_backend, "aws_s3");
+        assert_eq!(container_meta.object_key, container_meta.id.to_string() + "/simple_container.mov");
     }
 }
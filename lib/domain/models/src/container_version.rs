@@ -0,0 +1,112 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::model::Model;
+use crate::schema::container_version;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// File stored for a container, the current one and every one it replaced, so a container can be rolled back.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = container_version)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContainerVersion {
+    pub id: Uuid,
+    pub container_meta_id: Uuid,
+    pub tenant_id: String,
+    pub version: i32,
+    pub video_track_id: Uuid,
+    pub audio_track_id: Uuid,
+    pub subtitle_track_id: Uuid,
+    pub file_size_bytes: i64,
+    pub duration_in_ms: i64,
+    pub original_file_name: String,
+    pub mime_type: String,
+    pub major_brand: String,
+    pub compatible_brands: Vec<Option<String>>,
+    pub storage_backend: String,
+    pub object_key: String,
+    pub scan_verdict: String,
+    pub web_optimized: bool,
+    // subject which uploaded the file
+    pub created_by: String,
+    pub date_time_created: DateTime<Utc>,
+}
+
+impl Model for ContainerVersion {
+    fn new() -> Self {
+        ContainerVersion {
+            id: Uuid::nil(),
+            container_meta_id: Uuid::nil(),
+            tenant_id: String::from(""),
+            version: 1,
+            video_track_id: Uuid::nil(),
+            audio_track_id: Uuid::nil(),
+            subtitle_track_id: Uuid::nil(),
+            file_size_bytes: 0,
+            duration_in_ms: 0,
+            original_file_name: String::from(""),
+            mime_type: String::from(""),
+            major_brand: String::from(""),
+            compatible_brands: Vec::new(),
+            storage_backend: String::from(""),
+            object_key: String::from(""),
+            scan_verdict: String::from("not_scanned"),
+            web_optimized: false,
+            created_by: String::from(""),
+            date_time_created: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_container_version() {
+        let container_meta_id = Uuid::new_v4();
+
+        let mut container_version = ContainerVersion::new();
+        container_version.id = Uuid::new_v4();
+        container_version.container_meta_id = container_meta_id;
+        container_version.tenant_id = String::from("tenant-a");
+        container_version.version = 2;
+        container_version.file_size_bytes = 100000;
+        container_version.original_file_name = String::from("simple_container.mp4");
+        container_version.object_key = container_meta_id.to_string() + "/versions/2/simple_container.mp4";
+        container_version.created_by = String::from("user-1");
+
+        assert_eq!(container_version.container_meta_id, container_meta_id);
+        assert_eq!(container_version.tenant_id, String::from("tenant-a"));
+        assert_eq!(container_version.version, 2);
+        assert_eq!(container_version.file_size_bytes, 100000);
+        assert_eq!(container_version.original_file_name, "simple_container.mp4");
+        assert_eq!(container_version.object_key, container_meta_id.to_string() + "/versions/2/simple_container.mp4");
+        assert_eq!(container_version.scan_verdict, "not_scanned");
+        assert_eq!(container_version.created_by, "user-1");
+    }
+}
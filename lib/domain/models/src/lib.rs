@@ -23,7 +23,17 @@
 
 pub mod model;
 pub mod container_meta;
+pub mod container_version;
 pub mod track;
+pub mod thumbnail;
+pub mod api_key;
+pub mod container_grant;
+pub mod usage_entry;
+pub mod quarantined_upload;
+pub mod rendition;
+pub mod chapter;
+pub mod principal;
+pub mod tenant;
 pub mod schema;
 
 pub enum ModelType {
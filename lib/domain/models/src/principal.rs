@@ -0,0 +1,119 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use crate::tenant::DEFAULT_TENANT_ID;
+
+/// How a request proved its identity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthenticationMethod {
+    Jwt,
+    ApiKey,
+}
+
+/// Roles are ordered, each role includes the permissions of the roles before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Uploader,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn parse(role: &str) -> Option<Role> {
+        match role {
+            "viewer" => Some(Role::Viewer),
+            "uploader" => Some(Role::Uploader),
+            "editor" => Some(Role::Editor),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Uploader => "uploader",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+/// The authenticated caller on whose behalf a service operation is executed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Principal {
+    pub subject: String,
+    pub authentication_method: AuthenticationMethod,
+    pub roles: Vec<Role>,
+    // set by the credential or resolved from the request, `None` falls back to the default tenant
+    pub tenant_id: Option<String>,
+}
+
+impl Principal {
+    pub fn new(subject: &str, authentication_method: AuthenticationMethod) -> Self {
+        Principal {
+            subject: String::from(subject),
+            authentication_method,
+            roles: Vec::new(),
+            tenant_id: None,
+        }
+    }
+
+    /// Whether the principal holds the role or a role including it.
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.iter().any(|assigned_role| *assigned_role >= role)
+    }
+
+    pub fn resolved_tenant_id(&self) -> &str {
+        self.tenant_id.as_deref().unwrap_or(DEFAULT_TENANT_ID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_principal() {
+        let principal = Principal::new("user-1", AuthenticationMethod::Jwt);
+        assert_eq!(principal.subject, "user-1");
+        assert_eq!(principal.authentication_method, AuthenticationMethod::Jwt);
+        assert!(!principal.has_role(Role::Viewer));
+        assert_eq!(principal.resolved_tenant_id(), DEFAULT_TENANT_ID);
+    }
+
+    #[test]
+    fn test_principal_roles() {
+        let mut principal = Principal::new("user-1", AuthenticationMethod::ApiKey);
+        principal.roles = vec![Role::parse("editor").unwrap()];
+        assert!(principal.has_role(Role::Viewer));
+        assert!(principal.has_role(Role::Uploader));
+        assert!(principal.has_role(Role::Editor));
+        assert!(!principal.has_role(Role::Admin));
+        assert_eq!(Role::parse("owner"), None);
+        assert_eq!(Role::Admin.as_str(), "admin");
+    }
+}
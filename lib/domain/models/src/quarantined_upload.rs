@@ -0,0 +1,84 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+
+use crate::model::Model;
+use crate::schema::quarantined_upload;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Upload rejected by content validation, kept below the quarantine prefix for inspection
+/// instead of reaching the catalog.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = quarantined_upload)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct QuarantinedUpload {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub subject: String,
+    pub original_file_name: String,
+    pub object_key: String,
+    pub file_size_bytes: i64,
+    pub reason_code: String,
+    pub reason: String,
+    pub date_time_created: DateTime<Utc>,
+}
+
+impl Model for QuarantinedUpload {
+    fn new() -> Self {
+        QuarantinedUpload {
+            id: Uuid::nil(),
+            tenant_id: String::from(""),
+            subject: String::from(""),
+            original_file_name: String::from(""),
+            object_key: String::from(""),
+            file_size_bytes: 0,
+            reason_code: String::from(""),
+            reason: String::from(""),
+            date_time_created: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_quarantined_upload() {
+        let mut quarantined_upload = QuarantinedUpload::new();
+        quarantined_upload.id = Uuid::new_v4();
+        quarantined_upload.original_file_name = String::from("invoice.mp4");
+        quarantined_upload.object_key = format!("quarantine/{}/invoice.mp4", quarantined_upload.id);
+        quarantined_upload.reason_code = String::from("polyglot");
+        quarantined_upload.reason = String::from("The file contains a PDF header");
+
+        assert_eq!(quarantined_upload.reason_code, "polyglot");
+        assert!(quarantined_upload.object_key.ends_with("/invoice.mp4"));
+        assert_eq!(quarantined_upload.file_size_bytes, 0);
+    }
+}
@@ -0,0 +1,103 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+
+use crate::model::Model;
+use crate::schema::rendition;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Transcoded copy of the uploaded file produced by one step of the rendition ladder.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = rendition)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Rendition {
+    pub id: Uuid,
+    pub container_meta_id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub blob_name: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub video_bit_rate_kbps: i32,
+    pub audio_bit_rate_kbps: i32,
+    pub file_size_bytes: i64,
+    pub date_time_created: DateTime<Utc>,
+}
+
+impl Model for Rendition {
+    fn new() -> Self {
+        Rendition {
+            id: Uuid::nil(),
+            container_meta_id: Uuid::nil(),
+            tenant_id: String::from(""),
+            name: String::from(""),
+            blob_name: String::from(""),
+            mime_type: String::from(""),
+            width: 0,
+            height: 0,
+            video_bit_rate_kbps: 0,
+            audio_bit_rate_kbps: 0,
+            file_size_bytes: 0,
+            date_time_created: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_rendition() {
+        let container_meta_id = Uuid::new_v4();
+
+        let mut rendition = Rendition::new();
+        rendition.id = Uuid::new_v4();
+        rendition.container_meta_id = container_meta_id;
+        rendition.tenant_id = String::from("tenant-a");
+        rendition.name = String::from("720p");
+        rendition.blob_name = container_meta_id.to_string() + "/renditions/720p.mp4";
+        rendition.mime_type = String::from("video/mp4");
+        rendition.width = 1280;
+        rendition.height = 720;
+        rendition.video_bit_rate_kbps = 2800;
+        rendition.audio_bit_rate_kbps = 128;
+        rendition.file_size_bytes = 10485760;
+
+        assert_eq!(rendition.container_meta_id, container_meta_id);
+        assert_eq!(rendition.name, String::from("720p"));
+        assert!(rendition.blob_name.ends_with("/renditions/720p.mp4"));
+        assert_eq!(rendition.mime_type, String::from("video/mp4"));
+        assert_eq!(rendition.width, 1280);
+        assert_eq!(rendition.height, 720);
+        assert_eq!(rendition.video_bit_rate_kbps, 2800);
+        assert_eq!(rendition.audio_bit_rate_kbps, 128);
+        assert_eq!(rendition.file_size_bytes, 10485760);
+    }
+}
@@ -1,5 +1,19 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_key (id) {
+        id -> Uuid,
+        name -> Varchar,
+        subject -> Varchar,
+        key_prefix -> Varchar,
+        key_hash -> Varchar,
+        date_time_created -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        roles -> Array<Nullable<Text>>,
+        tenant_id -> Varchar,
+    }
+}
+
 diesel::table! {
     audio_track (id) {
         id -> Uuid,
@@ -9,6 +23,27 @@ diesel::table! {
         bit_rate -> Int4,
         channel_config -> Varchar,
         sample_frequenz -> Int4,
+        duration_in_ms -> Int8,
+        timescale -> Int4,
+        tenant_id -> Varchar,
+        analysis_status -> Varchar,
+        integrated_loudness_lufs -> Nullable<Float8>,
+        true_peak_dbtp -> Nullable<Float8>,
+        loudness_range_lu -> Nullable<Float8>,
+        waveform -> Nullable<Text>,
+        mp4_track_id -> Int4,
+    }
+}
+
+diesel::table! {
+    container_grant (id) {
+        id -> Uuid,
+        container_meta_id -> Uuid,
+        grantee -> Varchar,
+        permission -> Varchar,
+        granted_by -> Varchar,
+        date_time_created -> Timestamptz,
+        tenant_id -> Varchar,
     }
 }
 
@@ -23,8 +58,24 @@ diesel::table! {
         video_track_id -> Uuid,
         audio_track_id -> Uuid,
         subtitle_track_id -> Uuid,
-        file_size_in_kb -> Int8,
-        duration -> Float8,
+        file_size_bytes -> Int8,
+        duration_in_ms -> Int8,
+        deleted_at -> Nullable<Timestamptz>,
+        original_file_name -> Varchar,
+        mime_type -> Varchar,
+        major_brand -> Varchar,
+        compatible_brands -> Array<Nullable<Text>>,
+        storage_backend -> Varchar,
+        object_key -> Varchar,
+        needs_backfill -> Bool,
+        owner_id -> Varchar,
+        tenant_id -> Varchar,
+        scan_verdict -> Varchar,
+        transcoding_status -> Varchar,
+        media_created_at -> Nullable<Timestamptz>,
+        web_optimized -> Bool,
+        parent_container_id -> Nullable<Uuid>,
+        version -> Int4,
     }
 }
 
@@ -34,6 +85,24 @@ diesel::table! {
         container_meta_id -> Uuid,
         name -> Varchar,
         media_type -> Varchar,
+        duration_in_ms -> Int8,
+        timescale -> Int4,
+        tenant_id -> Varchar,
+        mp4_track_id -> Int4,
+    }
+}
+
+diesel::table! {
+    thumbnail (id) {
+        id -> Uuid,
+        container_meta_id -> Uuid,
+        name -> Varchar,
+        blob_name -> Varchar,
+        mime_type -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        time_offset_in_ms -> Int8,
+        tenant_id -> Varchar,
     }
 }
 
@@ -47,12 +116,103 @@ diesel::table! {
         height -> Int4,
         bit_rate -> Int4,
         frame_rate -> Int4,
+        duration_in_ms -> Int8,
+        timescale -> Int4,
+        tenant_id -> Varchar,
+        mp4_track_id -> Int4,
+    }
+}
+
+diesel::table! {
+    usage_ledger (id) {
+        id -> Uuid,
+        tenant_id -> Varchar,
+        subject -> Varchar,
+        container_meta_id -> Uuid,
+        bytes_delta -> Int8,
+        container_count_delta -> Int8,
+        duration_in_ms_delta -> Int8,
+        date_time_created -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    quarantined_upload (id) {
+        id -> Uuid,
+        tenant_id -> Varchar,
+        subject -> Varchar,
+        original_file_name -> Varchar,
+        object_key -> Varchar,
+        file_size_bytes -> Int8,
+        reason_code -> Varchar,
+        reason -> Varchar,
+        date_time_created -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    rendition (id) {
+        id -> Uuid,
+        container_meta_id -> Uuid,
+        tenant_id -> Varchar,
+        name -> Varchar,
+        blob_name -> Varchar,
+        mime_type -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        video_bit_rate_kbps -> Int4,
+        audio_bit_rate_kbps -> Int4,
+        file_size_bytes -> Int8,
+        date_time_created -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    chapter (id) {
+        id -> Uuid,
+        container_meta_id -> Uuid,
+        tenant_id -> Varchar,
+        title -> Varchar,
+        start_time_in_ms -> Int8,
+        end_time_in_ms -> Int8,
+    }
+}
+
+diesel::table! {
+    container_version (id) {
+        id -> Uuid,
+        container_meta_id -> Uuid,
+        tenant_id -> Varchar,
+        version -> Int4,
+        video_track_id -> Uuid,
+        audio_track_id -> Uuid,
+        subtitle_track_id -> Uuid,
+        file_size_bytes -> Int8,
+        duration_in_ms -> Int8,
+        original_file_name -> Varchar,
+        mime_type -> Varchar,
+        major_brand -> Varchar,
+        compatible_brands -> Array<Nullable<Text>>,
+        storage_backend -> Varchar,
+        object_key -> Varchar,
+        scan_verdict -> Varchar,
+        web_optimized -> Bool,
+        created_by -> Varchar,
+        date_time_created -> Timestamptz,
     }
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_key,
     audio_track,
+    chapter,
+    container_grant,
     container_meta,
+    container_version,
+    quarantined_upload,
+    rendition,
     subtitle_track,
+    thumbnail,
+    usage_ledger,
     video_track,
 );
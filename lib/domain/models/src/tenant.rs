@@ -0,0 +1,56 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+/// Tenant of rows and blobs which existed before multi-tenancy and of callers not bound to a tenant.
+pub const DEFAULT_TENANT_ID: &str = "default";
+
+const MAX_TENANT_ID_LENGTH: usize = 64;
+
+/// Tenant ids become part of blob keys and database settings, so only lowercase
+/// alphanumerics, `-` and `_` are accepted.
+pub fn is_valid_tenant_id(tenant_id: &str) -> bool {
+    !tenant_id.is_empty()
+        && tenant_id.len() <= MAX_TENANT_ID_LENGTH
+        && tenant_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_is_valid_tenant_id() {
+        assert!(is_valid_tenant_id(DEFAULT_TENANT_ID));
+        assert!(is_valid_tenant_id("brand-a_2"));
+        assert!(!is_valid_tenant_id(""));
+        assert!(!is_valid_tenant_id("Brand"));
+        assert!(!is_valid_tenant_id("../brand"));
+        assert!(!is_valid_tenant_id("brand a"));
+        assert!(!is_valid_tenant_id(&"a".repeat(MAX_TENANT_ID_LENGTH + 1)));
+    }
+}
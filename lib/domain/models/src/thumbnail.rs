@@ -0,0 +1,87 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+use crate::model::Model;
+use crate::schema::thumbnail;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = thumbnail)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Thumbnail {
+    pub id: Uuid,
+    pub container_meta_id: Uuid,
+    pub name: String,
+    pub blob_name: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub time_offset_in_ms: i64,
+    pub tenant_id: String,
+}
+
+impl Model for Thumbnail {
+    fn new() -> Self {
+        Thumbnail {
+            id: Uuid::nil(),
+            container_meta_id: Uuid::nil(),
+            name: String::from(""),
+            blob_name: String::from(""),
+            mime_type: String::from(""),
+            width: 0,
+            height: 0,
+            time_offset_in_ms: 0,
+            tenant_id: String::from(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_thumbnail() {
+        let container_meta_id = Uuid::new_v4();
+
+        let mut thumbnail = Thumbnail::new();
+        thumbnail.id = Uuid::new_v4();
+        thumbnail.container_meta_id = container_meta_id;
+        thumbnail.name = String::from("poster.jpg");
+        thumbnail.blob_name = container_meta_id.to_string() + "/thumbnails/poster.jpg";
+        thumbnail.mime_type = String::from("image/jpeg");
+        thumbnail.width = 1280;
+        thumbnail.height = 720;
+        thumbnail.time_offset_in_ms = 5000;
+
+        assert_eq!(thumbnail.container_meta_id, container_meta_id);
+        assert_eq!(thumbnail.name, String::from("poster.jpg"));
+        assert!(thumbnail.blob_name.ends_with("/thumbnails/poster.jpg"));
+        assert_eq!(thumbnail.mime_type, String::from("image/jpeg"));
+        assert_eq!(thumbnail.width, 1280);
+        assert_eq!(thumbnail.height, 720);
+        assert_eq!(thumbnail.time_offset_in_ms, 5000);
+    }
+}
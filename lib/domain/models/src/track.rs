@@ -40,6 +40,10 @@ pub struct VideoTrack {
     pub height: i32,
     pub bit_rate: i32,
     pub frame_rate: i32,
+    pub duration_in_ms: i64,
+    pub timescale: i32,
+    pub tenant_id: String,
+    pub mp4_track_id: i32, // id of the track within the MP4 file, 0 if unknown
 }
 
 impl Model for VideoTrack {
@@ -53,6 +57,10 @@ impl Model for VideoTrack {
             height: 0,
             bit_rate: 0,
             frame_rate: 0,
+            duration_in_ms: 0,
+            timescale: 0,
+            tenant_id: String::from(""),
+            mp4_track_id: 0,
         }
     }
 }
@@ -68,6 +76,15 @@ pub struct AudioTrack {
     pub bit_rate: i32,
     pub channel_config: String,
     pub sample_frequenz: i32, // in hz
+    pub duration_in_ms: i64,
+    pub timescale: i32,
+    pub tenant_id: String,
+    pub analysis_status: String, // pending, completed, failed or unsupported
+    pub integrated_loudness_lufs: Option<f64>,
+    pub true_peak_dbtp: Option<f64>,
+    pub loudness_range_lu: Option<f64>,
+    pub waveform: Option<String>, // audiowaveform JSON
+    pub mp4_track_id: i32, // id of the track within the MP4 file, 0 if unknown
 }
 
 impl Model for AudioTrack {
@@ -80,6 +97,15 @@ impl Model for AudioTrack {
             bit_rate: 0,
             channel_config: String::from(""),
             sample_frequenz: 0, // in hz
+            duration_in_ms: 0,
+            timescale: 0,
+            tenant_id: String::from(""),
+            analysis_status: String::from("pending"),
+            integrated_loudness_lufs: None,
+            true_peak_dbtp: None,
+            loudness_range_lu: None,
+            waveform: None,
+            mp4_track_id: 0,
         }
     }
 }
@@ -92,6 +118,10 @@ pub struct SubtitleTrack {
     pub container_meta_id: Uuid,
     pub name: String,
     pub media_type: String,
+    pub duration_in_ms: i64,
+    pub timescale: i32,
+    pub tenant_id: String,
+    pub mp4_track_id: i32, // id of the track within the MP4 file, 0 if unknown
 }
 
 impl Model for SubtitleTrack {
@@ -101,6 +131,10 @@ impl Model for SubtitleTrack {
             container_meta_id: Uuid::nil(),
             name: String::from(""),
             media_type: String::from(""),
+            duration_in_ms: 0,
+            timescale: 0,
+            tenant_id: String::from(""),
+            mp4_track_id: 0,
         }
     }
 }
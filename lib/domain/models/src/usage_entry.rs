@@ -0,0 +1,95 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+use crate::model::Model;
+use crate::schema::usage_ledger;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Append-only change of the storage consumed by a subject, summed up to its and its tenant's usage.
+#[derive(Insertable, Queryable, Selectable, Identifiable, Debug, PartialEq)]
+#[diesel(table_name = usage_ledger)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UsageEntry {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub subject: String,
+    pub container_meta_id: Uuid,
+    pub bytes_delta: i64,
+    pub container_count_delta: i64,
+    pub duration_in_ms_delta: i64,
+    pub date_time_created: DateTime<Utc>,
+}
+
+impl Model for UsageEntry {
+    fn new() -> Self {
+        UsageEntry {
+            id: Uuid::nil(),
+            tenant_id: String::from(""),
+            subject: String::from(""),
+            container_meta_id: Uuid::nil(),
+            bytes_delta: 0,
+            container_count_delta: 0,
+            duration_in_ms_delta: 0,
+            date_time_created: Utc::now(),
+        }
+    }
+}
+
+/// Sum of usage entries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Usage {
+    pub bytes: i64,
+    pub container_count: i64,
+    pub duration_in_ms: i64,
+}
+
+impl Usage {
+    pub fn is_empty(&self) -> bool {
+        self.bytes == 0 && self.container_count == 0 && self.duration_in_ms == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_usage_entry() {
+        let mut usage_entry = UsageEntry::new();
+        usage_entry.id = Uuid::new_v4();
+        usage_entry.tenant_id = String::from("default");
+        usage_entry.subject = String::from("user-1");
+        usage_entry.bytes_delta = 5862561;
+        usage_entry.container_count_delta = 1;
+
+        assert_eq!(usage_entry.subject, "user-1");
+        assert_eq!(usage_entry.bytes_delta, 5862561);
+        assert!(Usage::default().is_empty());
+        assert!(!Usage { bytes: 0, container_count: 1, duration_in_ms: 0 }.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+// api keys look like `mms_<key prefix>_<secret>`, the key prefix is stored in plain text for the lookup
+const API_KEY_SCHEME: &str = "mms";
+const KEY_PREFIX_BYTES: usize = 4;
+const SECRET_BYTES: usize = 32;
+
+pub struct GeneratedApiKey {
+    pub api_key: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+}
+
+/// Generates a random api key. The plain text key is only returned here and must never be persisted.
+pub fn generate_api_key() -> GeneratedApiKey {
+    let mut key_prefix_bytes = [0u8; KEY_PREFIX_BYTES];
+    let mut secret_bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut key_prefix_bytes);
+    rand::thread_rng().fill_bytes(&mut secret_bytes);
+
+    let key_prefix = hex::encode(key_prefix_bytes);
+    let api_key = format!("{}_{}_{}", API_KEY_SCHEME, key_prefix, hex::encode(secret_bytes));
+    let key_hash = hash_api_key(&api_key);
+    GeneratedApiKey { api_key, key_prefix, key_hash }
+}
+
+/// Returns the key prefix of a well formed api key.
+pub fn parse_key_prefix(api_key: &str) -> Option<&str> {
+    let mut parts = api_key.splitn(3, '_');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(API_KEY_SCHEME), Some(key_prefix), Some(secret))
+            if key_prefix.len() == KEY_PREFIX_BYTES * 2 && !secret.is_empty() => Some(key_prefix),
+        _ => None,
+    }
+}
+
+/// Api keys carry enough entropy for a single SHA-256 round, a slow password hash would only add latency.
+pub fn hash_api_key(api_key: &str) -> String {
+    hex::encode(Sha256::digest(api_key.as_bytes()))
+}
+
+pub fn verify_api_key(api_key: &str, key_hash: &str) -> bool {
+    hash_api_key(api_key).as_bytes().ct_eq(key_hash.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_api_key_hasher() {
+        let generated_api_key = generate_api_key();
+        assert!(generated_api_key.api_key.starts_with("mms_"));
+        assert_eq!(parse_key_prefix(&generated_api_key.api_key), Some(generated_api_key.key_prefix.as_str()));
+        assert!(!generated_api_key.api_key.contains(&generated_api_key.key_hash));
+        assert!(verify_api_key(&generated_api_key.api_key, &generated_api_key.key_hash));
+        assert!(!verify_api_key(&(generated_api_key.api_key.clone() + "0"), &generated_api_key.key_hash));
+
+        assert_eq!(parse_key_prefix("mms_0a1b2c3d"), None);
+        assert_eq!(parse_key_prefix("abc_0a1b2c3d_secret"), None);
+        assert_eq!(parse_key_prefix("mms_0a1b_secret"), None);
+    }
+}
@@ -0,0 +1,234 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use jsonwebtoken::{
+    decode, decode_header, encode, jwk::JwkSet, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use tracing::info;
+use models::{principal::{AuthenticationMethod, Principal, Role}, tenant};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    exp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iat: Option<u64>,
+    #[serde(default)]
+    roles: Vec<String>,
+    // binds the subject to a tenant, callers without one may select a tenant per request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tenant_id: Option<String>,
+}
+
+/// Empty values are unset. At least one of `hs256_secret` or `jwks_file` is required.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub hs256_secret: String,
+    pub jwks_file: String,
+    pub issuer: String,
+    pub audience: String,
+}
+
+/// Verifies HS256 tokens with a shared secret and RS256 tokens with the public keys of a JWKS file.
+#[derive(Clone)]
+pub struct JwtAuthenticator {
+    hs256_secret: Option<Vec<u8>>,
+    jwks: Option<JwkSet>,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtAuthenticator {
+    pub fn new(jwt_config: &JwtConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let non_empty = |value: &String| Some(value.clone()).filter(|value| !value.is_empty());
+        let mut jwt_authenticator = JwtAuthenticator {
+            hs256_secret: non_empty(&jwt_config.hs256_secret).map(String::into_bytes),
+            jwks: None,
+            issuer: non_empty(&jwt_config.issuer),
+            audience: non_empty(&jwt_config.audience),
+        };
+        if let Some(jwks_file) = non_empty(&jwt_config.jwks_file) {
+            jwt_authenticator.jwks = Some(read_jwks_file(&jwks_file)?);
+        }
+        if jwt_authenticator.hs256_secret.is_none() && jwt_authenticator.jwks.is_none() {
+            return Err("Either an HS256 secret or a JWKS file is required".into());
+        }
+        Ok(jwt_authenticator)
+    }
+
+    pub fn with_hs256_secret(hs256_secret: &[u8]) -> Self {
+        JwtAuthenticator {
+            hs256_secret: Some(hs256_secret.to_vec()),
+            jwks: None,
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    pub fn with_jwks_file(jwks_file: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(JwtAuthenticator {
+            hs256_secret: None,
+            jwks: Some(read_jwks_file(jwks_file)?),
+            issuer: None,
+            audience: None,
+        })
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<Principal, Box<dyn std::error::Error>> {
+        let header = decode_header(token)?;
+        let decoding_key = match header.alg {
+            Algorithm::HS256 => match &self.hs256_secret {
+                Some(hs256_secret) => DecodingKey::from_secret(hs256_secret),
+                None => return Err("HS256 tokens are not accepted".into()),
+            },
+            Algorithm::RS256 => {
+                let jwks = self.jwks.as_ref().ok_or("RS256 tokens are not accepted")?;
+                let jwk = match &header.kid {
+                    Some(kid) => jwks.find(kid),
+                    // a token without key id is only unambiguous for a single key
+                    None if jwks.keys.len() == 1 => jwks.keys.first(),
+                    None => None,
+                }
+                .ok_or("No matching key found in the JWKS")?;
+                DecodingKey::from_jwk(jwk)?
+            }
+            alg => return Err(format!("Unsupported algorithm {:?}", alg).into()),
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let token_data = decode::<Claims>(token, &decoding_key, &validation)?;
+        info!("Successfully authenticated subject {}", token_data.claims.sub);
+        let mut principal = Principal::new(&token_data.claims.sub, AuthenticationMethod::Jwt);
+        // unknown roles of other applications sharing the identity provider are ignored
+        principal.roles = token_data.claims.roles.iter().filter_map(|role| Role::parse(role)).collect();
+        if let Some(tenant_id) = token_data.claims.tenant_id {
+            if !tenant::is_valid_tenant_id(&tenant_id) {
+                return Err(format!("Invalid tenant {}", tenant_id).into());
+            }
+            principal.tenant_id = Some(tenant_id);
+        }
+        Ok(principal)
+    }
+
+    /// Mints an HS256 token with the configured secret, e.g. for local testing.
+    pub fn mint_hs256_token(&self, subject: &str, roles: &[Role], expires_in_secs: u64) -> Result<String, Box<dyn std::error::Error>> {
+        let hs256_secret = self.hs256_secret.as_ref().ok_or("No HS256 secret configured")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let claims = Claims {
+            sub: String::from(subject),
+            exp: now + expires_in_secs,
+            iat: Some(now),
+            roles: roles.iter().map(|role| String::from(role.as_str())).collect(),
+            tenant_id: None,
+        };
+        Ok(encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(hs256_secret))?)
+    }
+}
+
+fn read_jwks_file(jwks_file: &str) -> Result<JwkSet, Box<dyn std::error::Error>> {
+    let jwks: JwkSet = serde_json::from_str(&std::fs::read_to_string(jwks_file)?)?;
+    info!("Successfully read {} keys from {}", jwks.keys.len(), jwks_file);
+    Ok(jwks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_hs256_token() {
+        let jwt_authenticator = JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length");
+        let token = jwt_authenticator.mint_hs256_token("user-1", &[Role::Uploader], 60).unwrap();
+
+        let principal = jwt_authenticator.authenticate(&token).unwrap();
+        assert_eq!(principal.subject, "user-1");
+        assert_eq!(principal.authentication_method, AuthenticationMethod::Jwt);
+        assert_eq!(principal.roles, vec![Role::Uploader]);
+        assert_eq!(principal.tenant_id, None);
+
+        let other_jwt_authenticator = JwtAuthenticator::with_hs256_secret(b"another-secret-of-at-least-32-bytes");
+        assert!(other_jwt_authenticator.authenticate(&token).is_err());
+        assert!(jwt_authenticator.authenticate("not-a-token").is_err());
+
+        // configured authenticators verify the same tokens, an empty configuration is rejected
+        let mut jwt_config = JwtConfig::default();
+        assert!(JwtAuthenticator::new(&jwt_config).is_err());
+        jwt_config.hs256_secret = String::from("a-secret-of-at-least-32-bytes-length");
+        assert_eq!(JwtAuthenticator::new(&jwt_config).unwrap().authenticate(&token).unwrap().subject, "user-1");
+    }
+
+    #[test]
+    fn test_rs256_token_from_jwks_file() {
+        let jwt_authenticator = JwtAuthenticator::with_jwks_file("assets/test_jwks.json").unwrap();
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(String::from("test"));
+        let claims = Claims {
+            sub: String::from("service-account"),
+            exp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 60,
+            iat: None,
+            roles: vec![String::from("admin"), String::from("unknown")],
+            tenant_id: Some(String::from("brand-a")),
+        };
+        let encoding_key =
+            EncodingKey::from_rsa_pem(&std::fs::read("assets/test_rs256_private_key.pem").unwrap()).unwrap();
+        let token = encode(&header, &claims, &encoding_key).unwrap();
+
+        let principal = jwt_authenticator.authenticate(&token).unwrap();
+        assert_eq!(principal.subject, "service-account");
+        assert_eq!(principal.roles, vec![Role::Admin]);
+        assert_eq!(principal.tenant_id, Some(String::from("brand-a")));
+
+        // HS256 tokens must not be verified with a public key
+        let hs256_token = JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length")
+            .mint_hs256_token("user-1", &[Role::Uploader], 60)
+            .unwrap();
+        assert!(jwt_authenticator.authenticate(&hs256_token).is_err());
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let jwt_authenticator = JwtAuthenticator::with_hs256_secret(b"a-secret-of-at-least-32-bytes-length");
+        let claims = Claims { sub: String::from("user-1"), exp: 1, iat: None, roles: Vec::new(), tenant_id: None };
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(b"a-secret-of-at-least-32-bytes-length"),
+        )
+        .unwrap();
+        assert!(jwt_authenticator.authenticate(&token).is_err());
+    }
+}
@@ -22,132 +22,353 @@
 
 
 use aws_sdk_s3::{
+    config::{Credentials, Region},
     error::SdkError,
     operation::{
-        create_bucket::{CreateBucketError, CreateBucketOutput},
         get_object::{GetObjectError, GetObjectOutput},
-        put_object::{PutObjectError, PutObjectOutput},
+        head_bucket::{HeadBucketError, HeadBucketOutput},
+        put_object::PutObjectOutput,
     },
     primitives::ByteStream,
-    types::{BucketLocationConstraint, CreateBucketConfiguration},
+    types::{Delete, ObjectIdentifier},
     Client, Error,
 };
 use bytes::Bytes;
+use futures::TryStreamExt;
 use std::fs;
 use std::{
-    env,
     io::{self, Write},
     path::Path,
 };
 
-use log::info;
-use uuid::Uuid;
+use tracing::{info, warn};
+
+use crate::envelope_encryption::EnvelopeEncryption;
+use crate::operation_metrics::observe;
+use serde::Deserialize;
+
+// maximum number of keys accepted by a single DeleteObjects request
+const MAX_KEYS_PER_DELETE_REQUEST: usize = 1000;
+
+/// Without an access key the default AWS credential chain is used, e.g. an instance profile.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AwsS3Config {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub region: String,
+    // empty for AWS itself, set for S3 compatible storage
+    pub endpoint_url: String,
+    pub bucket_name: String,
+}
 
 #[derive(Clone)]
 pub struct AwsS3BucketConnector {
     bucket_name: Option<String>,
     storage_client: Option<Client>,
+    // prepended to every blob name, empty for blobs at the root of the bucket
+    key_prefix: String,
+    // blobs are stored in plain form without it
+    envelope_encryption: Option<EnvelopeEncryption>,
 }
 
 impl AwsS3BucketConnector {
-    pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        std::env::var("AWS_ACCESS_KEY_ID").expect("AWS_ACCESS_KEY_ID environment variable expected");
-        std::env::var("AWS_SECRET_ACCESS_KEY").expect("AWS_SECRET_ACCESS_KEY environment variable expected");
-        std::env::var("AWS_DEFAULT_REGION").expect("AWS_DEFAULT_REGION environment variable expected");
-        std::env::var("AWS_ENDPOINT_URL").expect("AWS_ENDPOINT_URL environment variable expected");
-        let bucket_name =
-            std::env::var("AWS_BUCKET_NAME").expect("AWS_BUCKET_NAME environment variable expected");
-
-        let config = aws_config::load_from_env().await;
+    /// Identifies the backend in `ContainerMeta::storage_backend`
+    pub const STORAGE_BACKEND: &str = "aws_s3";
+
+    pub async fn new(aws_s3_config: &AwsS3Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config_loader = aws_config::from_env().region(Region::new(aws_s3_config.region.clone()));
+        if !aws_s3_config.endpoint_url.is_empty() {
+            config_loader = config_loader.endpoint_url(&aws_s3_config.endpoint_url);
+        }
+        if !aws_s3_config.access_key_id.is_empty() {
+            config_loader = config_loader.credentials_provider(Credentials::new(
+                &aws_s3_config.access_key_id,
+                &aws_s3_config.secret_access_key,
+                None,
+                None,
+                "app_config",
+            ));
+        }
+
+        let config = config_loader.load().await;
         Ok(AwsS3BucketConnector {
-            bucket_name: Some(String::from(bucket_name)),
+            bucket_name: Some(aws_s3_config.bucket_name.clone()),
             storage_client: Some(Client::new(&config)),
+            key_prefix: String::from(""),
+            envelope_encryption: None,
         })
     }
 
-    pub async fn get_object(
+    /// Returns a connector encrypting uploaded blobs and decrypting them on retrieval. Blobs stored
+    /// before the encryption was enabled are still returned as they are.
+    pub fn with_envelope_encryption(&self, envelope_encryption: EnvelopeEncryption) -> Self {
+        let mut aws_s3_bucket_connector = self.clone();
+        aws_s3_bucket_connector.envelope_encryption = Some(envelope_encryption);
+        aws_s3_bucket_connector
+    }
+
+    /// Returns a connector storing blobs below `<tenant_id>/`. Blob names passed to and returned
+    /// from the connector stay relative to that prefix.
+    pub fn for_tenant(&self, tenant_id: &str) -> Self {
+        let mut aws_s3_bucket_connector = self.clone();
+        aws_s3_bucket_connector.key_prefix = format!("{}/", tenant_id);
+        aws_s3_bucket_connector
+    }
+
+    fn blob_key(&self, blob_name: &str) -> String {
+        format!("{}{}", self.key_prefix, blob_name)
+    }
+
+    /// Verifies the bucket is reachable with the configured credentials.
+    pub async fn check_health(&self) -> Result<HeadBucketOutput, SdkError<HeadBucketError>> {
+        let head_bucket = self.storage_client
+            .as_ref()
+            .unwrap()
+            .head_bucket()
+            .bucket(self.bucket_name.as_ref().unwrap())
+            .send();
+        observe(Self::STORAGE_BACKEND, "head_bucket", head_bucket).await
+    }
+
+    async fn send_get_object(
         &self,
         blob_name: &str,
+        range: Option<String>,
     ) -> Result<GetObjectOutput, SdkError<GetObjectError>> {
-        self.storage_client
+        let get_object = self.storage_client
             .as_ref()
             .unwrap()
             .get_object()
             .bucket(self.bucket_name.as_ref().unwrap())
-            .key(blob_name)
-            .send()
-            .await
+            .key(self.blob_key(blob_name))
+            .set_range(range)
+            .send();
+        observe(Self::STORAGE_BACKEND, "get_object", get_object).await
+    }
+
+    /// Encrypted blobs are decrypted as a whole, the returned body holds the plain bytes.
+    pub async fn get_object(
+        &self,
+        blob_name: &str,
+    ) -> Result<GetObjectOutput, Box<dyn std::error::Error>> {
+        let mut get_object_output = self.send_get_object(blob_name, None).await?;
+        let envelope_encryption = match self.envelope_encryption.as_ref() {
+            Some(envelope_encryption) => envelope_encryption,
+            None => return Ok(get_object_output),
+        };
+
+        let bytes = get_object_output.body.collect().await?.into_bytes();
+        let bytes = if EnvelopeEncryption::is_encrypted(&bytes) {
+            Bytes::from(envelope_encryption.decrypt(&bytes)?)
+        } else {
+            bytes
+        };
+        get_object_output.content_length = bytes.len() as i64;
+        get_object_output.body = ByteStream::from(bytes);
+        Ok(get_object_output)
+    }
+
+    async fn get_range_bytes(
+        &self,
+        blob_name: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, Box<dyn std::error::Error>> {
+        let get_object_output = self.send_get_object(blob_name, Some(format!("bytes={}-{}", start, end))).await?;
+        Ok(get_object_output.body.collect().await?.into_bytes())
+    }
+
+    /// Returns the plain bytes `start..=end` of the blob. Of encrypted blobs only the header and the
+    /// chunks covering the range are downloaded.
+    pub async fn get_object_range(
+        &self,
+        blob_name: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, Box<dyn std::error::Error>> {
+        let envelope_encryption = match self.envelope_encryption.as_ref() {
+            Some(envelope_encryption) => envelope_encryption,
+            None => return self.get_range_bytes(blob_name, start, end).await,
+        };
+        let bytes = envelope_encryption
+            .read_range(|start, end| async move { Ok(self.get_range_bytes(blob_name, start, end).await?.to_vec()) }, start, end)
+            .await?;
+        Ok(Bytes::from(bytes))
     }
 
     pub async fn upload_blob(
         &self,
         blob_name: &str,
         file_name: &str,
-    ) -> Result<PutObjectOutput, SdkError<PutObjectError>> {
-        let body = ByteStream::from_path(Path::new(file_name)).await;
-        let put_object_output = self
+    ) -> Result<PutObjectOutput, Box<dyn std::error::Error>> {
+        let encrypted_file_name = match self.envelope_encryption.clone() {
+            Some(envelope_encryption) => {
+                let file_name = String::from(file_name);
+                Some(tokio::task::spawn_blocking(move || envelope_encryption.encrypt_file(&file_name)).await??)
+            }
+            None => None,
+        };
+        let body = ByteStream::from_path(Path::new(encrypted_file_name.as_deref().unwrap_or(file_name))).await;
+        let put_object = self
             .storage_client
             .as_ref()
             .unwrap()
             .put_object()
             .bucket(self.bucket_name.as_ref().unwrap())
-            .key(blob_name)
+            .key(self.blob_key(blob_name))
             .body(body.unwrap())
-            .send()
-            .await;
+            .send();
+        let put_object_output = observe(Self::STORAGE_BACKEND, "put_object", put_object).await;
+        if let Some(encrypted_file_name) = encrypted_file_name {
+            if let Err(err) = fs::remove_file(&encrypted_file_name) {
+                warn!("Failed to remove encrypted file {}: {}", encrypted_file_name, err);
+            }
+        }
         info!("Successfully uploaded blob {}", blob_name);
-        put_object_output
+        Ok(put_object_output?)
     }
 
     pub async fn upload_bytes(
         &self,
         blob_name: &str,
         bytes: Vec<u8>,
-    ) -> Result<PutObjectOutput, SdkError<PutObjectError>> {
-        let body = ByteStream::from(bytes);
-        let put_object_output = self
+    ) -> Result<PutObjectOutput, Box<dyn std::error::Error>> {
+        let body = match self.envelope_encryption.as_ref() {
+            Some(envelope_encryption) => ByteStream::from(envelope_encryption.encrypt(&bytes)?),
+            None => ByteStream::from(bytes),
+        };
+        let put_object = self
             .storage_client
             .as_ref()
             .unwrap()
             .put_object()
             .bucket(self.bucket_name.as_ref().unwrap())
-            .key(blob_name)
+            .key(self.blob_key(blob_name))
             .body(body)
-            .send()
-            .await;
+            .send();
+        let put_object_output = observe(Self::STORAGE_BACKEND, "put_object", put_object).await?;
         info!("Successfully uploaded blob {}", blob_name);
-        put_object_output
+        Ok(put_object_output)
     }
 
     pub async fn write_bytes_to_file(&self, bytes: &Bytes, file_path: &str) -> Result<(), io::Error> {
         let mut file = fs::OpenOptions::new()
             .create(true) // To create a new file
             .write(true)
+            .truncate(true)
             .open(file_path)?;
 
-        file.write_all(&bytes)?;
+        file.write_all(bytes)?;
         info!("Successfully created file {}", file_path);
         Ok(())
     }
 
+    /// Writes the blob to `file_path` chunk by chunk as it is received. Encrypted blobs are decrypted as a whole.
+    pub async fn download_blob(&self, blob_name: &str, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut body = self.get_object(blob_name).await?.body;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(file_path)?;
+        while let Some(bytes) = body.try_next().await? {
+            file.write_all(&bytes)?;
+        }
+        info!("Successfully downloaded blob {}", blob_name);
+        Ok(())
+    }
+
+    pub async fn list_blob_names(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let mut blob_names = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let list_objects = self
+                .storage_client
+                .as_ref()
+                .unwrap()
+                .list_objects_v2()
+                .bucket(self.bucket_name.as_ref().unwrap())
+                .prefix(self.blob_key(prefix))
+                .set_continuation_token(continuation_token)
+                .send();
+            let list_objects_output = observe(Self::STORAGE_BACKEND, "list_objects", list_objects).await?;
+
+            for object in list_objects_output.contents().unwrap_or_default() {
+                if let Some(key) = object.key().and_then(|key| key.strip_prefix(self.key_prefix.as_str())) {
+                    blob_names.push(String::from(key));
+                }
+            }
+
+            if !list_objects_output.is_truncated() {
+                break;
+            }
+            continuation_token = list_objects_output.next_continuation_token().map(String::from);
+        }
+
+        info!("Successfully listed {} blobs with prefix {}", blob_names.len(), prefix);
+        Ok(blob_names)
+    }
+
     pub async fn delete_blob(&self, blob_name: &str) -> Result<(), Error> {
-        self.storage_client
+        let delete_object = self.storage_client
             .as_ref()
             .unwrap()
             .delete_object()
             .bucket(self.bucket_name.as_ref().unwrap())
-            .key(blob_name)
-            .send()
-            .await?;
+            .key(self.blob_key(blob_name))
+            .send();
+        observe(Self::STORAGE_BACKEND, "delete_object", delete_object).await?;
 
         info!("Successfully deleted blob {}", blob_name);
         Ok(())
     }
+
+    /// Deletes every blob below the prefix in batches. Already deleted blobs are not treated as failure.
+    pub async fn delete_blobs_by_prefix(&self, prefix: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let blob_names = self.list_blob_names(prefix).await?;
+        let mut failed_blob_names = Vec::new();
+
+        for chunk in blob_names.chunks(MAX_KEYS_PER_DELETE_REQUEST) {
+            let object_identifiers = chunk
+                .iter()
+                .map(|blob_name| ObjectIdentifier::builder().key(self.blob_key(blob_name)).build())
+                .collect();
+            let delete_objects = self
+                .storage_client
+                .as_ref()
+                .unwrap()
+                .delete_objects()
+                .bucket(self.bucket_name.as_ref().unwrap())
+                .delete(Delete::builder().set_objects(Some(object_identifiers)).quiet(true).build())
+                .send();
+            let delete_objects_output = observe(Self::STORAGE_BACKEND, "delete_objects", delete_objects).await?;
+
+            for error in delete_objects_output.errors().unwrap_or_default() {
+                warn!(
+                    "Failed to delete blob {}: {}",
+                    error.key().unwrap_or_default(),
+                    error.message().unwrap_or_default()
+                );
+                failed_blob_names.push(String::from(error.key().unwrap_or_default()));
+            }
+        }
+
+        if !failed_blob_names.is_empty() {
+            return Err(format!("Failed to delete blobs {}", failed_blob_names.join(", ")).into());
+        }
+        info!("Successfully deleted {} blobs with prefix {}", blob_names.len(), prefix);
+        Ok(blob_names.len())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::envelope_encryption::KeyfileKms;
+    use std::env;
+    use std::sync::Arc;
+    use uuid::Uuid;
 
     // In order to run the test execute: `RUST_LOG=info cargo test`
     #[tokio::test]
@@ -156,7 +377,15 @@ mod tests {
 
         let env_file_path = "./assets/aws-secrets.dev.cfg";
         dotenv::from_path(env_file_path).ok();
-        let aws_s3_bucket_connector = Box::new(AwsS3BucketConnector::new().await.unwrap());
+        let aws_s3_config = AwsS3Config {
+            access_key_id: env::var("AWS_ACCESS_KEY_ID").unwrap_or_default(),
+            secret_access_key: env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default(),
+            region: env::var("AWS_DEFAULT_REGION").unwrap_or_default(),
+            endpoint_url: env::var("AWS_ENDPOINT_URL").unwrap_or_default(),
+            bucket_name: env::var("AWS_BUCKET_NAME").unwrap_or_default(),
+        };
+        let aws_s3_bucket_connector = Box::new(AwsS3BucketConnector::new(&aws_s3_config).await.unwrap());
+        assert!(aws_s3_bucket_connector.check_health().await.is_ok());
 
         let upload_file_path = "assets/sample.txt";
         let download_file_path = "temp/sample-aws-copy.txt";
@@ -166,21 +395,84 @@ mod tests {
             .upload_blob(&blob_name, upload_file_path)
             .await;
         assert!(upload_blob_result.is_ok());
+        let list_blob_names_result = aws_s3_bucket_connector
+            .list_blob_names(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(list_blob_names_result.unwrap(), vec![blob_name.clone()]);
         let get_object_output = aws_s3_bucket_connector.get_object(&blob_name).await;
         assert!(get_object_output.is_ok());
-        let bytes = get_object_output?
+        let bytes = get_object_output
+            .unwrap()
             .body
             .collect()
             .await
             .unwrap()
             .into_bytes(); // retrieve bytes
-        assert!(bytes.len() > 0);
+        assert!(!bytes.is_empty());
         let write_bytes_to_file_result = aws_s3_bucket_connector
             .write_bytes_to_file(&bytes, download_file_path)
             .await;
         assert!(write_bytes_to_file_result.is_ok());
         let delete_blob_result = aws_s3_bucket_connector.delete_blob(&blob_name).await;
         assert!(delete_blob_result.is_ok());
+
+        // deleting by prefix removes every blob of the container and tolerates already deleted ones
+        let upload_blob_result = aws_s3_bucket_connector
+            .upload_blob(&(uuid.to_string() + "/thumbnails/sample.txt"), upload_file_path)
+            .await;
+        assert!(upload_blob_result.is_ok());
+        let delete_blobs_result = aws_s3_bucket_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 1);
+        let delete_blobs_result = aws_s3_bucket_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 0);
+
+        // blobs of a tenant are stored below its prefix while names stay relative to it
+        let tenant_aws_s3_bucket_connector = aws_s3_bucket_connector.for_tenant("tenant-a");
+        let upload_blob_result = tenant_aws_s3_bucket_connector
+            .upload_blob(&blob_name, upload_file_path)
+            .await;
+        assert!(upload_blob_result.is_ok());
+        let list_blob_names_result = tenant_aws_s3_bucket_connector
+            .list_blob_names(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(list_blob_names_result.unwrap(), vec![blob_name.clone()]);
+        let list_blob_names_result = aws_s3_bucket_connector
+            .list_blob_names(&(String::from("tenant-a/") + &uuid.to_string() + "/"))
+            .await;
+        assert_eq!(list_blob_names_result.unwrap(), vec![String::from("tenant-a/") + &blob_name]);
+        let delete_blobs_result = tenant_aws_s3_bucket_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 1);
+
+        // encrypted blobs are decrypted on retrieval, also range-wise, while plain blobs pass through
+        let envelope_encryption = EnvelopeEncryption::new(Arc::new(KeyfileKms::new(&[7u8; 32]).unwrap()), 4);
+        let encrypting_aws_s3_bucket_connector = aws_s3_bucket_connector.with_envelope_encryption(envelope_encryption);
+        let plain_bytes = fs::read(upload_file_path).unwrap();
+        let upload_blob_result = encrypting_aws_s3_bucket_connector
+            .upload_blob(&blob_name, upload_file_path)
+            .await;
+        assert!(upload_blob_result.is_ok());
+        let stored_bytes = aws_s3_bucket_connector.get_object(&blob_name).await.unwrap().body.collect().await.unwrap().into_bytes();
+        assert!(EnvelopeEncryption::is_encrypted(&stored_bytes));
+        let bytes = encrypting_aws_s3_bucket_connector.get_object(&blob_name).await.unwrap().body.collect().await.unwrap().into_bytes();
+        assert_eq!(bytes.to_vec(), plain_bytes);
+        let get_object_range_result = encrypting_aws_s3_bucket_connector.get_object_range(&blob_name, 2, 9).await;
+        assert_eq!(get_object_range_result.unwrap().to_vec(), plain_bytes[2..=9].to_vec());
+        let upload_bytes_result = aws_s3_bucket_connector
+            .upload_bytes(&(uuid.to_string() + "/plain.txt"), plain_bytes.clone())
+            .await;
+        assert!(upload_bytes_result.is_ok());
+        let bytes = encrypting_aws_s3_bucket_connector.get_object(&(uuid.to_string() + "/plain.txt")).await.unwrap().body.collect().await.unwrap().into_bytes();
+        assert_eq!(bytes.to_vec(), plain_bytes);
+        let delete_blobs_result = aws_s3_bucket_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 2);
         Ok(())
     }
 }
@@ -26,41 +26,80 @@ use std::{
     io::{BufReader, Read, Write},
 };
 
-use azure_core::Error;
+use azure_core::{error::ErrorKind, Error, StatusCode};
 use azure_storage::prelude::*;
 use azure_storage_blobs::prelude::*;
-use bytes::Bytes;
-use log::info;
-use uuid::Uuid;
+use futures::StreamExt;
+use tracing::info;
+use serde::Deserialize;
+
+use crate::envelope_encryption::EnvelopeEncryption;
+use crate::operation_metrics::observe;
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AzureBlobStorageConfig {
+    pub access_key: String,
+    pub account_name: String,
+    pub container_name: String,
+}
 
 #[derive(Clone)]
 pub struct AzureBlobStorageAccountConnector {
     container_client: Option<ContainerClient>,
+    // prepended to every blob name, empty for blobs at the root of the container
+    key_prefix: String,
+    // blobs are stored in plain form without it
+    envelope_encryption: Option<EnvelopeEncryption>,
 }
 
 impl AzureBlobStorageAccountConnector {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {    
-        let azure_access_key =
-            std::env::var("AZURE_ACCESS_KEY").expect("AZURE_ACCESS_KEY environment variable expected");
-        let azure_account_name =
-            std::env::var("AZURE_ACCOUNT_NAME").expect("AZURE_ACCOUNT_NAME environment variable expected");
-        let azure_container_name =
-            std::env::var("AZURE_CONTAINER_NAME").expect("AZURE_CONTAINER_NAME environment variable expected");
-        let storage_credentials = StorageCredentials::access_key(azure_account_name.clone(), azure_access_key);
+    /// Identifies the backend in `ContainerMeta::storage_backend`
+    pub const STORAGE_BACKEND: &str = "azure_blob_storage";
+
+    pub fn new(azure_blob_storage_config: &AzureBlobStorageConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let storage_credentials = StorageCredentials::access_key(
+            azure_blob_storage_config.account_name.clone(),
+            azure_blob_storage_config.access_key.clone(),
+        );
         Ok(AzureBlobStorageAccountConnector {
             container_client: Some(
-                ClientBuilder::new(azure_account_name, storage_credentials)
-                    .container_client(azure_container_name),
+                ClientBuilder::new(azure_blob_storage_config.account_name.clone(), storage_credentials)
+                    .container_client(azure_blob_storage_config.container_name.clone()),
             ),
+            key_prefix: String::from(""),
+            envelope_encryption: None,
         })
     }
 
+    /// Returns a connector encrypting uploaded blobs and decrypting them on retrieval. Blobs stored
+    /// before the encryption was enabled are still returned as they are.
+    pub fn with_envelope_encryption(&self, envelope_encryption: EnvelopeEncryption) -> Self {
+        let mut azure_blob_storage_account_connector = self.clone();
+        azure_blob_storage_account_connector.envelope_encryption = Some(envelope_encryption);
+        azure_blob_storage_account_connector
+    }
+
+    /// Returns a connector storing blobs below `<tenant_id>/`. Blob names passed to and returned
+    /// from the connector stay relative to that prefix.
+    pub fn for_tenant(&self, tenant_id: &str) -> Self {
+        let mut azure_blob_storage_account_connector = self.clone();
+        azure_blob_storage_account_connector.key_prefix = format!("{}/", tenant_id);
+        azure_blob_storage_account_connector
+    }
+
+    /// Verifies the container is reachable with the configured credentials.
+    pub async fn check_health(&self) -> Result<(), Error> {
+        observe(Self::STORAGE_BACKEND, "get_properties", self.container_client.as_ref().unwrap().get_properties().into_future()).await?;
+        Ok(())
+    }
+
     pub(crate) fn get_blob_client(&self, blob_name: &str) -> Option<BlobClient> {
         let blob_client = self
             .container_client
             .as_ref()
             .unwrap()
-            .blob_client(blob_name);
+            .blob_client(format!("{}{}", self.key_prefix, blob_name));
         Some(blob_client)
     }
 
@@ -70,15 +109,45 @@ impl AzureBlobStorageAccountConnector {
         let mut reader = BufReader::new(f);
         let mut buffer = Vec::new();
         reader.read_to_end(&mut buffer)?;
-        blob_client.put_block_blob(buffer).await?;
+        if let Some(envelope_encryption) = self.envelope_encryption.as_ref() {
+            buffer = envelope_encryption.encrypt(&buffer).map_err(|err| Error::new(ErrorKind::DataConversion, err))?;
+        }
+        observe(Self::STORAGE_BACKEND, "put_block_blob", blob_client.put_block_blob(buffer).into_future()).await?;
         info!("Successfully uploaded blob {}", blob_name);
         Ok(())
     }
 
     pub async fn retrieve_bytes(&self, blob_name: &str) -> Result<Vec<u8>, Error> {
         let blob_client = self.get_blob_client(blob_name).unwrap();
-        let data = blob_client.get_content().await?;
-        Ok(data)
+        let data = observe(Self::STORAGE_BACKEND, "get_content", blob_client.get_content()).await?;
+        match self.envelope_encryption.as_ref() {
+            Some(envelope_encryption) if EnvelopeEncryption::is_encrypted(&data) => {
+                envelope_encryption.decrypt(&data).map_err(|err| Error::new(ErrorKind::DataConversion, err))
+            }
+            _ => Ok(data),
+        }
+    }
+
+    async fn retrieve_range_bytes(&self, blob_name: &str, start: u64, end: u64) -> Result<Vec<u8>, Error> {
+        let blob_client = self.get_blob_client(blob_name).unwrap();
+        let mut bytes = Vec::new();
+        let mut responses = blob_client.get().range(start..end + 1).into_stream();
+        while let Some(response) = observe(Self::STORAGE_BACKEND, "get_blob", async { responses.next().await.transpose() }).await? {
+            bytes.extend(response.data.collect().await?);
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the plain bytes `start..=end` of the blob. Of encrypted blobs only the header and the
+    /// chunks covering the range are downloaded.
+    pub async fn retrieve_range(&self, blob_name: &str, start: u64, end: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let envelope_encryption = match self.envelope_encryption.as_ref() {
+            Some(envelope_encryption) => envelope_encryption,
+            None => return Ok(self.retrieve_range_bytes(blob_name, start, end).await?),
+        };
+        envelope_encryption
+            .read_range(|start, end| async move { Ok(self.retrieve_range_bytes(blob_name, start, end).await?) }, start, end)
+            .await
     }
 
     pub async fn download_blob(&self, blob_name: &str, file_path: &str) -> Result<(), Error> {
@@ -87,6 +156,7 @@ impl AzureBlobStorageAccountConnector {
         let mut file = fs::OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .open(file_path)?;
 
         file.write_all(&data)?;
@@ -96,15 +166,56 @@ impl AzureBlobStorageAccountConnector {
 
     pub async fn delete_blob(&self, blob_name: &str) -> Result<(), Error> {
         let blob_client = self.get_blob_client(blob_name).unwrap();
-        blob_client.delete().await?;
-        info!("Successfully deleted blob {}", blob_name);
+        match observe(Self::STORAGE_BACKEND, "delete_blob", blob_client.delete().into_future()).await {
+            Ok(_) => info!("Successfully deleted blob {}", blob_name),
+            // deleting is idempotent like on S3, a missing blob was already deleted
+            Err(err) if matches!(err.kind(), ErrorKind::HttpResponse { status: StatusCode::NotFound, .. }) => {
+                info!("Blob {} was already deleted", blob_name)
+            }
+            Err(err) => return Err(err),
+        }
         Ok(())
     }
+
+    pub async fn list_blob_names(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        let mut blob_names = Vec::new();
+        let mut pages = self
+            .container_client
+            .as_ref()
+            .unwrap()
+            .list_blobs()
+            .prefix(format!("{}{}", self.key_prefix, prefix))
+            .into_stream();
+        while let Some(page) = observe(Self::STORAGE_BACKEND, "list_blobs", async { pages.next().await.transpose() }).await? {
+            for blob in page.blobs.blobs() {
+                if let Some(blob_name) = blob.name.strip_prefix(self.key_prefix.as_str()) {
+                    blob_names.push(String::from(blob_name));
+                }
+            }
+        }
+
+        info!("Successfully listed {} blobs with prefix {}", blob_names.len(), prefix);
+        Ok(blob_names)
+    }
+
+    /// Deletes every blob below the prefix. Already deleted blobs are not treated as failure.
+    pub async fn delete_blobs_by_prefix(&self, prefix: &str) -> Result<usize, Error> {
+        let blob_names = self.list_blob_names(prefix).await?;
+        for blob_name in blob_names.iter() {
+            self.delete_blob(blob_name).await?;
+        }
+
+        info!("Successfully deleted {} blobs with prefix {}", blob_names.len(), prefix);
+        Ok(blob_names.len())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::envelope_encryption::KeyfileKms;
+    use std::sync::Arc;
+    use uuid::Uuid;
 
     // In order to run the test execute: `RUST_LOG=info cargo test`
     #[tokio::test]
@@ -114,8 +225,14 @@ mod tests {
         let env_file_path = "./assets/az-secrets.dev.cfg";
         dotenv::from_path(env_file_path).ok();
 
+        let azure_blob_storage_config = AzureBlobStorageConfig {
+            access_key: std::env::var("AZURE_ACCESS_KEY").unwrap_or_default(),
+            account_name: std::env::var("AZURE_ACCOUNT_NAME").unwrap_or_default(),
+            container_name: std::env::var("AZURE_CONTAINER_NAME").unwrap_or_default(),
+        };
         let azure_blob_storage_account_connector = 
-            Box::new(AzureBlobStorageAccountConnector::new().unwrap());
+            Box::new(AzureBlobStorageAccountConnector::new(&azure_blob_storage_config).unwrap());
+        assert!(azure_blob_storage_account_connector.check_health().await.is_ok());
 
         let upload_file_path = "./assets/sample.txt";
         let download_file_path = "./temp/sample-azure-copy.txt";
@@ -129,6 +246,42 @@ mod tests {
             .download_blob(&blob_name, download_file_path)
             .await;
         assert!(download_blob_result.is_ok());
+        let delete_blobs_result = azure_blob_storage_account_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 1);
+        let delete_blob_result = azure_blob_storage_account_connector
+            .delete_blob(&blob_name)
+            .await;
+        assert!(delete_blob_result.is_ok());
+
+        // blobs of a tenant are stored below its prefix while names stay relative to it
+        let tenant_azure_blob_storage_account_connector = azure_blob_storage_account_connector.for_tenant("tenant-a");
+        let upload_blob_result = tenant_azure_blob_storage_account_connector
+            .upload_blob(&blob_name, upload_file_path)
+            .await;
+        assert!(upload_blob_result.is_ok());
+        let list_blob_names_result = azure_blob_storage_account_connector
+            .list_blob_names(&(String::from("tenant-a/") + &uuid.to_string() + "/"))
+            .await;
+        assert_eq!(list_blob_names_result.unwrap(), vec![String::from("tenant-a/") + &blob_name]);
+        let delete_blobs_result = tenant_azure_blob_storage_account_connector
+            .delete_blobs_by_prefix(&(uuid.to_string() + "/"))
+            .await;
+        assert_eq!(delete_blobs_result.unwrap(), 1);
+
+        // encrypted blobs are decrypted range-wise, reading only the chunks covering the range
+        let envelope_encryption = EnvelopeEncryption::new(Arc::new(KeyfileKms::new(&[7u8; 32]).unwrap()), 4);
+        let encrypting_azure_blob_storage_account_connector = azure_blob_storage_account_connector.with_envelope_encryption(envelope_encryption);
+        let upload_blob_result = encrypting_azure_blob_storage_account_connector
+            .upload_blob(&blob_name, upload_file_path)
+            .await;
+        assert!(upload_blob_result.is_ok());
+        let plain_bytes = std::fs::read(upload_file_path)?;
+        let stored_bytes = azure_blob_storage_account_connector.retrieve_bytes(&blob_name).await?;
+        assert!(EnvelopeEncryption::is_encrypted(&stored_bytes));
+        let retrieve_range_result = encrypting_azure_blob_storage_account_connector.retrieve_range(&blob_name, 2, 9).await;
+        assert_eq!(retrieve_range_result.unwrap(), plain_bytes[2..=9].to_vec());
         let delete_blob_result = azure_blob_storage_account_connector
             .delete_blob(&blob_name)
             .await;
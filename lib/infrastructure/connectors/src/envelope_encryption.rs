@@ -0,0 +1,547 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    fs::{self, File},
+    future::Future,
+    io::{BufReader, BufWriter, Read, Write},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+// identifies encrypted objects and the version of the layout below
+const MAGIC: &[u8; 8] = b"MMSENC01";
+const TAG_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const NONCE_PREFIX_LEN: usize = 7;
+const DATA_KEY_LEN: usize = 32;
+const MAX_WRAPPED_KEY_LEN: usize = 1024;
+// magic, chunk size, plaintext length, nonce prefix and the lengths of key id and wrapped key
+const FIXED_HEADER_LEN: usize = MAGIC.len() + 4 + 8 + NONCE_PREFIX_LEN + 1 + 2;
+/// Upper bound of the header, reading this many bytes from the start of an object is enough to parse it
+pub const MAX_HEADER_LEN: usize = FIXED_HEADER_LEN + u8::MAX as usize + MAX_WRAPPED_KEY_LEN;
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EncryptionConfig {
+    // hex encoded 256 bit key-encryption key, empty to store blobs unencrypted
+    pub keyfile: String,
+    // plaintext bytes per encrypted chunk, the granularity of ranged reads
+    pub chunk_size_bytes: usize,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        EncryptionConfig {
+            keyfile: String::from(""),
+            chunk_size_bytes: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EncryptionError {
+    message: String,
+}
+
+impl EncryptionError {
+    pub fn new(message: &str) -> Self {
+        EncryptionError {
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+impl From<std::io::Error> for EncryptionError {
+    fn from(err: std::io::Error) -> Self {
+        EncryptionError::new(&err.to_string())
+    }
+}
+
+/// Wraps the per-object data keys with a key-encryption key which never leaves the key management,
+/// e.g. a local keyfile or a cloud KMS.
+pub trait KeyManagementService: Send + Sync {
+    /// Identifies the key-encryption key, stored with every object to unwrap its data key later on
+    fn key_id(&self) -> &str;
+
+    fn wrap_key(&self, data_key: &[u8]) -> Result<Vec<u8>, EncryptionError>;
+
+    fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>, EncryptionError>;
+}
+
+/// Key-encryption key read from a local keyfile, data keys are wrapped with AES-256-GCM.
+pub struct KeyfileKms {
+    cipher: Aes256Gcm,
+    key_id: String,
+}
+
+impl KeyfileKms {
+    pub fn new(key_encryption_key: &[u8]) -> Result<Self, EncryptionError> {
+        if key_encryption_key.len() != DATA_KEY_LEN {
+            return Err(EncryptionError::new(&format!(
+                "key-encryption key has {} bytes, expected {}", key_encryption_key.len(), DATA_KEY_LEN)));
+        }
+        Ok(KeyfileKms {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key_encryption_key)),
+            // a digest prefix tells keys apart without revealing them
+            key_id: hex::encode(&Sha256::digest(key_encryption_key)[..8]),
+        })
+    }
+
+    pub fn from_keyfile(keyfile: &str) -> Result<Self, EncryptionError> {
+        let content = fs::read_to_string(keyfile)
+            .map_err(|err| EncryptionError::new(&format!("Failed to read keyfile {}: {}", keyfile, err)))?;
+        let key_encryption_key = hex::decode(content.trim())
+            .map_err(|err| EncryptionError::new(&format!("Keyfile {} is not hex encoded: {}", keyfile, err)))?;
+        Self::new(&key_encryption_key)
+    }
+}
+
+impl KeyManagementService for KeyfileKms {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn wrap_key(&self, data_key: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher
+            .encrypt(&nonce, Payload { msg: data_key, aad: self.key_id.as_bytes() })
+            .map_err(|_| EncryptionError::new("Failed to wrap data key"))?;
+        let mut wrapped_key = nonce.to_vec();
+        wrapped_key.extend_from_slice(&ciphertext);
+        Ok(wrapped_key)
+    }
+
+    fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        if key_id != self.key_id {
+            return Err(EncryptionError::new(&format!(
+                "Data key is wrapped with key {}, the keyfile holds key {}", key_id, self.key_id)));
+        }
+        if wrapped_key.len() < NONCE_LEN + TAG_LEN {
+            return Err(EncryptionError::new("Wrapped data key is truncated"));
+        }
+        let (nonce, ciphertext) = wrapped_key.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: self.key_id.as_bytes() })
+            .map_err(|_| EncryptionError::new("Failed to unwrap data key"))
+    }
+}
+
+/// Header in front of every encrypted object. The plaintext is split into chunks of `chunk_size` bytes,
+/// each sealed on its own with the header as associated data, so ranges can be decrypted without the
+/// preceding chunks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedHeader {
+    pub chunk_size: usize,
+    pub plaintext_len: u64,
+    pub key_id: String,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    wrapped_key: Vec<u8>,
+}
+
+impl EncryptedHeader {
+    /// Parses the header at the start of `bytes`, `None` for objects stored without encryption.
+    pub fn parse(bytes: &[u8]) -> Result<Option<Self>, EncryptionError> {
+        if !bytes.starts_with(MAGIC) {
+            return Ok(None);
+        }
+        let truncated = || EncryptionError::new("Encryption header is truncated");
+        if bytes.len() < FIXED_HEADER_LEN {
+            return Err(truncated());
+        }
+        let mut position = MAGIC.len();
+        let mut take = |len: usize| -> Result<&[u8], EncryptionError> {
+            let field = bytes.get(position..position + len).ok_or_else(truncated)?;
+            position += len;
+            Ok(field)
+        };
+        let chunk_size = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+        let plaintext_len = u64::from_be_bytes(take(8)?.try_into().unwrap());
+        let nonce_prefix: [u8; NONCE_PREFIX_LEN] = take(NONCE_PREFIX_LEN)?.try_into().unwrap();
+        let key_id_len = take(1)?[0] as usize;
+        let key_id = String::from_utf8(take(key_id_len)?.to_vec())
+            .map_err(|_| EncryptionError::new("Key id of the encryption header is not utf-8"))?;
+        let wrapped_key_len = u16::from_be_bytes(take(2)?.try_into().unwrap()) as usize;
+        if chunk_size == 0 || wrapped_key_len > MAX_WRAPPED_KEY_LEN {
+            return Err(EncryptionError::new("Encryption header is malformed"));
+        }
+        let wrapped_key = take(wrapped_key_len)?.to_vec();
+        Ok(Some(EncryptedHeader { chunk_size, plaintext_len, key_id, nonce_prefix, wrapped_key }))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(self.chunk_size as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.plaintext_len.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes.push(self.key_id.len() as u8);
+        bytes.extend_from_slice(self.key_id.as_bytes());
+        bytes.extend_from_slice(&(self.wrapped_key.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.wrapped_key);
+        bytes
+    }
+
+    pub fn encoded_len(&self) -> usize {
+        FIXED_HEADER_LEN + self.key_id.len() + self.wrapped_key.len()
+    }
+
+    /// Size of the stored object, the header followed by the sealed chunks.
+    pub fn ciphertext_len(&self) -> u64 {
+        self.chunk_end(self.chunk_count() - 1)
+    }
+
+    // an empty plaintext is still sealed in one chunk, so its tag authenticates the header
+    fn chunk_count(&self) -> u64 {
+        std::cmp::max(1, self.plaintext_len.div_ceil(self.chunk_size as u64))
+    }
+
+    fn chunk_plaintext_len(&self, index: u64) -> usize {
+        std::cmp::min(self.chunk_size as u64, self.plaintext_len - index * self.chunk_size as u64) as usize
+    }
+
+    fn chunk_offset(&self, index: u64) -> u64 {
+        self.encoded_len() as u64 + index * (self.chunk_size + TAG_LEN) as u64
+    }
+
+    // exclusive end of the sealed chunk, the last one is shorter unless the plaintext fills it
+    fn chunk_end(&self, index: u64) -> u64 {
+        self.chunk_offset(index) + (self.chunk_plaintext_len(index) + TAG_LEN) as u64
+    }
+
+    // the last chunk is flagged, which detects objects truncated at a chunk boundary
+    fn chunk_nonce(&self, index: u64) -> [u8; NONCE_LEN] {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&(index as u32).to_be_bytes());
+        nonce[NONCE_LEN - 1] = (index + 1 == self.chunk_count()) as u8;
+        nonce
+    }
+
+    // clamps the inclusive plaintext range to the object like HTTP ranges do
+    fn plaintext_range(&self, start: u64, end: u64) -> Result<(u64, u64), EncryptionError> {
+        if start > end || start >= self.plaintext_len {
+            return Err(EncryptionError::new(&format!(
+                "Range {}-{} is not satisfiable for {} bytes", start, end, self.plaintext_len)));
+        }
+        Ok((start, std::cmp::min(end, self.plaintext_len - 1)))
+    }
+
+    /// Inclusive range of the stored object holding the chunks of the plaintext bytes `start..=end`.
+    pub fn ciphertext_range(&self, start: u64, end: u64) -> Result<(u64, u64), EncryptionError> {
+        let (start, end) = self.plaintext_range(start, end)?;
+        let first_chunk = start / self.chunk_size as u64;
+        let last_chunk = end / self.chunk_size as u64;
+        Ok((self.chunk_offset(first_chunk), self.chunk_end(last_chunk) - 1))
+    }
+}
+
+/// Envelope encryption of blobs with AES-256-GCM. Every object gets its own data key, stored in the
+/// object header wrapped by the key management.
+#[derive(Clone)]
+pub struct EnvelopeEncryption {
+    key_management_service: Arc<dyn KeyManagementService>,
+    chunk_size: usize,
+}
+
+impl EnvelopeEncryption {
+    pub fn new(key_management_service: Arc<dyn KeyManagementService>, chunk_size: usize) -> Self {
+        EnvelopeEncryption {
+            key_management_service,
+            chunk_size,
+        }
+    }
+
+    /// Returns `None` if no keyfile is configured.
+    pub fn from_config(encryption_config: &EncryptionConfig) -> Result<Option<Self>, EncryptionError> {
+        if encryption_config.keyfile.is_empty() {
+            return Ok(None);
+        }
+        if encryption_config.chunk_size_bytes == 0 || encryption_config.chunk_size_bytes > u32::MAX as usize {
+            return Err(EncryptionError::new("Chunk size has to be between 1 byte and 4 GiB"));
+        }
+        let key_management_service = KeyfileKms::from_keyfile(&encryption_config.keyfile)?;
+        Ok(Some(Self::new(Arc::new(key_management_service), encryption_config.chunk_size_bytes)))
+    }
+
+    pub fn is_encrypted(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+
+    fn new_header(&self, plaintext_len: u64) -> Result<(EncryptedHeader, Aes256Gcm), EncryptionError> {
+        let data_key = Aes256Gcm::generate_key(&mut OsRng);
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        let header = EncryptedHeader {
+            chunk_size: self.chunk_size,
+            plaintext_len,
+            key_id: String::from(self.key_management_service.key_id()),
+            nonce_prefix,
+            wrapped_key: self.key_management_service.wrap_key(&data_key)?,
+        };
+        if header.chunk_count() > u32::MAX as u64 {
+            return Err(EncryptionError::new("Too many chunks, the chunk size is too small for the object"));
+        }
+        if header.key_id.len() > u8::MAX as usize || header.wrapped_key.len() > MAX_WRAPPED_KEY_LEN {
+            return Err(EncryptionError::new("Key id or wrapped data key are too long for the encryption header"));
+        }
+        Ok((header, Aes256Gcm::new(&data_key)))
+    }
+
+    fn data_cipher(&self, header: &EncryptedHeader) -> Result<Aes256Gcm, EncryptionError> {
+        let data_key = self.key_management_service.unwrap_key(&header.key_id, &header.wrapped_key)?;
+        if data_key.len() != DATA_KEY_LEN {
+            return Err(EncryptionError::new("Unwrapped data key has an unexpected length"));
+        }
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key)))
+    }
+
+    fn encrypt_to(&self, reader: &mut impl Read, plaintext_len: u64, writer: &mut impl Write) -> Result<(), EncryptionError> {
+        let (header, cipher) = self.new_header(plaintext_len)?;
+        let aad = header.to_bytes();
+        writer.write_all(&aad)?;
+        let mut buffer = vec![0u8; header.chunk_size];
+        for index in 0..header.chunk_count() {
+            let chunk = &mut buffer[..header.chunk_plaintext_len(index)];
+            reader.read_exact(chunk)?;
+            let ciphertext = cipher
+                .encrypt(Nonce::from_slice(&header.chunk_nonce(index)), Payload { msg: chunk, aad: &aad })
+                .map_err(|_| EncryptionError::new("Failed to encrypt chunk"))?;
+            writer.write_all(&ciphertext)?;
+        }
+        Ok(())
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let mut ciphertext = Vec::new();
+        self.encrypt_to(&mut &plaintext[..], plaintext.len() as u64, &mut ciphertext)?;
+        Ok(ciphertext)
+    }
+
+    /// Encrypts the file chunk-wise into a new file next to it and returns its path.
+    /// Callers remove the encrypted file once it is uploaded.
+    pub fn encrypt_file(&self, file_path: &str) -> Result<String, EncryptionError> {
+        let encrypted_file_path = format!("{}.{}.enc", file_path, Uuid::new_v4());
+        let file = File::open(file_path)?;
+        let plaintext_len = file.metadata()?.len();
+        let result = File::create(&encrypted_file_path)
+            .map_err(EncryptionError::from)
+            .and_then(|encrypted_file| {
+                let mut writer = BufWriter::new(encrypted_file);
+                self.encrypt_to(&mut BufReader::new(file), plaintext_len, &mut writer)?;
+                writer.flush()?;
+                Ok(())
+            });
+        if let Err(err) = result {
+            let _ = fs::remove_file(&encrypted_file_path);
+            return Err(err);
+        }
+        Ok(encrypted_file_path)
+    }
+
+    pub fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let header = EncryptedHeader::parse(bytes)?
+            .ok_or_else(|| EncryptionError::new("Object is not encrypted"))?;
+        self.decrypt_chunks(&header, &bytes[header.encoded_len()..], 0, header.chunk_count() - 1)
+    }
+
+    /// Decrypts the plaintext bytes `start..=end` from the bytes stored at `header.ciphertext_range(start, end)`.
+    pub fn decrypt_range(&self, header: &EncryptedHeader, ciphertext: &[u8], start: u64, end: u64) -> Result<Vec<u8>, EncryptionError> {
+        let (start, end) = header.plaintext_range(start, end)?;
+        let first_chunk = start / header.chunk_size as u64;
+        let plaintext = self.decrypt_chunks(header, ciphertext, first_chunk, end / header.chunk_size as u64)?;
+        let offset = (start - first_chunk * header.chunk_size as u64) as usize;
+        Ok(plaintext[offset..offset + (end - start + 1) as usize].to_vec())
+    }
+
+    /// Reads the plain bytes `start..=end` of a stored object, `read_stored_range` returning the stored bytes of an
+    /// inclusive range. Of encrypted objects only the header and the chunks covering the range are read, objects
+    /// stored before the encryption was enabled are read as they are.
+    pub async fn read_range<F, Fut>(&self, read_stored_range: F, start: u64, end: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+    where
+        F: Fn(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<u8>, Box<dyn std::error::Error>>>,
+    {
+        let header_bytes = read_stored_range(0, MAX_HEADER_LEN as u64 - 1).await?;
+        match EncryptedHeader::parse(&header_bytes)? {
+            Some(header) => {
+                let (ciphertext_start, ciphertext_end) = header.ciphertext_range(start, end)?;
+                let ciphertext = read_stored_range(ciphertext_start, ciphertext_end).await?;
+                Ok(self.decrypt_range(&header, &ciphertext, start, end)?)
+            }
+            None => read_stored_range(start, end).await,
+        }
+    }
+
+    fn decrypt_chunks(&self, header: &EncryptedHeader, ciphertext: &[u8], first_chunk: u64, last_chunk: u64) -> Result<Vec<u8>, EncryptionError> {
+        let cipher = self.data_cipher(header)?;
+        let aad = header.to_bytes();
+        let mut plaintext = Vec::new();
+        let mut position = 0;
+        for index in first_chunk..=last_chunk {
+            let chunk_len = header.chunk_plaintext_len(index) + TAG_LEN;
+            let chunk = ciphertext.get(position..position + chunk_len)
+                .ok_or_else(|| EncryptionError::new("Encrypted object is truncated"))?;
+            plaintext.extend(cipher
+                .decrypt(Nonce::from_slice(&header.chunk_nonce(index)), Payload { msg: chunk, aad: &aad })
+                .map_err(|_| EncryptionError::new(&format!("Failed to decrypt chunk {}, the object was modified", index)))?);
+            position += chunk_len;
+        }
+        if position != ciphertext.len() {
+            return Err(EncryptionError::new("Encrypted object has trailing bytes"));
+        }
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope_encryption(chunk_size: usize) -> EnvelopeEncryption {
+        EnvelopeEncryption::new(Arc::new(KeyfileKms::new(&[7u8; 32]).unwrap()), chunk_size)
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_envelope_encryption() {
+        let envelope_encryption = envelope_encryption(16);
+        let plaintext: Vec<u8> = (0..100u8).collect();
+
+        // round trips with a short last chunk, an exact multiple of the chunk size and no data at all
+        for len in [100, 32, 0] {
+            let ciphertext = envelope_encryption.encrypt(&plaintext[..len]).unwrap();
+            assert!(EnvelopeEncryption::is_encrypted(&ciphertext));
+            let header = EncryptedHeader::parse(&ciphertext).unwrap().unwrap();
+            assert_eq!(header.ciphertext_len(), ciphertext.len() as u64);
+            assert_eq!(envelope_encryption.decrypt(&ciphertext).unwrap(), plaintext[..len].to_vec());
+        }
+        assert!(!EnvelopeEncryption::is_encrypted(b"\0\0\0\x20ftypisom"));
+        assert!(EncryptedHeader::parse(b"\0\0\0\x20ftypisom").unwrap().is_none());
+
+        // ranges only need the chunks covering them
+        let ciphertext = envelope_encryption.encrypt(&plaintext).unwrap();
+        let header = EncryptedHeader::parse(&ciphertext[..MAX_HEADER_LEN.min(ciphertext.len())]).unwrap().unwrap();
+        for (start, end) in [(0, 0), (10, 40), (16, 31), (90, 1000)] {
+            let (ciphertext_start, ciphertext_end) = header.ciphertext_range(start, end).unwrap();
+            let range = &ciphertext[ciphertext_start as usize..=ciphertext_end as usize];
+            let end = end.min(99);
+            assert_eq!(envelope_encryption.decrypt_range(&header, range, start, end).unwrap(),
+                plaintext[start as usize..=end as usize].to_vec());
+        }
+        assert!(header.ciphertext_range(100, 120).is_err());
+
+        // modified, truncated and reordered objects are rejected
+        let mut modified = ciphertext.clone();
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        assert!(envelope_encryption.decrypt(&modified).is_err());
+        assert!(envelope_encryption.decrypt(&ciphertext[..ciphertext.len() - 16 - 4]).is_err());
+        let truncated_at_chunk = &ciphertext[..header.chunk_end(1) as usize];
+        assert!(envelope_encryption.decrypt_chunks(&header, &truncated_at_chunk[header.encoded_len()..], 0, 1).is_ok());
+        let mut truncated_header = header.clone();
+        truncated_header.plaintext_len = 32;
+        assert!(envelope_encryption.decrypt_chunks(&truncated_header, &truncated_at_chunk[header.encoded_len()..], 0, 1).is_err());
+
+        // data keys wrapped with another key-encryption key cannot be unwrapped
+        let other_envelope_encryption = EnvelopeEncryption::new(Arc::new(KeyfileKms::new(&[8u8; 32]).unwrap()), 16);
+        assert!(other_envelope_encryption.decrypt(&ciphertext).is_err());
+        assert!(KeyfileKms::new(&[7u8; 16]).is_err());
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[tokio::test]
+    async fn test_read_range() -> Result<(), Box<dyn std::error::Error>> {
+        let envelope_encryption = envelope_encryption(16);
+        let plaintext: Vec<u8> = (0..100u8).collect();
+        let ciphertext = envelope_encryption.encrypt(&plaintext)?;
+        let read_ranges = std::sync::Mutex::new(Vec::new());
+        // serves inclusive ranges like object stores do, clamped to the end of the object
+        let read_stored_range = |blob: &[u8], start: u64, end: u64| {
+            read_ranges.lock().unwrap().push((start, end));
+            let end = std::cmp::min(end as usize, blob.len() - 1);
+            blob[start as usize..=end].to_vec()
+        };
+
+        // a range in the middle of the file spanning a chunk boundary only reads the header and two chunks
+        let range = envelope_encryption
+            .read_range(|start, end| { let bytes = read_stored_range(&ciphertext, start, end); async move { Ok(bytes) } }, 40, 51)
+            .await?;
+        assert_eq!(range, plaintext[40..=51].to_vec());
+        let header = EncryptedHeader::parse(&ciphertext)?.unwrap();
+        assert_eq!(*read_ranges.lock().unwrap(), vec![(0, MAX_HEADER_LEN as u64 - 1), header.ciphertext_range(40, 51)?]);
+        assert_eq!(header.ciphertext_range(40, 51)?, (header.chunk_offset(2), header.chunk_end(3) - 1));
+
+        // plain objects stored before the encryption was enabled are read as they are
+        let range = envelope_encryption
+            .read_range(|start, end| { let bytes = read_stored_range(&plaintext, start, end); async move { Ok(bytes) } }, 40, 51)
+            .await?;
+        assert_eq!(range, plaintext[40..=51].to_vec());
+
+        let result = envelope_encryption
+            .read_range(|start, end| { let bytes = read_stored_range(&ciphertext, start, end); async move { Ok(bytes) } }, 100, 120)
+            .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_encrypt_file() {
+        let temp_dir = std::env::temp_dir().join(format!("envelope-encryption-{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let keyfile = temp_dir.join("kek.hex");
+        fs::write(&keyfile, format!("{}\n", hex::encode([9u8; 32]))).unwrap();
+        let file_path = temp_dir.join("sample.mp4");
+        let plaintext: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&file_path, &plaintext).unwrap();
+
+        let encryption_config = EncryptionConfig {
+            keyfile: String::from(keyfile.to_str().unwrap()),
+            chunk_size_bytes: 1024,
+        };
+        let envelope_encryption = EnvelopeEncryption::from_config(&encryption_config).unwrap().unwrap();
+        let encrypted_file_path = envelope_encryption.encrypt_file(file_path.to_str().unwrap()).unwrap();
+        let ciphertext = fs::read(&encrypted_file_path).unwrap();
+        assert_eq!(envelope_encryption.decrypt(&ciphertext).unwrap(), plaintext);
+
+        assert!(EnvelopeEncryption::from_config(&EncryptionConfig::default()).unwrap().is_none());
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
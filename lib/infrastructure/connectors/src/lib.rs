@@ -22,4 +22,6 @@
 
 
 pub mod aws_s3_bucket_connector;
-// pub mod azure_blob_storage_account_connector;
+pub mod azure_blob_storage_account_connector;
+pub mod envelope_encryption;
+mod operation_metrics;
@@ -0,0 +1,78 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::future::Future;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Runs a blob storage operation within a span and records its latency and failures, labeled by backend
+/// and operation. Without an installed metrics recorder, e.g. in tests, recording is a no-op.
+pub(crate) async fn observe<T, E>(
+    backend: &'static str,
+    operation: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let started = Instant::now();
+    let result = future
+        .instrument(tracing::info_span!("blob_operation", backend, operation))
+        .await;
+    metrics::histogram!("mms_blob_operation_duration_seconds", "backend" => backend, "operation" => operation)
+        .record(started.elapsed().as_secs_f64());
+    if result.is_err() {
+        metrics::counter!("mms_blob_operation_errors_total", "backend" => backend, "operation" => operation).increment(1);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_observe() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            let result = runtime.block_on(observe("aws_s3", "get_object", async { Ok::<u8, String>(1) }));
+            assert_eq!(result, Ok(1));
+            let result = runtime.block_on(observe("aws_s3", "get_object", async { Err::<u8, String>(String::from("denied")) }));
+            assert!(result.is_err());
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let durations = snapshot
+            .iter()
+            .find(|(key, _, _, _)| key.key().name() == "mms_blob_operation_duration_seconds")
+            .map(|(_, _, _, value)| value);
+        assert!(matches!(durations, Some(DebugValue::Histogram(values)) if values.len() == 2));
+        let errors = snapshot
+            .iter()
+            .find(|(key, _, _, _)| key.key().name() == "mms_blob_operation_errors_total")
+            .map(|(_, _, _, value)| value);
+        assert_eq!(errors, Some(&DebugValue::Counter(1)));
+    }
+}
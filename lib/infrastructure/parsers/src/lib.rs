@@ -21,4 +21,13 @@
 // SOFTWARE.
 
 
-pub mod mp4_parser;
\ No newline at end of file
+pub mod mp4_chapters;
+pub mod mp4_clipper;
+pub mod mp4_demuxer;
+pub mod mp4_faststart;
+pub mod mp4_metadata;
+pub mod mp4_parser;
+pub mod mp4_validator;
+
+#[cfg(test)]
+pub(crate) mod test_boxes;
\ No newline at end of file
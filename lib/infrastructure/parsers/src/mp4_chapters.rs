@@ -0,0 +1,360 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+//! Reads chapter markers embedded in MP4 files, from a QuickTime chapter text track or a Nero `chpl` box.
+//!
+//! Matroska chapters are not read. Uploads are validated as MP4 and no Matroska demuxer is available,
+//! so chapters of Matroska sources have to be added through the chapters API.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+
+use crate::mp4_validator::{read_at, read_boxes, BoxHeader};
+
+// Nero chapter start times are given in units of 100 nanoseconds
+const NERO_TIME_UNITS_PER_MS: u64 = 10_000;
+// upper bounds keeping hostile files from allocating or seeking endlessly
+const MAX_CHAPTER_COUNT: usize = 1024;
+const MAX_CHAPTER_TITLE_SIZE: u64 = 1024;
+const MAX_SAMPLE_TABLE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Chapter marker embedded in the file, the chapter ends where the next one starts or the movie ends.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbeddedChapter {
+    pub title: String,
+    pub start_time_in_ms: i64,
+    pub end_time_in_ms: i64,
+}
+
+#[tracing::instrument]
+pub fn read_chapters_from_file(filename: &str) -> Result<Vec<EmbeddedChapter>, Box<dyn std::error::Error>> {
+    let f = File::open(filename)?;
+    let size = f.metadata()?.len();
+    read_chapters(&mut BufReader::new(f), size)
+}
+
+/// Reads the QuickTime chapter text track referenced by a `tref/chap` box and falls back to the Nero `chpl` box
+/// in `moov/udta`, files carrying both list the same chapters.
+pub fn read_chapters<R: Read + Seek>(reader: &mut R, size: u64) -> Result<Vec<EmbeddedChapter>, Box<dyn std::error::Error>> {
+    let boxes = read_boxes(reader, 0, size)?;
+    let moov = match find_box(&boxes, b"moov") {
+        Some(moov) => moov,
+        None => return Ok(Vec::new()),
+    };
+    let moov_boxes = read_boxes(reader, moov.content_offset(), moov.end())?;
+    let movie_duration_in_ms = match find_box(&moov_boxes, b"mvhd") {
+        Some(mvhd) => read_duration_in_ms(reader, mvhd, 12)?,
+        None => 0,
+    };
+
+    let traks: Vec<&BoxHeader> = moov_boxes.iter().filter(|b| &b.box_type == b"trak").collect();
+    let mut chapter_track_ids = Vec::new();
+    for trak in &traks {
+        let trak_boxes = read_boxes(reader, trak.content_offset(), trak.end())?;
+        if let Some(tref) = find_box(&trak_boxes, b"tref") {
+            for reference in read_boxes(reader, tref.content_offset(), tref.end())?.iter().filter(|b| &b.box_type == b"chap") {
+                let track_ids = read_payload(reader, reference, MAX_SAMPLE_TABLE_SIZE)?;
+                chapter_track_ids.extend(track_ids.chunks_exact(4).map(|id| u32::from_be_bytes(id.try_into().unwrap())));
+            }
+        }
+    }
+    for trak in &traks {
+        let trak_boxes = read_boxes(reader, trak.content_offset(), trak.end())?;
+        let track_id = match find_box(&trak_boxes, b"tkhd") {
+            Some(tkhd) => read_tkhd_track_id(reader, tkhd)?,
+            None => continue,
+        };
+        if chapter_track_ids.contains(&track_id) {
+            let chapters = read_text_track_chapters(reader, &trak_boxes, movie_duration_in_ms)?;
+            if !chapters.is_empty() {
+                return Ok(chapters);
+            }
+        }
+    }
+
+    if let Some(udta) = find_box(&moov_boxes, b"udta") {
+        // QuickTime terminates the udta box with four zero bytes, which are no box
+        let udta_boxes = read_boxes(reader, udta.content_offset(), udta.end())
+            .or_else(|_| read_boxes(reader, udta.content_offset(), udta.end().saturating_sub(4).max(udta.content_offset())))?;
+        if let Some(chpl) = find_box(&udta_boxes, b"chpl") {
+            return read_nero_chapters(&read_payload(reader, chpl, MAX_SAMPLE_TABLE_SIZE)?, movie_duration_in_ms);
+        }
+    }
+    Ok(Vec::new())
+}
+
+fn find_box<'a>(boxes: &'a [BoxHeader], box_type: &[u8; 4]) -> Option<&'a BoxHeader> {
+    boxes.iter().find(|b| &b.box_type == box_type)
+}
+
+fn read_payload<R: Read + Seek>(reader: &mut R, header: &BoxHeader, max_size: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let len = header.end() - header.content_offset();
+    if len > max_size {
+        return Err(format!("The {} box at offset {} exceeds {} bytes", header.name(), header.offset, max_size).into());
+    }
+    Ok(read_at(reader, header.content_offset(), len)?)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Box<dyn std::error::Error>> {
+    bytes.get(offset..offset + 4)
+        .map(|value| u32::from_be_bytes(value.try_into().unwrap()))
+        .ok_or_else(|| "Truncated box".into())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, Box<dyn std::error::Error>> {
+    bytes.get(offset..offset + 8)
+        .map(|value| u64::from_be_bytes(value.try_into().unwrap()))
+        .ok_or_else(|| "Truncated box".into())
+}
+
+// mvhd and mdhd share their layout up to the duration, `timescale_offset` is the offset of the timescale in version 0
+fn read_timescale_and_duration(payload: &[u8], timescale_offset: usize) -> Result<(u32, u64), Box<dyn std::error::Error>> {
+    match payload.first() {
+        Some(1) => Ok((read_u32(payload, timescale_offset + 8)?, read_u64(payload, timescale_offset + 12)?)),
+        _ => Ok((read_u32(payload, timescale_offset)?, read_u32(payload, timescale_offset + 4)? as u64)),
+    }
+}
+
+fn read_duration_in_ms<R: Read + Seek>(reader: &mut R, header: &BoxHeader, timescale_offset: usize) -> Result<i64, Box<dyn std::error::Error>> {
+    let payload = read_payload(reader, header, 1024)?;
+    let (timescale, duration) = read_timescale_and_duration(&payload, timescale_offset)?;
+    Ok(to_ms(duration, timescale))
+}
+
+fn to_ms(time: u64, timescale: u32) -> i64 {
+    match timescale {
+        0 => 0,
+        _ => (time as u128 * 1000 / timescale as u128).min(i64::MAX as u128) as i64,
+    }
+}
+
+fn read_tkhd_track_id<R: Read + Seek>(reader: &mut R, tkhd: &BoxHeader) -> Result<u32, Box<dyn std::error::Error>> {
+    let payload = read_payload(reader, tkhd, 1024)?;
+    match payload.first() {
+        Some(1) => read_u32(&payload, 20),
+        _ => read_u32(&payload, 12),
+    }
+}
+
+fn read_nero_chapters(payload: &[u8], movie_duration_in_ms: i64) -> Result<Vec<EmbeddedChapter>, Box<dyn std::error::Error>> {
+    // version 1 carries four reserved bytes ahead of the chapter count
+    let mut offset = match payload.first() {
+        Some(0) => 4,
+        _ => 8,
+    };
+    let chapter_count = *payload.get(offset).ok_or("Truncated chpl box")? as usize;
+    offset += 1;
+    let mut starts = Vec::with_capacity(chapter_count);
+    for _ in 0..chapter_count {
+        let start = read_u64(payload, offset)?;
+        let title_len = *payload.get(offset + 8).ok_or("Truncated chpl box")? as usize;
+        let title = payload.get(offset + 9..offset + 9 + title_len).ok_or("Truncated chpl box")?;
+        starts.push(((start / NERO_TIME_UNITS_PER_MS).min(i64::MAX as u64) as i64, String::from_utf8_lossy(title).to_string()));
+        offset += 9 + title_len;
+    }
+    Ok(to_chapters(starts, movie_duration_in_ms))
+}
+
+// the samples of a chapter text track are a 16 bit length followed by the title
+fn read_text_track_chapters<R: Read + Seek>(reader: &mut R, trak_boxes: &[BoxHeader], movie_duration_in_ms: i64)
+    -> Result<Vec<EmbeddedChapter>, Box<dyn std::error::Error>> {
+    let mdia = find_box(trak_boxes, b"mdia").ok_or("The chapter track has no mdia box")?;
+    let mdia_boxes = read_boxes(reader, mdia.content_offset(), mdia.end())?;
+    let mdhd = find_box(&mdia_boxes, b"mdhd").ok_or("The chapter track has no mdhd box")?;
+    let (timescale, _) = read_timescale_and_duration(&read_payload(reader, mdhd, 1024)?, 12)?;
+    let minf = find_box(&mdia_boxes, b"minf").ok_or("The chapter track has no minf box")?;
+    let minf_boxes = read_boxes(reader, minf.content_offset(), minf.end())?;
+    let stbl = find_box(&minf_boxes, b"stbl").ok_or("The chapter track has no stbl box")?;
+    let stbl_boxes = read_boxes(reader, stbl.content_offset(), stbl.end())?;
+    let table = |reader: &mut R, box_type: &[u8; 4]| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match find_box(&stbl_boxes, box_type) {
+            Some(header) => read_payload(reader, header, MAX_SAMPLE_TABLE_SIZE),
+            None => Err(format!("The chapter track has no {} box", String::from_utf8_lossy(box_type)).into()),
+        }
+    };
+
+    // sample start times
+    let stts = table(reader, b"stts")?;
+    let mut sample_times = Vec::new();
+    let mut time = 0u64;
+    for entry in 0..read_u32(&stts, 4)? as usize {
+        let sample_count = read_u32(&stts, 8 + entry * 8)?;
+        let sample_delta = read_u32(&stts, 12 + entry * 8)? as u64;
+        for _ in 0..sample_count {
+            if sample_times.len() == MAX_CHAPTER_COUNT {
+                break;
+            }
+            sample_times.push(time);
+            time += sample_delta;
+        }
+    }
+
+    // sample sizes
+    let stsz = table(reader, b"stsz")?;
+    let uniform_sample_size = read_u32(&stsz, 4)?;
+    let sample_size = |sample: usize| -> Result<u32, Box<dyn std::error::Error>> {
+        match uniform_sample_size {
+            0 => read_u32(&stsz, 12 + sample * 4),
+            size => Ok(size),
+        }
+    };
+
+    // sample offsets, the samples of a chunk are stored back to back
+    let stsc = table(reader, b"stsc")?;
+    let (chunk_offsets, entry_size) = match find_box(&stbl_boxes, b"co64") {
+        Some(_) => (table(reader, b"co64")?, 8),
+        None => (table(reader, b"stco")?, 4),
+    };
+    let stsc_entry_count = read_u32(&stsc, 4)? as usize;
+    let mut sample_offsets = Vec::with_capacity(sample_times.len());
+    'chunks: for chunk in 0..read_u32(&chunk_offsets, 4)? as usize {
+        let mut offset = match entry_size {
+            4 => read_u32(&chunk_offsets, 8 + chunk * 4)? as u64,
+            _ => read_u64(&chunk_offsets, 8 + chunk * 8)?,
+        };
+        // the last entry whose first chunk (one based) is not behind this chunk applies
+        let mut samples_per_chunk = 0;
+        for entry in 0..stsc_entry_count {
+            if read_u32(&stsc, 8 + entry * 12)? as usize > chunk + 1 {
+                break;
+            }
+            samples_per_chunk = read_u32(&stsc, 12 + entry * 12)?;
+        }
+        for _ in 0..samples_per_chunk {
+            if sample_offsets.len() == sample_times.len() {
+                break 'chunks;
+            }
+            let size = sample_size(sample_offsets.len())?;
+            sample_offsets.push((offset, size));
+            offset += size as u64;
+        }
+    }
+
+    let mut starts = Vec::with_capacity(sample_offsets.len());
+    for ((offset, size), time) in sample_offsets.into_iter().zip(sample_times) {
+        if size < 2 {
+            continue;
+        }
+        let sample = read_at(reader, offset, (size as u64).min(2 + MAX_CHAPTER_TITLE_SIZE))?;
+        let title_len = (u16::from_be_bytes([sample[0], sample[1]]) as usize).min(sample.len() - 2);
+        starts.push((to_ms(time, timescale), decode_text(&sample[2..2 + title_len])));
+    }
+    Ok(to_chapters(starts, movie_duration_in_ms))
+}
+
+// text samples are UTF-8 unless they start with a UTF-16 byte order mark
+fn decode_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xfe, 0xff, rest @ ..] => String::from_utf16_lossy(
+            &rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect::<Vec<u16>>()),
+        [0xff, 0xfe, rest @ ..] => String::from_utf16_lossy(
+            &rest.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect::<Vec<u16>>()),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+fn to_chapters(mut starts: Vec<(i64, String)>, movie_duration_in_ms: i64) -> Vec<EmbeddedChapter> {
+    starts.truncate(MAX_CHAPTER_COUNT);
+    starts.sort_by_key(|(start_time_in_ms, _)| *start_time_in_ms);
+    let ends: Vec<i64> = starts.iter().skip(1).map(|(start_time_in_ms, _)| *start_time_in_ms)
+        .chain(std::iter::once(movie_duration_in_ms))
+        .collect();
+    starts.into_iter()
+        .zip(ends)
+        .map(|((start_time_in_ms, title), end_time_in_ms)| EmbeddedChapter {
+            title: title.trim().to_string(),
+            start_time_in_ms,
+            end_time_in_ms: end_time_in_ms.max(start_time_in_ms),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_boxes::{full_box, mp4_box};
+    use std::io::Cursor;
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_be_bytes()).collect()
+    }
+
+    // 60 seconds movie at a timescale of 1000
+    fn mvhd() -> Vec<u8> {
+        full_box(b"mvhd", &[u32s(&[0, 0, 1000, 60_000]), vec![0; 80]].concat())
+    }
+
+    fn tkhd(track_id: u32) -> Vec<u8> {
+        full_box(b"tkhd", &[u32s(&[0, 0, track_id]), vec![0; 68]].concat())
+    }
+
+    fn read(bytes: &[u8]) -> Vec<EmbeddedChapter> {
+        read_chapters(&mut Cursor::new(bytes), bytes.len() as u64).unwrap()
+    }
+
+    fn chapter(title: &str, start_time_in_ms: i64, end_time_in_ms: i64) -> EmbeddedChapter {
+        EmbeddedChapter { title: String::from(title), start_time_in_ms, end_time_in_ms }
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_chapters() {
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\x02\0isomiso2avc1mp41");
+
+        // Nero chapters
+        let chpl = mp4_box(b"chpl", &[
+            &[1, 0, 0, 0, 0, 0, 0, 0, 2][..],
+            &0u64.to_be_bytes(), &[5], b"Intro",
+            &(250_000_000u64).to_be_bytes(), &[4], b"Main",
+        ].concat());
+        let moov = mp4_box(b"moov", &[mvhd(), mp4_box(b"udta", &chpl)].concat());
+        assert_eq!(read(&[ftyp.clone(), moov].concat()), vec![chapter("Intro", 0, 25_000), chapter("Main", 25_000, 60_000)]);
+
+        // QuickTime chapter text track with both samples in a single chunk behind the moov box
+        // the second title is UTF-16 with a byte order mark
+        let samples = [&5u16.to_be_bytes()[..], b"Intro", &10u16.to_be_bytes(), &[0xfe, 0xff, 0, b'M', 0, b'a', 0, b'i', 0, b'n']].concat();
+        let stbl = |chunk_offset: u32| mp4_box(b"stbl", &[
+            full_box(b"stts", &u32s(&[2, 1, 30_000, 1, 30_000])),
+            full_box(b"stsc", &u32s(&[1, 1, 2, 1])),
+            full_box(b"stsz", &u32s(&[0, 2, 7, 12])),
+            full_box(b"stco", &u32s(&[1, chunk_offset])),
+        ].concat());
+        let chapter_trak = |chunk_offset: u32| mp4_box(b"trak", &[
+            tkhd(2),
+            mp4_box(b"mdia", &[
+                full_box(b"mdhd", &u32s(&[0, 0, 600, 36_000, 0])),
+                mp4_box(b"minf", &stbl(chunk_offset)),
+            ].concat()),
+        ].concat());
+        let video_trak = mp4_box(b"trak", &[tkhd(1), mp4_box(b"tref", &mp4_box(b"chap", &u32s(&[2])))].concat());
+        let moov_len = mp4_box(b"moov", &[mvhd(), video_trak.clone(), chapter_trak(0)].concat()).len();
+        let chunk_offset = (ftyp.len() + moov_len + 8) as u32;
+        let moov = mp4_box(b"moov", &[mvhd(), video_trak, chapter_trak(chunk_offset)].concat());
+        let bytes = [ftyp.clone(), moov, mp4_box(b"mdat", &samples)].concat();
+        assert_eq!(read(&bytes), vec![chapter("Intro", 0, 50_000), chapter("Main", 50_000, 60_000)]);
+
+        // Files without chapters
+        assert!(read(&[ftyp, mp4_box(b"moov", &mvhd())].concat()).is_empty());
+    }
+}
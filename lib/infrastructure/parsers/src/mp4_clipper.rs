@@ -0,0 +1,321 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use mp4::{AacConfig, AvcConfig, MediaConfig, MediaType, Mp4Config, Mp4Track, Mp4Writer, TrackConfig, TtxtConfig};
+use tracing::info;
+
+/// Returned when a clip cannot be cut from the file without re-encoding it.
+#[derive(Debug)]
+pub struct Mp4ClipError {
+    pub code: &'static str,
+    pub reason: String,
+}
+
+impl Mp4ClipError {
+    pub const INVALID_RANGE: &'static str = "invalid_range";
+    pub const UNSUPPORTED_CODEC: &'static str = "unsupported_codec";
+    pub const FRAGMENTED: &'static str = "fragmented";
+
+    fn new(code: &'static str, reason: String) -> Self {
+        Mp4ClipError { code, reason }
+    }
+}
+
+impl fmt::Display for Mp4ClipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for Mp4ClipError {}
+
+/// Time range actually covered by a clip, the requested range widened to the surrounding keyframes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipRange {
+    pub start_time_in_ms: u64,
+    pub end_time_in_ms: u64,
+}
+
+// a track copied into the clip: its sample ids within the range and their start times in the track's timescale
+struct ClipTrack {
+    track_id: u32,
+    timescale: u64,
+    samples: Vec<(u32, u64)>,
+}
+
+/// Copies the samples of `source` between `start_time_in_ms` and `end_time_in_ms` into a new MP4 at `target`
+/// without re-encoding. The clip starts at the video keyframe at or before the requested start and ends in front of
+/// the first keyframe at or behind the requested end, so every frame can be decoded. H.264, AAC and tx3g tracks
+/// are supported, chapter tracks are dropped. The `moov` box of the clip is written behind the media data.
+#[tracing::instrument]
+pub fn clip_file(source: &str, target: &str, start_time_in_ms: u64, end_time_in_ms: u64) -> Result<ClipRange, Box<dyn std::error::Error>> {
+    if start_time_in_ms >= end_time_in_ms {
+        return Err(Box::new(Mp4ClipError::new(
+            Mp4ClipError::INVALID_RANGE,
+            String::from("The start of the clip must precede its end"),
+        )));
+    }
+    let f = File::open(source)?;
+    let size = f.metadata()?.len();
+    let mut mp4 = mp4::Mp4Reader::read_header(BufReader::new(f), size)?;
+    if mp4.is_fragmented() {
+        return Err(Box::new(Mp4ClipError::new(
+            Mp4ClipError::FRAGMENTED,
+            String::from("Clipping fragmented MP4 files is not supported"),
+        )));
+    }
+    let duration_in_ms = mp4.duration().as_millis() as u64;
+    if end_time_in_ms > duration_in_ms {
+        return Err(Box::new(Mp4ClipError::new(
+            Mp4ClipError::INVALID_RANGE,
+            format!("The end of the clip exceeds the duration of {} ms", duration_in_ms),
+        )));
+    }
+
+    let mut tracks: Vec<&Mp4Track> = mp4.tracks().values()
+        // QuickTime chapter tracks carry no media
+        .filter(|track| &track.trak.mdia.hdlr.handler_type.value != b"text")
+        .collect();
+    tracks.sort_by_key(|track| track.track_id());
+    let mut track_configs = Vec::with_capacity(tracks.len());
+    for track in tracks.iter() {
+        track_configs.push(track_config(track)?);
+    }
+
+    // the video track, or any other track if there is none, determines the keyframe boundaries
+    let reference_track = tracks.iter()
+        .find(|track| matches!(track.media_type(), Ok(MediaType::H264)))
+        .or(tracks.first())
+        .ok_or("The file contains no tracks")?;
+    let reference_timescale = reference_track.timescale() as u64;
+    if reference_timescale == 0 {
+        return Err("Invalid timescale of 0".into());
+    }
+    let (clip_start, clip_end) = keyframe_range(
+        &sample_start_times(reference_track),
+        track_end_time(reference_track),
+        reference_track.trak.mdia.minf.stbl.stss.as_ref().map(|stss| stss.entries.as_slice()),
+        start_time_in_ms * reference_timescale / 1000,
+        end_time_in_ms * reference_timescale / 1000,
+    ).ok_or_else(|| Mp4ClipError::new(Mp4ClipError::INVALID_RANGE, String::from("The clip contains no keyframe")))?;
+
+    let clip_tracks: Vec<ClipTrack> = tracks.iter()
+        .map(|track| {
+            let timescale = (track.timescale() as u64).max(1);
+            // compares start times across timescales without rounding
+            let within = |start_time: u64| {
+                start_time as u128 * reference_timescale as u128 >= clip_start as u128 * timescale as u128
+                    && (start_time as u128 * reference_timescale as u128) < clip_end as u128 * timescale as u128
+            };
+            ClipTrack {
+                track_id: track.track_id(),
+                timescale,
+                samples: sample_start_times(track)
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, start_time)| within(*start_time))
+                    .map(|(index, start_time)| (index as u32 + 1, start_time))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    // interleaves the tracks by time, so players read the media data front to back
+    let mut order: Vec<(u128, usize, u32)> = clip_tracks.iter()
+        .enumerate()
+        .flat_map(|(index, clip_track)| clip_track.samples.iter().map(move |(sample_id, start_time)| {
+            (*start_time as u128 * 1_000_000 / clip_track.timescale as u128, index, *sample_id)
+        }))
+        .collect();
+    order.sort();
+
+    let config = Mp4Config {
+        major_brand: *mp4.major_brand(),
+        minor_version: mp4.minor_version(),
+        compatible_brands: mp4.compatible_brands().to_vec(),
+        timescale: 1000,
+    };
+    let mut writer = Mp4Writer::write_start(BufWriter::new(File::create(target)?), &config)?;
+    for track_config in track_configs.iter() {
+        writer.add_track(track_config)?;
+    }
+    for (_, index, sample_id) in order {
+        let clip_track = &clip_tracks[index];
+        let mut sample = mp4.read_sample(clip_track.track_id, sample_id)?
+            .ok_or_else(|| format!("Sample {} of track {} could not be read", sample_id, clip_track.track_id))?;
+        let first_start_time = clip_track.samples[0].1;
+        sample.start_time -= first_start_time;
+        writer.write_sample(index as u32 + 1, &sample)?;
+    }
+    writer.write_end()?;
+    writer.into_writer().flush()?;
+
+    let clip_range = ClipRange {
+        start_time_in_ms: clip_start * 1000 / reference_timescale,
+        end_time_in_ms: clip_end * 1000 / reference_timescale,
+    };
+    info!(start_time_in_ms = clip_range.start_time_in_ms, end_time_in_ms = clip_range.end_time_in_ms, "Clipped MP4");
+    Ok(clip_range)
+}
+
+pub(crate) fn track_config(track: &Mp4Track) -> Result<TrackConfig, Box<dyn std::error::Error>> {
+    let media_conf = match track.media_type() {
+        Ok(MediaType::H264) => MediaConfig::AvcConfig(AvcConfig {
+            width: track.width(),
+            height: track.height(),
+            seq_param_set: track.sequence_parameter_set()?.to_vec(),
+            pic_param_set: track.picture_parameter_set()?.to_vec(),
+        }),
+        Ok(MediaType::AAC) => MediaConfig::AacConfig(AacConfig {
+            bitrate: track.bitrate(),
+            profile: track.audio_profile()?,
+            freq_index: track.sample_freq_index()?,
+            chan_conf: track.channel_config()?,
+        }),
+        Ok(MediaType::TTXT) => MediaConfig::TtxtConfig(TtxtConfig {}),
+        _ => {
+            let codec = track.box_type().map(|box_type| box_type.to_string()).unwrap_or(String::from("unknown"));
+            return Err(Box::new(Mp4ClipError::new(
+                Mp4ClipError::UNSUPPORTED_CODEC,
+                format!("Track {} uses the {} codec, clips support H.264, AAC and tx3g", track.track_id(), codec),
+            )));
+        }
+    };
+    Ok(TrackConfig {
+        track_type: track.track_type()?,
+        timescale: track.timescale(),
+        language: track.language().to_string(),
+        media_conf,
+    })
+}
+
+fn track_end_time(track: &Mp4Track) -> u64 {
+    track.trak.mdia.minf.stbl.stts.entries.iter()
+        .map(|entry| entry.sample_count as u64 * entry.sample_delta as u64)
+        .sum()
+}
+
+// the decoding start times of all samples of the track, in its timescale
+fn sample_start_times(track: &Mp4Track) -> Vec<u64> {
+    let mut start_times = Vec::with_capacity(track.sample_count() as usize);
+    let mut start_time = 0;
+    for entry in track.trak.mdia.minf.stbl.stts.entries.iter() {
+        for _ in 0..entry.sample_count {
+            start_times.push(start_time);
+            start_time += entry.sample_delta as u64;
+        }
+    }
+    start_times
+}
+
+/// Widens `start_time`..`end_time` to keyframe boundaries, returning the start time of the last sync sample at or
+/// before `start_time` and the start time of the first sync sample at or behind `end_time`, or `track_end_time`.
+/// `sync_samples` holds the ascending 1-based sample ids of the stss box, `None` meaning every sample is a sync sample.
+fn keyframe_range(sample_start_times: &[u64], track_end_time: u64, sync_samples: Option<&[u32]>, start_time: u64, end_time: u64)
+    -> Option<(u64, u64)> {
+    let is_sync = |index: usize| sync_samples.is_none_or(|sync_samples| sync_samples.binary_search(&(index as u32 + 1)).is_ok());
+    let start_index = (0..sample_start_times.len())
+        .filter(|index| is_sync(*index))
+        .take_while(|index| sample_start_times[*index] <= start_time)
+        .last()
+        .or_else(|| (0..sample_start_times.len()).find(|index| is_sync(*index)))?;
+    let clip_start = sample_start_times[start_index];
+    let clip_end = (start_index + 1..sample_start_times.len())
+        .find(|index| is_sync(*index) && sample_start_times[*index] >= end_time.max(clip_start + 1))
+        .map(|index| sample_start_times[index])
+        .unwrap_or(track_end_time);
+    Some((clip_start, clip_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_clipper() {
+        // ten samples of 100 ticks, keyframes at samples 1, 4 and 8
+        let start_times: Vec<u64> = (0..10).map(|index| index * 100).collect();
+        let sync_samples = [1, 4, 8];
+
+        assert_eq!(keyframe_range(&start_times, 1000, Some(&sync_samples), 350, 500), Some((300, 700)));
+        assert_eq!(keyframe_range(&start_times, 1000, Some(&sync_samples), 300, 700), Some((300, 700)));
+        // the last keyframe runs to the end of the track
+        assert_eq!(keyframe_range(&start_times, 1000, Some(&sync_samples), 750, 900), Some((700, 1000)));
+        assert_eq!(keyframe_range(&start_times, 1000, Some(&sync_samples), 0, 50), Some((0, 300)));
+        // every sample is a keyframe without stss
+        assert_eq!(keyframe_range(&start_times, 1000, None, 350, 500), Some((300, 500)));
+        assert_eq!(keyframe_range(&[], 0, None, 0, 500), None);
+
+        let err = clip_file("missing.mp4", "clip.mp4", 500, 500).unwrap_err();
+        assert_eq!(err.downcast_ref::<Mp4ClipError>().unwrap().code, Mp4ClipError::INVALID_RANGE);
+
+        // 4 seconds of H.264 at 10 fps with a keyframe every second, clipped from 1.5 to 2.5 seconds
+        let source = std::env::temp_dir().join(format!("mp4-clipper-{}.mp4", std::process::id()));
+        let target = source.with_extension("clip.mp4");
+        let config = Mp4Config {
+            major_brand: str::parse("isom").unwrap(),
+            minor_version: 512,
+            compatible_brands: vec![str::parse("isom").unwrap(), str::parse("avc1").unwrap()],
+            timescale: 1000,
+        };
+        let mut writer = Mp4Writer::write_start(BufWriter::new(File::create(&source).unwrap()), &config).unwrap();
+        writer.add_track(&TrackConfig::from(MediaConfig::AvcConfig(AvcConfig {
+            width: 320,
+            height: 240,
+            seq_param_set: vec![0x67, 0x42, 0xc0, 0x1e],
+            pic_param_set: vec![0x68, 0xce, 0x3c, 0x80],
+        }))).unwrap();
+        for index in 0..40u64 {
+            writer.write_sample(1, &mp4::Mp4Sample {
+                start_time: index * 100,
+                duration: 100,
+                rendering_offset: 0,
+                is_sync: index % 10 == 0,
+                bytes: mp4::Bytes::from(vec![index as u8; 16]),
+            }).unwrap();
+        }
+        writer.write_end().unwrap();
+        writer.into_writer().flush().unwrap();
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+
+        let clip_range = clip_file(source, target, 1500, 2500).unwrap();
+        assert_eq!(clip_range, ClipRange { start_time_in_ms: 1000, end_time_in_ms: 3000 });
+        let f = File::open(target).unwrap();
+        let size = f.metadata().unwrap().len();
+        let mut clip = mp4::Mp4Reader::read_header(BufReader::new(f), size).unwrap();
+        assert_eq!(clip.sample_count(1).unwrap(), 20);
+        assert_eq!(clip.duration().as_millis(), 2000);
+        let first_sample = clip.read_sample(1, 1).unwrap().unwrap();
+        assert!(first_sample.is_sync);
+        assert_eq!(first_sample.bytes.as_ref(), &[10u8; 16]);
+        assert!(clip.read_sample(1, 11).unwrap().unwrap().is_sync);
+        std::fs::remove_file(source).unwrap();
+        std::fs::remove_file(target).unwrap();
+    }
+}
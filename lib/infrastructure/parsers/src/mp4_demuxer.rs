@@ -0,0 +1,308 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use mp4::{MediaType, Mp4Config, Mp4Track, Mp4Writer, TrackConfig};
+pub use mp4::TrackType;
+use tracing::info;
+
+use crate::mp4_clipper;
+
+/// Returned when a track cannot be exported from the file without re-encoding it.
+#[derive(Debug)]
+pub struct Mp4DemuxError {
+    pub code: &'static str,
+    pub reason: String,
+}
+
+impl Mp4DemuxError {
+    pub const TRACK_NOT_FOUND: &'static str = "track_not_found";
+    pub const UNSUPPORTED_CODEC: &'static str = "unsupported_codec";
+    pub const FRAGMENTED: &'static str = "fragmented";
+
+    fn new(code: &'static str, reason: String) -> Self {
+        Mp4DemuxError { code, reason }
+    }
+}
+
+impl fmt::Display for Mp4DemuxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for Mp4DemuxError {}
+
+/// Format of an exported track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportedTrack {
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+}
+
+/// Writes the track `track_id` of `source` as a standalone file to `target` without re-encoding: H.264 video as MP4,
+/// AAC audio as M4A and tx3g subtitles as WebVTT. Without a `track_id` the first track of `track_type` is exported.
+#[tracing::instrument]
+pub fn export_track_to_file(source: &str, target: &str, track_type: TrackType, track_id: Option<u32>)
+    -> Result<ExportedTrack, Box<dyn std::error::Error>> {
+    let f = File::open(source)?;
+    let size = f.metadata()?.len();
+    let mut mp4 = mp4::Mp4Reader::read_header(BufReader::new(f), size)?;
+    if mp4.is_fragmented() {
+        return Err(Box::new(Mp4DemuxError::new(
+            Mp4DemuxError::FRAGMENTED,
+            String::from("Exporting tracks of fragmented MP4 files is not supported"),
+        )));
+    }
+
+    let is_of_type = |track: &&Mp4Track| matches!(track.track_type(), Ok(found) if found == track_type);
+    let track = match track_id {
+        Some(track_id) => mp4.tracks().get(&track_id).filter(is_of_type).ok_or_else(|| Mp4DemuxError::new(
+            Mp4DemuxError::TRACK_NOT_FOUND,
+            format!("The file contains no {} track with id {}", track_type, track_id),
+        ))?,
+        None => mp4.tracks().values().filter(is_of_type).min_by_key(|track| track.track_id()).ok_or_else(|| Mp4DemuxError::new(
+            Mp4DemuxError::TRACK_NOT_FOUND,
+            format!("The file contains no {} track", track_type),
+        ))?,
+    };
+    let track_id = track.track_id();
+    let timescale = (track.timescale() as u64).max(1);
+    let sample_count = track.sample_count();
+    let exported_track = match (track_type, track.media_type()) {
+        (TrackType::Video, Ok(MediaType::H264)) => ExportedTrack { mime_type: "video/mp4", extension: "mp4" },
+        (TrackType::Audio, Ok(MediaType::AAC)) => ExportedTrack { mime_type: "audio/mp4", extension: "m4a" },
+        (TrackType::Subtitle, Ok(MediaType::TTXT)) => ExportedTrack { mime_type: "text/vtt", extension: "vtt" },
+        _ => {
+            let codec = track.box_type().map(|box_type| box_type.to_string()).unwrap_or(String::from("unknown"));
+            return Err(Box::new(Mp4DemuxError::new(
+                Mp4DemuxError::UNSUPPORTED_CODEC,
+                format!("The {} track uses the {} codec, exports support H.264, AAC and tx3g", track_type, codec),
+            )));
+        }
+    };
+
+    if track_type == TrackType::Subtitle {
+        let mut writer = BufWriter::new(File::create(target)?);
+        writer.write_all(b"WEBVTT\n")?;
+        for sample_id in 1..=sample_count {
+            let sample = mp4.read_sample(track_id, sample_id)?
+                .ok_or_else(|| format!("Sample {} of track {} could not be read", sample_id, track_id))?;
+            let text = tx3g_text(&sample.bytes);
+            // empty samples fill the gaps between cues
+            if text.trim().is_empty() {
+                continue;
+            }
+            write!(
+                writer,
+                "\n{} --> {}\n{}\n",
+                webvtt_timestamp(sample.start_time * 1000 / timescale),
+                webvtt_timestamp((sample.start_time + sample.duration as u64) * 1000 / timescale),
+                text.trim_end(),
+            )?;
+        }
+        writer.flush()?;
+    } else {
+        let track_config: TrackConfig = mp4_clipper::track_config(track)?;
+        let brand = if track_type == TrackType::Audio { "M4A " } else { "isom" };
+        let config = Mp4Config {
+            major_brand: str::parse(brand)?,
+            minor_version: 512,
+            compatible_brands: vec![str::parse(brand)?, str::parse("mp42")?, str::parse("isom")?],
+            timescale: 1000,
+        };
+        let mut writer = Mp4Writer::write_start(BufWriter::new(File::create(target)?), &config)?;
+        writer.add_track(&track_config)?;
+        for sample_id in 1..=sample_count {
+            let sample = mp4.read_sample(track_id, sample_id)?
+                .ok_or_else(|| format!("Sample {} of track {} could not be read", sample_id, track_id))?;
+            writer.write_sample(1, &sample)?;
+        }
+        writer.write_end()?;
+        writer.into_writer().flush()?;
+    }
+
+    info!(track_id, sample_count, mime_type = exported_track.mime_type, "Exported track");
+    Ok(exported_track)
+}
+
+// a tx3g sample starts with the length of its UTF-8 text, optionally followed by style boxes
+fn tx3g_text(bytes: &[u8]) -> String {
+    if bytes.len() < 2 {
+        return String::new();
+    }
+    let length = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    let text = &bytes[2..(2 + length).min(bytes.len())];
+    String::from_utf8_lossy(text).into_owned()
+}
+
+fn webvtt_timestamp(time_in_ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        time_in_ms / 3_600_000,
+        time_in_ms / 60_000 % 60,
+        time_in_ms / 1000 % 60,
+        time_in_ms % 1000,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mp4::{AacConfig, AudioObjectType, ChannelConfig, MediaConfig, SampleFreqIndex, TtxtConfig};
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_demuxer() {
+        assert_eq!(tx3g_text(&[0x00, 0x02, b'h', b'i', 0x00, 0x00, 0x00, 0x08]), "hi");
+        assert_eq!(tx3g_text(&[0x00, 0x00]), "");
+        assert_eq!(webvtt_timestamp(3_723_004), "01:02:03.004");
+
+        // one second of AAC audio and two subtitle cues around a gap
+        let source = std::env::temp_dir().join(format!("mp4-demuxer-{}.mp4", std::process::id()));
+        let audio_target = source.with_extension("m4a");
+        let subtitle_target = source.with_extension("vtt");
+        let config = Mp4Config {
+            major_brand: str::parse("isom").unwrap(),
+            minor_version: 512,
+            compatible_brands: vec![str::parse("isom").unwrap()],
+            timescale: 1000,
+        };
+        let mut writer = Mp4Writer::write_start(BufWriter::new(File::create(&source).unwrap()), &config).unwrap();
+        writer.add_track(&TrackConfig::from(MediaConfig::AacConfig(AacConfig {
+            bitrate: 128000,
+            profile: AudioObjectType::AacLowComplexity,
+            freq_index: SampleFreqIndex::Freq48000,
+            chan_conf: ChannelConfig::Stereo,
+        }))).unwrap();
+        writer.add_track(&TrackConfig::from(MediaConfig::TtxtConfig(TtxtConfig {}))).unwrap();
+        for index in 0..10u64 {
+            writer.write_sample(1, &mp4::Mp4Sample {
+                start_time: index * 100,
+                duration: 100,
+                rendering_offset: 0,
+                is_sync: true,
+                bytes: mp4::Bytes::from(vec![index as u8; 8]),
+            }).unwrap();
+        }
+        for (index, text) in ["first", "", "second"].iter().enumerate() {
+            let mut bytes = (text.len() as u16).to_be_bytes().to_vec();
+            bytes.extend_from_slice(text.as_bytes());
+            writer.write_sample(2, &mp4::Mp4Sample {
+                start_time: index as u64 * 400,
+                duration: 400,
+                rendering_offset: 0,
+                is_sync: true,
+                bytes: mp4::Bytes::from(bytes),
+            }).unwrap();
+        }
+        writer.write_end().unwrap();
+        writer.into_writer().flush().unwrap();
+        let (source, audio_target, subtitle_target) =
+            (source.to_str().unwrap(), audio_target.to_str().unwrap(), subtitle_target.to_str().unwrap());
+
+        let exported_track = export_track_to_file(source, audio_target, TrackType::Audio, None).unwrap();
+        assert_eq!(exported_track, ExportedTrack { mime_type: "audio/mp4", extension: "m4a" });
+        let f = File::open(audio_target).unwrap();
+        let size = f.metadata().unwrap().len();
+        let mut audio = mp4::Mp4Reader::read_header(BufReader::new(f), size).unwrap();
+        assert_eq!(audio.tracks().len(), 1);
+        assert_eq!(audio.major_brand().to_string(), "M4A ");
+        assert_eq!(audio.sample_count(1).unwrap(), 10);
+        assert_eq!(audio.read_sample(1, 3).unwrap().unwrap().bytes.as_ref(), &[2u8; 8]);
+
+        let exported_track = export_track_to_file(source, subtitle_target, TrackType::Subtitle, Some(2)).unwrap();
+        assert_eq!(exported_track.extension, "vtt");
+        assert_eq!(
+            std::fs::read_to_string(subtitle_target).unwrap(),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:00.400\nfirst\n\n00:00:00.800 --> 00:00:01.200\nsecond\n",
+        );
+
+        let err = export_track_to_file(source, audio_target, TrackType::Video, None).unwrap_err();
+        assert_eq!(err.downcast_ref::<Mp4DemuxError>().unwrap().code, Mp4DemuxError::TRACK_NOT_FOUND);
+        // the id of the subtitle track does not match an audio track
+        let err = export_track_to_file(source, audio_target, TrackType::Audio, Some(2)).unwrap_err();
+        assert_eq!(err.downcast_ref::<Mp4DemuxError>().unwrap().code, Mp4DemuxError::TRACK_NOT_FOUND);
+        for path in [source, audio_target, subtitle_target] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mp4_demuxer_with_two_tracks_of_the_same_type() {
+        // two AAC tracks whose samples differ by their bytes
+        let source = std::env::temp_dir().join(format!("mp4-demuxer-two-tracks-{}.mp4", std::process::id()));
+        let target = source.with_extension("m4a");
+        let config = Mp4Config {
+            major_brand: str::parse("isom").unwrap(),
+            minor_version: 512,
+            compatible_brands: vec![str::parse("isom").unwrap()],
+            timescale: 1000,
+        };
+        let mut writer = Mp4Writer::write_start(BufWriter::new(File::create(&source).unwrap()), &config).unwrap();
+        for _ in 0..2 {
+            writer.add_track(&TrackConfig::from(MediaConfig::AacConfig(AacConfig {
+                bitrate: 128000,
+                profile: AudioObjectType::AacLowComplexity,
+                freq_index: SampleFreqIndex::Freq48000,
+                chan_conf: ChannelConfig::Stereo,
+            }))).unwrap();
+        }
+        for track_id in 1..=2u32 {
+            for index in 0..5u64 {
+                writer.write_sample(track_id, &mp4::Mp4Sample {
+                    start_time: index * 100,
+                    duration: 100,
+                    rendering_offset: 0,
+                    is_sync: true,
+                    bytes: mp4::Bytes::from(vec![track_id as u8 * 10; 8]),
+                }).unwrap();
+            }
+        }
+        writer.write_end().unwrap();
+        writer.into_writer().flush().unwrap();
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+
+        let first_sample_of_export = |track_id: Option<u32>| {
+            export_track_to_file(source, target, TrackType::Audio, track_id).unwrap();
+            let f = File::open(target).unwrap();
+            let size = f.metadata().unwrap().len();
+            let mut audio = mp4::Mp4Reader::read_header(BufReader::new(f), size).unwrap();
+            assert_eq!(audio.tracks().len(), 1);
+            audio.read_sample(1, 1).unwrap().unwrap().bytes.to_vec()
+        };
+        assert_eq!(first_sample_of_export(Some(2)), vec![20u8; 8]);
+        assert_eq!(first_sample_of_export(Some(1)), vec![10u8; 8]);
+        assert_eq!(first_sample_of_export(None), vec![10u8; 8]);
+
+        let err = export_track_to_file(source, target, TrackType::Audio, Some(3)).unwrap_err();
+        assert_eq!(err.downcast_ref::<Mp4DemuxError>().unwrap().code, Mp4DemuxError::TRACK_NOT_FOUND);
+        for path in [source, target] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
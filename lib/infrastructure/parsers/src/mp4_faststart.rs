@@ -0,0 +1,135 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+use tracing::info;
+
+use crate::mp4_metadata::shift_chunk_offsets;
+use crate::mp4_validator::{read_at, read_boxes, BoxHeader};
+
+/// Whether progressive playback can start before the whole file has been downloaded,
+/// i.e. the `moov` box precedes the media data. Files without media data count as web-optimized.
+pub fn is_faststart<R: Read + Seek>(reader: &mut R, size: u64) -> Result<bool, Box<dyn std::error::Error>> {
+    let boxes = read_boxes(reader, 0, size)?;
+    let (moov, mdat) = find_moov_and_first_mdat(&boxes)?;
+    Ok(mdat.is_none_or(|mdat| moov.offset < mdat.offset))
+}
+
+/// Copies `source` to `target` with the `moov` box moved in front of the first `mdat` box, shifting the chunk
+/// offsets of the media data moved along. Returns `false` without writing `target` if `source` is web-optimized already.
+#[tracing::instrument]
+pub fn faststart_file(source: &str, target: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let source_file = File::open(source)?;
+    let size = source_file.metadata()?.len();
+    let mut reader = BufReader::new(source_file);
+    let boxes = read_boxes(&mut reader, 0, size)?;
+    let (moov, mdat) = find_moov_and_first_mdat(&boxes)?;
+    let mdat = match mdat {
+        Some(mdat) if mdat.offset < moov.offset => mdat.clone(),
+        _ => return Ok(false),
+    };
+    let moov = moov.clone();
+    if moov.header_size != 8 {
+        return Err("The moov box has a 64 bit size".into());
+    }
+
+    // everything between the first mdat and the moov box moves back by the size of the moov box,
+    // boxes behind the moov box keep their position
+    let mut new_moov = read_at(&mut reader, moov.offset, moov.size)?;
+    shift_chunk_offsets(&mut new_moov, mdat.offset..moov.offset, moov.size as i64)?;
+
+    let mut writer = BufWriter::new(File::create(target)?);
+    copy_range(&mut reader, &mut writer, 0, mdat.offset)?;
+    writer.write_all(&new_moov)?;
+    copy_range(&mut reader, &mut writer, mdat.offset, moov.offset)?;
+    copy_range(&mut reader, &mut writer, moov.end(), size)?;
+    writer.flush()?;
+    info!("Successfully moved the moov box of {} in front of the media data", source);
+    Ok(true)
+}
+
+fn find_moov_and_first_mdat(boxes: &[BoxHeader]) -> Result<(&BoxHeader, Option<&BoxHeader>), Box<dyn std::error::Error>> {
+    let moov = boxes.iter().find(|b| &b.box_type == b"moov").ok_or("The file contains no moov box")?;
+    Ok((moov, boxes.iter().find(|b| &b.box_type == b"mdat")))
+}
+
+fn copy_range<R: Read + Seek, W: Write>(reader: &mut R, writer: &mut W, start: u64, end: u64) -> Result<(), io::Error> {
+    reader.seek(SeekFrom::Start(start))?;
+    io::copy(&mut reader.take(end - start), writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4_validator::Mp4Validator;
+    use crate::test_boxes::{full_box, mp4_box};
+    use std::io::Cursor;
+
+    fn moov(chunk_offset: u32) -> Vec<u8> {
+        let stco = full_box(b"stco", &[1u32.to_be_bytes(), chunk_offset.to_be_bytes()].concat());
+        mp4_box(b"moov", &[
+            mp4_box(b"mvhd", &[0; 100]),
+            mp4_box(b"trak", &[mp4_box(b"tkhd", &[0; 84]), mp4_box(b"mdia", &mp4_box(b"minf", &mp4_box(b"stbl", &stco)))].concat()),
+        ].concat())
+    }
+
+    fn chunk_offset(bytes: &[u8]) -> usize {
+        let stco = bytes.windows(4).position(|window| window == b"stco").unwrap();
+        u32::from_be_bytes(bytes[stco + 12..stco + 16].try_into().unwrap()) as usize
+    }
+
+    fn is_faststart_bytes(bytes: &[u8]) -> bool {
+        is_faststart(&mut Cursor::new(bytes), bytes.len() as u64).unwrap()
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_faststart() {
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\x02\0isomiso2avc1mp41");
+        let mdat = mp4_box(b"mdat", b"media payload");
+        let bytes = [ftyp.clone(), mdat.clone(), moov((ftyp.len() + 8) as u32), mp4_box(b"free", &[0; 4])].concat();
+        assert!(!is_faststart_bytes(&bytes));
+
+        let source = std::env::temp_dir().join(format!("mp4-faststart-{}.mp4", std::process::id()));
+        let target = source.with_extension("faststart.mp4");
+        std::fs::write(&source, &bytes).unwrap();
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+        assert!(faststart_file(source, target).unwrap());
+        let written = std::fs::read(target).unwrap();
+        assert_eq!(written.len(), bytes.len());
+        assert!(is_faststart_bytes(&written));
+        assert_eq!(&written[chunk_offset(&written)..chunk_offset(&written) + 13], b"media payload");
+        assert!(Mp4Validator::new().validate(&mut Cursor::new(&written), written.len() as u64).is_ok());
+
+        // web-optimized files are left alone
+        std::fs::remove_file(target).unwrap();
+        std::fs::write(source, &written).unwrap();
+        assert!(!faststart_file(source, target).unwrap());
+        assert!(!std::path::Path::new(target).exists());
+        std::fs::remove_file(source).unwrap();
+    }
+}
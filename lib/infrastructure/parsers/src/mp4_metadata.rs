@@ -0,0 +1,594 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use tracing::info;
+
+use crate::mp4_validator::{read_at, read_boxes, BoxHeader};
+
+// usertype of the top level `uuid` box holding an XMP packet
+const XMP_UUID: [u8; 16] = [0xbe, 0x7a, 0xcf, 0xcb, 0x97, 0xa9, 0x42, 0xe8, 0x9c, 0x71, 0x99, 0x94, 0x91, 0xe3, 0xaf, 0xac];
+// seconds between 1904-01-01, the epoch of `mvhd` times, and 1970-01-01
+const MAC_EPOCH_OFFSET_IN_SECS: i64 = 2_082_844_800;
+// larger metadata boxes, e.g. huge cover art, are skipped rather than read into memory
+const MAX_METADATA_BOX_SIZE: u64 = 16 * 1024 * 1024;
+// well-known types of the `data` box of an ilst item
+const DATA_TYPE_UTF8: u32 = 1;
+const DATA_TYPE_UTF16: u32 = 2;
+const DATA_TYPE_JPEG: u32 = 13;
+const DATA_TYPE_PNG: u32 = 14;
+
+const TITLE: &[u8; 4] = b"\xa9nam";
+const ARTIST: &[u8; 4] = b"\xa9ART";
+const DESCRIPTION: &[u8; 4] = b"desc";
+const LONG_DESCRIPTION: &[u8; 4] = b"ldes";
+const COMMENT: &[u8; 4] = b"\xa9cmt";
+const DAY: &[u8; 4] = b"\xa9day";
+const KEYWORDS: &[u8; 4] = b"keyw";
+const COVER_ART: &[u8; 4] = b"covr";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverArt {
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// Metadata embedded by the producer of a file. iTunes style `ilst` items take precedence over QuickTime
+/// `udta` text atoms, which take precedence over XMP.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmbeddedMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub description: Option<String>,
+    pub comment: Option<String>,
+    pub tags: Vec<String>,
+    pub creation_time: Option<DateTime<Utc>>,
+    pub cover_art: Option<CoverArt>,
+}
+
+impl EmbeddedMetadata {
+    fn set_text(field: &mut Option<String>, value: &str) {
+        let value = value.trim();
+        if field.is_none() && !value.is_empty() {
+            *field = Some(value.to_string());
+        }
+    }
+
+    fn set_tags(&mut self, tags: impl Iterator<Item = String>) {
+        if self.tags.is_empty() {
+            self.tags = tags.map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect();
+        }
+    }
+
+    fn set_creation_time(&mut self, value: &str) {
+        if self.creation_time.is_none() {
+            self.creation_time = parse_date_time(value.trim());
+        }
+    }
+}
+
+#[tracing::instrument]
+pub fn read_metadata_from_file(filename: &str) -> Result<EmbeddedMetadata, Box<dyn std::error::Error>> {
+    let f = File::open(filename)?;
+    let size = f.metadata()?.len();
+    read_metadata(&mut BufReader::new(f), size)
+}
+
+pub fn read_metadata<R: Read + Seek>(reader: &mut R, size: u64) -> Result<EmbeddedMetadata, Box<dyn std::error::Error>> {
+    let mut metadata = EmbeddedMetadata::default();
+    let boxes = read_boxes(reader, 0, size)?;
+    let mut xmp_packets = Vec::new();
+    if let Some(moov) = find_box(&boxes, b"moov") {
+        let moov_boxes = read_boxes(reader, moov.content_offset(), moov.end())?;
+        let udta_boxes = match find_box(&moov_boxes, b"udta") {
+            Some(udta) => read_udta_boxes(reader, udta)?,
+            None => Vec::new(),
+        };
+        // some producers put the meta box into moov rather than udta
+        for meta in udta_boxes.iter().chain(moov_boxes.iter()).filter(|b| &b.box_type == b"meta") {
+            read_ilst(reader, meta, &mut metadata)?;
+        }
+        for atom in udta_boxes.iter() {
+            match &atom.box_type {
+                b"XMP_" => xmp_packets.push(read_payload(reader, atom)?),
+                box_type if box_type[0] == 0xa9 => read_quicktime_text(reader, atom, &mut metadata)?,
+                _ => {}
+            }
+        }
+        if let Some(mvhd) = find_box(&moov_boxes, b"mvhd") {
+            if let Some(creation_time) = read_mvhd_creation_time(reader, mvhd)? {
+                metadata.creation_time.get_or_insert(creation_time);
+            }
+        }
+    }
+    for uuid in boxes.iter().filter(|b| &b.box_type == b"uuid" && b.size >= b.header_size + 16) {
+        let payload = read_payload(reader, uuid)?;
+        if payload[..16] == XMP_UUID {
+            xmp_packets.push(payload[16..].to_vec());
+        }
+    }
+    for xmp_packet in xmp_packets {
+        read_xmp(&String::from_utf8_lossy(&xmp_packet), &mut metadata);
+    }
+    Ok(metadata)
+}
+
+fn find_box<'a>(boxes: &'a [BoxHeader], box_type: &[u8; 4]) -> Option<&'a BoxHeader> {
+    boxes.iter().find(|b| &b.box_type == box_type)
+}
+
+fn read_payload<R: Read + Seek>(reader: &mut R, header: &BoxHeader) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let len = header.end() - header.content_offset();
+    if len > MAX_METADATA_BOX_SIZE {
+        return Err(format!("The {} box at offset {} exceeds {} bytes", header.name(), header.offset, MAX_METADATA_BOX_SIZE).into());
+    }
+    Ok(read_at(reader, header.content_offset(), len)?)
+}
+
+// QuickTime terminates the udta box with four zero bytes, which are no box
+fn read_udta_boxes<R: Read + Seek>(reader: &mut R, udta: &BoxHeader) -> Result<Vec<BoxHeader>, Box<dyn std::error::Error>> {
+    match read_boxes(reader, udta.content_offset(), udta.end()) {
+        Ok(boxes) => Ok(boxes),
+        Err(err) => {
+            if udta.end() - udta.content_offset() < 4 || read_at(reader, udta.end() - 4, 4)? != [0; 4] {
+                return Err(Box::new(err));
+            }
+            Ok(read_boxes(reader, udta.content_offset(), udta.end() - 4)?)
+        }
+    }
+}
+
+// the meta box is a full box in MP4 files but a plain box in QuickTime files
+fn meta_children_offset<R: Read + Seek>(reader: &mut R, meta: &BoxHeader) -> Result<u64, Box<dyn std::error::Error>> {
+    if meta.end() - meta.content_offset() >= 8 && read_at(reader, meta.content_offset() + 4, 4)? == b"hdlr" {
+        return Ok(meta.content_offset());
+    }
+    Ok(meta.content_offset() + 4)
+}
+
+fn read_ilst<R: Read + Seek>(reader: &mut R, meta: &BoxHeader, metadata: &mut EmbeddedMetadata) -> Result<(), Box<dyn std::error::Error>> {
+    let children_offset = meta_children_offset(reader, meta)?;
+    let meta_boxes = read_boxes(reader, children_offset, meta.end())?;
+    let ilst = match find_box(&meta_boxes, b"ilst") {
+        Some(ilst) => ilst,
+        None => return Ok(()),
+    };
+    for item in read_boxes(reader, ilst.content_offset(), ilst.end())? {
+        if item.size > MAX_METADATA_BOX_SIZE {
+            continue;
+        }
+        let item_boxes = read_boxes(reader, item.content_offset(), item.end())?;
+        let data = match find_box(&item_boxes, b"data") {
+            Some(data) if data.end() - data.content_offset() >= 8 => read_payload(reader, data)?,
+            _ => continue,
+        };
+        let data_type = u32::from_be_bytes([0, data[1], data[2], data[3]]);
+        let value = &data[8..];
+        let text = match data_type {
+            DATA_TYPE_UTF8 => String::from_utf8_lossy(value).to_string(),
+            DATA_TYPE_UTF16 => String::from_utf16_lossy(
+                &value.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect::<Vec<u16>>()),
+            _ => String::new(),
+        };
+        match &item.box_type {
+            TITLE => EmbeddedMetadata::set_text(&mut metadata.title, &text),
+            ARTIST => EmbeddedMetadata::set_text(&mut metadata.artist, &text),
+            DESCRIPTION | LONG_DESCRIPTION => EmbeddedMetadata::set_text(&mut metadata.description, &text),
+            COMMENT => EmbeddedMetadata::set_text(&mut metadata.comment, &text),
+            DAY => metadata.set_creation_time(&text),
+            KEYWORDS => metadata.set_tags(text.split(',').map(String::from)),
+            COVER_ART if metadata.cover_art.is_none() => {
+                let mime_type = match data_type {
+                    DATA_TYPE_JPEG => "image/jpeg",
+                    DATA_TYPE_PNG => "image/png",
+                    _ => continue,
+                };
+                if let Some((width, height)) = image_dimensions(mime_type, value) {
+                    metadata.cover_art = Some(CoverArt { mime_type: String::from(mime_type), width, height, bytes: value.to_vec() });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// QuickTime text atoms hold a list of (length, language, text) entries of which the first is used
+fn read_quicktime_text<R: Read + Seek>(reader: &mut R, atom: &BoxHeader, metadata: &mut EmbeddedMetadata) -> Result<(), Box<dyn std::error::Error>> {
+    let payload = read_payload(reader, atom)?;
+    if payload.len() < 4 {
+        return Ok(());
+    }
+    let len = (u16::from_be_bytes([payload[0], payload[1]]) as usize).min(payload.len() - 4);
+    let text = String::from_utf8_lossy(&payload[4..4 + len]).to_string();
+    match &atom.box_type {
+        TITLE => EmbeddedMetadata::set_text(&mut metadata.title, &text),
+        ARTIST | b"\xa9aut" => EmbeddedMetadata::set_text(&mut metadata.artist, &text),
+        b"\xa9des" => EmbeddedMetadata::set_text(&mut metadata.description, &text),
+        COMMENT => EmbeddedMetadata::set_text(&mut metadata.comment, &text),
+        DAY => metadata.set_creation_time(&text),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn read_mvhd_creation_time<R: Read + Seek>(reader: &mut R, mvhd: &BoxHeader) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+    if mvhd.end() - mvhd.content_offset() < 12 {
+        return Ok(None);
+    }
+    let head = read_at(reader, mvhd.content_offset(), 12)?;
+    let seconds = match head[0] {
+        1 => u64::from_be_bytes(head[4..12].try_into().unwrap()),
+        _ => u32::from_be_bytes(head[4..8].try_into().unwrap()) as u64,
+    };
+    // encoders not knowing the time write 0
+    if seconds == 0 {
+        return Ok(None);
+    }
+    Ok(DateTime::from_timestamp(seconds as i64 - MAC_EPOCH_OFFSET_IN_SECS, 0))
+}
+
+// accepts RFC 3339 date times as well as plain dates and years, e.g. `2024-05-16` or `2024`
+fn parse_date_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+        .or_else(|| value.parse::<i32>().ok().and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1)))?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+fn read_xmp(xmp: &str, metadata: &mut EmbeddedMetadata) {
+    if let Some(title) = xmp_values(xmp, "dc:title").first() {
+        EmbeddedMetadata::set_text(&mut metadata.title, title);
+    }
+    if let Some(description) = xmp_values(xmp, "dc:description").first() {
+        EmbeddedMetadata::set_text(&mut metadata.description, description);
+    }
+    if let Some(creator) = xmp_values(xmp, "dc:creator").first() {
+        EmbeddedMetadata::set_text(&mut metadata.artist, creator);
+    }
+    metadata.set_tags(xmp_values(xmp, "dc:subject").into_iter());
+    if let Some(create_date) = xmp_values(xmp, "xmp:CreateDate").first() {
+        metadata.set_creation_time(create_date);
+    }
+}
+
+// values of an XMP property, the `rdf:li` items of an `rdf:Alt`, `rdf:Bag` or `rdf:Seq`, the element text or the attribute value
+fn xmp_values(xmp: &str, property: &str) -> Vec<String> {
+    let open = format!("<{}", property);
+    let close = format!("</{}>", property);
+    if let Some(start) = xmp.find(&open).map(|start| start + open.len()) {
+        let rest = &xmp[start..];
+        if let (Some(tag_end), Some(end)) = (rest.find('>'), rest.find(&close)) {
+            if tag_end < end {
+                let inner = &rest[tag_end + 1..end];
+                let items: Vec<String> = inner.split("<rdf:li").skip(1)
+                    .filter_map(|item| {
+                        let text = &item[item.find('>')? + 1..];
+                        Some(unescape_xml(&text[..text.find("</rdf:li>")?]))
+                    })
+                    .collect();
+                if !items.is_empty() || inner.contains("<rdf:") {
+                    return items;
+                }
+                return vec![unescape_xml(inner)];
+            }
+        }
+    }
+    let attribute = format!("{}=\"", property);
+    match xmp.find(&attribute).map(|start| &xmp[start + attribute.len()..]) {
+        Some(rest) => rest.find('"').map(|end| vec![unescape_xml(&rest[..end])]).unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// width and height from the PNG IHDR chunk or the first JPEG start of frame segment
+fn image_dimensions(mime_type: &str, bytes: &[u8]) -> Option<(u32, u32)> {
+    if mime_type == "image/png" {
+        if bytes.len() < 24 || &bytes[12..16] != b"IHDR" {
+            return None;
+        }
+        return Some((u32::from_be_bytes(bytes[16..20].try_into().ok()?), u32::from_be_bytes(bytes[20..24].try_into().ok()?)));
+    }
+    let mut offset = 2;
+    while offset + 9 <= bytes.len() && bytes[offset] == 0xff {
+        let marker = bytes[offset + 1];
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            let height = u16::from_be_bytes([bytes[offset + 5], bytes[offset + 6]]) as u32;
+            let width = u16::from_be_bytes([bytes[offset + 7], bytes[offset + 8]]) as u32;
+            return Some((width, height));
+        }
+        offset += 2 + u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) as usize;
+    }
+    None
+}
+
+fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let size = u32::try_from(payload.len() + 8).map_err(|_| format!("The {} box exceeds 4 GiB", String::from_utf8_lossy(box_type)))?;
+    let mut bytes = size.to_be_bytes().to_vec();
+    bytes.extend_from_slice(box_type);
+    bytes.extend_from_slice(payload);
+    Ok(bytes)
+}
+
+fn text_item(box_type: &[u8; 4], text: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut data = DATA_TYPE_UTF8.to_be_bytes().to_vec();
+    data.extend([0; 4]);
+    data.extend_from_slice(text.as_bytes());
+    mp4_box(box_type, &mp4_box(b"data", &data)?)
+}
+
+/// Copies `source` to `target` replacing the title, description and keywords of the `ilst` box, empty values
+/// remove the item. Other items such as cover art are kept. Chunk offsets are shifted if `moov` precedes the media data.
+#[tracing::instrument]
+pub fn write_metadata_to_file(source: &str, target: &str, title: &str, description: &str, tags: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let source_file = File::open(source)?;
+    let size = source_file.metadata()?.len();
+    let mut reader = BufReader::new(source_file);
+    let boxes = read_boxes(&mut reader, 0, size)?;
+    let moov = find_box(&boxes, b"moov").ok_or("The file contains no moov box")?.clone();
+    let moov_bytes = read_at(&mut reader, moov.offset, moov.size)?;
+
+    let mut items = Vec::new();
+    for (box_type, text) in [(TITLE, title), (DESCRIPTION, description), (KEYWORDS, &tags.join(","))] {
+        if !text.trim().is_empty() {
+            items.push(text_item(box_type, text.trim())?);
+        }
+    }
+    let mut new_moov = rewrite_moov(&moov_bytes, moov.header_size, &items)?;
+    let delta = new_moov.len() as i64 - moov.size as i64;
+    if delta != 0 {
+        shift_chunk_offsets(&mut new_moov, moov.end()..u64::MAX, delta)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(target)?);
+    reader.seek(SeekFrom::Start(0))?;
+    io::copy(&mut (&mut reader).take(moov.offset), &mut writer)?;
+    writer.write_all(&new_moov)?;
+    reader.seek(SeekFrom::Start(moov.end()))?;
+    io::copy(&mut reader, &mut writer)?;
+    writer.flush()?;
+    info!("Successfully wrote metadata of {} to {}, the moov box changed by {} bytes", source, target, delta);
+    Ok(())
+}
+
+// rebuilds moov/udta/meta/ilst, creating missing boxes, and returns the new moov box
+fn rewrite_moov(moov_bytes: &[u8], header_size: u64, items: &[Vec<u8>]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut cursor = Cursor::new(moov_bytes);
+    let moov_boxes = read_boxes(&mut cursor, header_size, moov_bytes.len() as u64)?;
+    let bytes_of = |header: &BoxHeader| &moov_bytes[header.offset as usize..header.end() as usize];
+
+    let mut moov_payload = Vec::new();
+    let mut udta_payload = None;
+    for child in moov_boxes.iter() {
+        match &child.box_type {
+            b"udta" => {
+                let mut payload = Vec::new();
+                let mut meta_payload = None;
+                for udta_child in read_udta_boxes(&mut cursor, child)? {
+                    match &udta_child.box_type {
+                        b"meta" => meta_payload = Some(rewrite_meta(&mut cursor, moov_bytes, &udta_child, items)?),
+                        // superseded QuickTime text atoms would shadow the new values in other players
+                        TITLE | b"\xa9des" => {}
+                        _ => payload.extend_from_slice(bytes_of(&udta_child)),
+                    }
+                }
+                payload.extend(mp4_box(b"meta", &meta_payload.map_or_else(|| new_meta_payload(items), Ok)?)?);
+                udta_payload = Some(payload);
+            }
+            _ => moov_payload.extend_from_slice(bytes_of(child)),
+        }
+    }
+    let udta_payload = match udta_payload {
+        Some(udta_payload) => udta_payload,
+        None => mp4_box(b"meta", &new_meta_payload(items)?)?,
+    };
+    moov_payload.extend(mp4_box(b"udta", &udta_payload)?);
+    mp4_box(b"moov", &moov_payload)
+}
+
+fn new_meta_payload(items: &[Vec<u8>]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // full box header followed by the hdlr box of iTunes metadata
+    let mut payload = vec![0; 4];
+    payload.extend(mp4_box(b"hdlr", &[&[0; 8][..], b"mdirappl", &[0; 9]].concat())?);
+    payload.extend(mp4_box(b"ilst", &items.concat())?);
+    Ok(payload)
+}
+
+fn rewrite_meta(cursor: &mut Cursor<&[u8]>, bytes: &[u8], meta: &BoxHeader, items: &[Vec<u8>]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let children_offset = meta_children_offset(cursor, meta)?;
+    let mut payload = bytes[meta.content_offset() as usize..children_offset as usize].to_vec();
+    let mut has_ilst = false;
+    for child in read_boxes(cursor, children_offset, meta.end())? {
+        let child_bytes = &bytes[child.offset as usize..child.end() as usize];
+        if &child.box_type != b"ilst" {
+            payload.extend_from_slice(child_bytes);
+            continue;
+        }
+        has_ilst = true;
+        let mut ilst_payload = Vec::new();
+        for item in read_boxes(cursor, child.content_offset(), child.end())? {
+            if ![TITLE, DESCRIPTION, LONG_DESCRIPTION, KEYWORDS].contains(&&item.box_type) {
+                ilst_payload.extend_from_slice(&bytes[item.offset as usize..item.end() as usize]);
+            }
+        }
+        ilst_payload.extend(items.concat());
+        payload.extend(mp4_box(b"ilst", &ilst_payload)?);
+    }
+    if !has_ilst {
+        payload.extend(mp4_box(b"ilst", &items.concat())?);
+    }
+    Ok(payload)
+}
+
+/// Adds `delta` to every `stco` and `co64` chunk offset of the moov box within `offsets`,
+/// keeping the offsets valid after a box in front of the media data changed its size or moved.
+pub(crate) fn shift_chunk_offsets(moov: &mut [u8], offsets: Range<u64>, delta: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut pending = vec![(8u64, moov.len() as u64)];
+    while let Some((start, end)) = pending.pop() {
+        let children = read_boxes(&mut Cursor::new(&*moov), start, end)?;
+        for child in children {
+            match &child.box_type {
+                b"trak" | b"mdia" | b"minf" | b"stbl" => pending.push((child.content_offset(), child.end())),
+                b"stco" | b"co64" => {
+                    let entry_size = if &child.box_type == b"stco" { 4 } else { 8 };
+                    let table = &mut moov[child.content_offset() as usize..child.end() as usize];
+                    if table.len() < 8 {
+                        return Err(format!("Truncated {} box at offset {}", child.name(), child.offset).into());
+                    }
+                    let entry_count = u32::from_be_bytes(table[4..8].try_into().unwrap()) as usize;
+                    if table.len() < 8 + entry_count * entry_size {
+                        return Err(format!("The {} box at offset {} is too small for {} entries", child.name(), child.offset, entry_count).into());
+                    }
+                    for entry in table[8..8 + entry_count * entry_size].chunks_exact_mut(entry_size) {
+                        let offset = match entry_size {
+                            4 => u32::from_be_bytes(entry.try_into().unwrap()) as u64,
+                            _ => u64::from_be_bytes(entry.try_into().unwrap()),
+                        };
+                        if !offsets.contains(&offset) {
+                            continue;
+                        }
+                        let shifted = offset.checked_add_signed(delta).ok_or("Chunk offset out of range")?;
+                        match entry_size {
+                            4 => entry.copy_from_slice(&u32::try_from(shifted).map_err(|_| "Chunk offset exceeds the stco box")?.to_be_bytes()),
+                            _ => entry.copy_from_slice(&shifted.to_be_bytes()),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4_validator::Mp4Validator;
+    use crate::test_boxes::{full_box, mp4_box};
+
+    fn data_item(box_type: &[u8; 4], data_type: u32, value: &[u8]) -> Vec<u8> {
+        mp4_box(box_type, &mp4_box(b"data", &[&data_type.to_be_bytes()[..], &[0; 4], value].concat()))
+    }
+
+    // ftyp, moov with a single chunk pointing behind the moov box, mdat
+    fn mp4(udta: &[u8], trailer: &[u8]) -> Vec<u8> {
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\x02\0isomiso2avc1mp41");
+        // created 2024-05-16T10:00:00Z
+        let creation_time = (1_715_853_600i64 + MAC_EPOCH_OFFSET_IN_SECS) as u32;
+        let mvhd = full_box(b"mvhd", &[&creation_time.to_be_bytes()[..], &[0; 92]].concat());
+        let stco = |chunk_offset: u32| full_box(b"stco", &[1u32.to_be_bytes(), chunk_offset.to_be_bytes()].concat());
+        let trak = |chunk_offset: u32| mp4_box(b"trak", &[
+            mp4_box(b"tkhd", &[0; 84]),
+            mp4_box(b"mdia", &mp4_box(b"minf", &mp4_box(b"stbl", &stco(chunk_offset)))),
+        ].concat());
+        let moov_len = 8 + mvhd.len() + trak(0).len() + udta.len();
+        let chunk_offset = (ftyp.len() + moov_len + 8) as u32;
+        let moov = mp4_box(b"moov", &[mvhd, trak(chunk_offset), udta.to_vec()].concat());
+        [ftyp, moov, mp4_box(b"mdat", b"media payload"), trailer.to_vec()].concat()
+    }
+
+    fn chunk_offset(bytes: &[u8]) -> usize {
+        let stco = bytes.windows(4).position(|window| window == b"stco").unwrap();
+        u32::from_be_bytes(bytes[stco + 12..stco + 16].try_into().unwrap()) as usize
+    }
+
+    fn read(bytes: &[u8]) -> EmbeddedMetadata {
+        read_metadata(&mut Cursor::new(bytes), bytes.len() as u64).unwrap()
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_metadata() {
+        let png = [&b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"[..], &640u32.to_be_bytes(), &480u32.to_be_bytes(), &[8, 2, 0, 0, 0]].concat();
+        let ilst = mp4_box(b"ilst", &[
+            data_item(TITLE, DATA_TYPE_UTF8, b"Peace"),
+            data_item(ARTIST, DATA_TYPE_UTF8, b"MGTheTrain"),
+            data_item(KEYWORDS, DATA_TYPE_UTF8, b"Nature, Forest"),
+            data_item(COVER_ART, DATA_TYPE_PNG, &png),
+        ].concat());
+        let quicktime_description = mp4_box(b"\xa9des", &[&4u16.to_be_bytes()[..], &[0x55, 0xc4], b"Calm"].concat());
+        let meta = full_box(b"meta", &[mp4_box(b"hdlr", &[&[0; 8][..], b"mdirappl", &[0; 9]].concat()), ilst].concat());
+        let xmp = [&XMP_UUID[..], br#"<x:xmpmeta><rdf:Description xmp:CreateDate="2020-01-01T00:00:00Z"><dc:title><rdf:Alt><rdf:li xml:lang="x-default">Ignored &amp; shadowed</rdf:li></rdf:Alt></dc:title>
+            <dc:subject><rdf:Bag><rdf:li>Ignored</rdf:li></rdf:Bag></dc:subject></rdf:Description></x:xmpmeta>"#].concat();
+        // QuickTime style udta terminated by four zero bytes
+        let udta = mp4_box(b"udta", &[meta, quicktime_description, vec![0; 4]].concat());
+        let bytes = mp4(&udta, &mp4_box(b"uuid", &xmp));
+
+        let metadata = read(&bytes);
+        assert_eq!(metadata.title.as_deref(), Some("Peace"));
+        assert_eq!(metadata.artist.as_deref(), Some("MGTheTrain"));
+        assert_eq!(metadata.description.as_deref(), Some("Calm"));
+        assert_eq!(metadata.tags, vec!["Nature", "Forest"]);
+        assert_eq!(metadata.creation_time, Some(DateTime::parse_from_rfc3339("2024-05-16T10:00:00Z").unwrap().with_timezone(&Utc)));
+        let cover_art = metadata.cover_art.unwrap();
+        assert_eq!((cover_art.mime_type.as_str(), cover_art.width, cover_art.height), ("image/png", 640, 480));
+
+        // XMP fills what neither ilst nor udta provide
+        let metadata = read(&mp4(&[], &mp4_box(b"uuid", &xmp)));
+        assert_eq!(metadata.title.as_deref(), Some("Ignored & shadowed"));
+        assert_eq!(metadata.tags, vec!["Ignored"]);
+        assert_eq!(read(&mp4(&[], &[])), EmbeddedMetadata { creation_time: metadata.creation_time, ..Default::default() });
+
+        // Write back keeps the cover art and the media data reachable
+        let source = std::env::temp_dir().join(format!("mp4-metadata-{}.mp4", std::process::id()));
+        let target = source.with_extension("written.mp4");
+        std::fs::write(&source, &bytes).unwrap();
+        let (source, target) = (source.to_str().unwrap(), target.to_str().unwrap());
+        write_metadata_to_file(source, target, "Peace for the world", "", &[String::from("Nature")]).unwrap();
+        let written = std::fs::read(target).unwrap();
+        let metadata = read(&written);
+        assert_eq!(metadata.title.as_deref(), Some("Peace for the world"));
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.tags, vec!["Nature"]);
+        assert!(metadata.cover_art.is_some());
+        assert_eq!(&written[chunk_offset(&written)..chunk_offset(&written) + 13], b"media payload");
+        assert!(Mp4Validator::new().validate(&mut Cursor::new(&written), written.len() as u64).is_ok());
+
+        // Files without udta get one
+        std::fs::write(source, mp4(&[], &[])).unwrap();
+        write_metadata_to_file(source, target, "Peace", "Peace for the world", &[]).unwrap();
+        let written = std::fs::read(target).unwrap();
+        assert_eq!(read(&written).description.as_deref(), Some("Peace for the world"));
+        assert_eq!(&written[chunk_offset(&written)..chunk_offset(&written) + 13], b"media payload");
+        std::fs::remove_file(source).unwrap();
+        std::fs::remove_file(target).unwrap();
+    }
+}
@@ -26,15 +26,33 @@ use std::fs::File;
 use std::io::{prelude::*, Cursor};
 use std::io::{self, BufReader};
 use std::path::Path;
+use std::time::Instant;
 use chrono::{DateTime, Utc};
 use models::container_meta::ContainerMeta;
 use models::model::Model;
 use models::track::{VideoTrack, AudioTrack, SubtitleTrack};
 use uuid::Uuid;
 
-use log::info;
-use mp4::{Error, Mp4Track, TrackType};
+use tracing::{info, warn};
+use mp4::{Error, MediaType, Mp4Track, TrackType};
 use models;
+use crate::mp4_chapters::{self, EmbeddedChapter};
+
+// Annex B start code prepended to every NAL unit handed over to a H.264 decoder
+const ANNEX_B_START_CODE: [u8; 4] = [0x00, 0x00, 0x00, 0x01];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    pub track_id: u32,
+    pub sample_id: u32,
+    pub time_in_ms: u64,
+    pub width: u16,
+    pub height: u16,
+    pub bytes: Vec<u8>, // H.264 Annex B byte stream with SPS and PPS ahead of the sample's NAL units
+}
+
+/// Container meta data along with the first video, audio and subtitle track found in the file
+pub type ParsedContainer = (ContainerMeta, Option<VideoTrack>, Option<AudioTrack>, Option<SubtitleTrack>);
 
 #[derive(Clone)]
 pub struct Mp4Parser {} 
@@ -44,8 +62,23 @@ impl Mp4Parser {
         Mp4Parser {}
     }
 
+    #[tracing::instrument(skip(self))]
     pub fn parse_from_file(&self, filename: &str) -> 
-    Result<(ContainerMeta, Option<VideoTrack>, Option<AudioTrack>, Option<SubtitleTrack>), Box<dyn std::error::Error>> {
+    Result<ParsedContainer, Box<dyn std::error::Error>> {
+        let started = Instant::now();
+        // box type of the track being parsed, labels failures with the codec which could not be handled
+        let mut codec = String::from("unknown");
+        let result = self.parse_tracks_from_file(filename, &mut codec);
+        metrics::histogram!("mms_mp4_parse_duration_seconds").record(started.elapsed().as_secs_f64());
+        if let Err(err) = &result {
+            metrics::counter!("mms_mp4_parse_failures_total", "codec" => codec.clone()).increment(1);
+            warn!(%codec, error = %err, "Failed to parse MP4");
+        }
+        result
+    }
+
+    fn parse_tracks_from_file(&self, filename: &str, codec: &mut String) -> 
+    Result<ParsedContainer, Box<dyn std::error::Error>> {
         let f = File::open(filename)?;
         let size = f.metadata()?.len();
         let reader = BufReader::new(f);
@@ -61,8 +94,14 @@ impl Mp4Parser {
         container_meta.id = Uuid::new_v4();
         container_meta.date_time_created = Utc::now();
         container_meta.date_time_updated = container_meta.date_time_created;
-        container_meta.file_size_in_kb = mp4.size() as i64;
-        container_meta.duration = mp4.duration().as_secs() as f64;
+        container_meta.file_size_bytes = mp4.size() as i64;
+        container_meta.duration_in_ms = mp4.duration().as_millis() as i64;
+        container_meta.major_brand = mp4.major_brand().to_string();
+        container_meta.compatible_brands = mp4
+            .compatible_brands()
+            .iter()
+            .map(|brand| Some(brand.to_string()))
+            .collect();
         // container_meta title, description and tags need to be set
 
         // info!("File:");
@@ -73,40 +112,138 @@ impl Mp4Parser {
         let mut audio_track: Option<AudioTrack> = None;
         let mut subtitle_track: Option<SubtitleTrack> = None;
     
-        // Tracks
-        for track in mp4.tracks().values() {
-            let media_info = match track.track_type()? {
+        // Tracks, only the first track of each type by track id is kept
+        let mut tracks: Vec<&Mp4Track> = mp4.tracks().values().collect();
+        tracks.sort_by_key(|track| track.track_id());
+        for track in tracks {
+            *codec = track.box_type().map(|box_type| box_type.to_string()).unwrap_or(String::from("unknown"));
+            let track_type = match track.track_type() {
+                Ok(track_type) => track_type,
+                // QuickTime chapter tracks carry no media, their titles are read by read_chapters
+                Err(_) if &track.trak.mdia.hdlr.handler_type.value == b"text" => continue,
+                Err(err) => return Err(Box::new(err)),
+            };
+            let media_info = match track_type {
                 TrackType::Video => {
-                    let track_id = Uuid::new_v4();
-                    let mut video_info = self.get_video_info(track).unwrap();
-                    video_info.id = track_id;
-                    container_meta.video_track_id = track_id;
-                    video_track = Some(video_info);
+                    let mut video_info = self.get_video_info(track)?;
+                    if video_track.is_none() {
+                        video_info.id = Uuid::new_v4();
+                        container_meta.video_track_id = video_info.id;
+                        video_track = Some(video_info);
+                    }
                 },
                 TrackType::Audio => {
-                    let track_id = Uuid::new_v4();
-                    let mut audio_info = self.get_audio_info(track).unwrap();
-                    audio_info.id = track_id;
-                    container_meta.audio_track_id = track_id;
-                    audio_track = Some(audio_info);
+                    let mut audio_info = self.get_audio_info(track)?;
+                    if audio_track.is_none() {
+                        audio_info.id = Uuid::new_v4();
+                        container_meta.audio_track_id = audio_info.id;
+                        audio_track = Some(audio_info);
+                    }
                 },
                 TrackType::Subtitle => {
-                    let track_id = Uuid::new_v4();
-                    let mut subtitle_info = self.get_subtitle_info(track).unwrap();
-                    subtitle_info.id = track_id;
-                    container_meta.subtitle_track_id = track_id;
-                    subtitle_track = Some(subtitle_info);
+                    let mut subtitle_info = self.get_subtitle_info(track)?;
+                    if subtitle_track.is_none() {
+                        subtitle_info.id = Uuid::new_v4();
+                        container_meta.subtitle_track_id = subtitle_info.id;
+                        subtitle_track = Some(subtitle_info);
+                    }
                 }
             };
         }
         
-        info!("Successfull parsed MP4 filename {}", filename ); 
+        container_meta.mime_type = String::from(mime_type_from_brands(
+            &container_meta.major_brand,
+            &container_meta.compatible_brands,
+            video_track.is_some(),
+        ));
+
+        info!(duration_in_ms = container_meta.duration_in_ms, mime_type = %container_meta.mime_type, "Parsed MP4");
         Ok((container_meta, video_track, audio_track, subtitle_track))
     }
 
+    /// Chapter markers of the file, from a QuickTime chapter track or the Nero chpl box. Empty if it has none.
+    pub fn read_chapters(&self, filename: &str) -> Result<Vec<EmbeddedChapter>, Box<dyn std::error::Error>> {
+        mp4_chapters::read_chapters_from_file(filename)
+    }
+
+    pub fn read_keyframe(&self, filename: &str, time_in_ms: u64) -> Result<Keyframe, Box<dyn std::error::Error>> {
+        let mut keyframes = self.read_keyframes(filename, &[time_in_ms])?;
+        keyframes.pop().ok_or("No keyframe found".into())
+    }
+
+    /// Reads the keyframes closest to each of the requested times, parsing the MP4 header only once.
+    #[tracing::instrument(skip(self, times_in_ms), fields(keyframe_count = times_in_ms.len()))]
+    pub fn read_keyframes(&self, filename: &str, times_in_ms: &[u64]) -> Result<Vec<Keyframe>, Box<dyn std::error::Error>> {
+        let f = File::open(filename)?;
+        let size = f.metadata()?.len();
+        let reader = BufReader::new(f);
+        let mut mp4 = mp4::Mp4Reader::read_header(reader, size)?;
+        if mp4.is_fragmented() {
+            return Err("Keyframe lookup in fragmented MP4 files is not supported".into());
+        }
+
+        let (track_id, timescale, width, height, sample_durations, sync_samples, parameter_sets, nal_length_size) = {
+            let track = mp4
+                .tracks()
+                .values()
+                .find(|track| matches!(track.media_type(), Ok(MediaType::H264)))
+                .ok_or("No H.264 video track found")?;
+            let stbl = &track.trak.mdia.minf.stbl;
+            let avcc = &stbl.stsd.avc1.as_ref().ok_or("No avc1 sample entry found")?.avcc;
+
+            // parameter sets are stored once in the avcC box and not repeated in the samples
+            let mut parameter_sets = Vec::new();
+            for nal_unit in avcc.sequence_parameter_sets.iter().chain(avcc.picture_parameter_sets.iter()) {
+                parameter_sets.extend_from_slice(&ANNEX_B_START_CODE);
+                parameter_sets.extend_from_slice(&nal_unit.bytes);
+            }
+
+            (
+                track.track_id(),
+                track.timescale() as u64,
+                track.width(),
+                track.height(),
+                stbl.stts.entries.iter().map(|entry| (entry.sample_count, entry.sample_delta)).collect::<Vec<(u32, u32)>>(),
+                stbl.stss.as_ref().map(|stss| stss.entries.clone()),
+                parameter_sets,
+                avcc.length_size_minus_one as usize + 1,
+            )
+        };
+        if timescale == 0 {
+            return Err("Invalid timescale of 0 in video track".into());
+        }
+
+        let mut keyframes = Vec::with_capacity(times_in_ms.len());
+        for time_in_ms in times_in_ms {
+            let (sample_id, start_time) =
+                nearest_sync_sample(&sample_durations, sync_samples.as_deref(), time_in_ms * timescale / 1000)
+                    .ok_or("No sync sample found in video track")?;
+            let sample = mp4
+                .read_sample(track_id, sample_id)?
+                .ok_or("Sync sample could not be read")?;
+
+            let mut bytes = parameter_sets.clone();
+            bytes.extend(avcc_to_annex_b(&sample.bytes, nal_length_size)?);
+            keyframes.push(Keyframe {
+                track_id,
+                sample_id,
+                time_in_ms: start_time * 1000 / timescale,
+                width,
+                height,
+                bytes,
+            });
+        }
+
+        info!(track_id, "Read keyframes");
+        Ok(keyframes)
+    }
+
     fn get_video_info(&self, track: &Mp4Track) -> Result<VideoTrack, Box<dyn std::error::Error>> {
         let mut video_track = models::track::VideoTrack::new();
         video_track.media_type = track.media_type()?.to_string() as String;
+        video_track.duration_in_ms = track.duration().as_millis() as i64;
+        video_track.timescale = track.timescale() as i32;
+        video_track.mp4_track_id = track.track_id() as i32;
         video_track.width = track.width() as i32;
         video_track.height = track.height() as i32;
         video_track.bit_rate = (track.bitrate() / 1000) as i32;
@@ -129,6 +266,9 @@ impl Mp4Parser {
     fn get_audio_info(&self, track: &Mp4Track) -> Result<AudioTrack, Box<dyn std::error::Error>> {
         let mut audio_track = AudioTrack::new();
         audio_track.media_type = track.media_type()?.to_string() as String;        
+        audio_track.duration_in_ms = track.duration().as_millis() as i64;
+        audio_track.timescale = track.timescale() as i32;
+        audio_track.mp4_track_id = track.track_id() as i32;
         audio_track.bit_rate = (track.bitrate() / 1000) as i32;   
 
         let profile = match track.audio_profile() {
@@ -165,12 +305,99 @@ impl Mp4Parser {
         
         let mut subtitle_track = SubtitleTrack::new();
         subtitle_track.media_type = track.media_type()?.to_string() as String;        
+        subtitle_track.duration_in_ms = track.duration().as_millis() as i64;
+        subtitle_track.timescale = track.timescale() as i32;
+        subtitle_track.mp4_track_id = track.track_id() as i32;
 
         info!("{} ({:?})", track.media_type()?, track.box_type()?,);
         Ok(subtitle_track)
     }
 }
 
+/// Derives the MIME type from the ftyp brands, audio only MP4 files are served as `audio/mp4`.
+pub fn mime_type_from_brands(major_brand: &str, compatible_brands: &[Option<String>], has_video: bool) -> &'static str {
+    let is_brand = |brand: &str| {
+        major_brand == brand || compatible_brands.iter().any(|compatible_brand| compatible_brand.as_deref() == Some(brand))
+    };
+    if major_brand == "qt  " {
+        "video/quicktime"
+    } else if is_brand("3gp4") || is_brand("3gp5") || is_brand("3gp6") {
+        if has_video { "video/3gpp" } else { "audio/3gpp" }
+    } else if !has_video || major_brand == "M4A " {
+        "audio/mp4"
+    } else {
+        "video/mp4"
+    }
+}
+
+/// Returns the 1-based id and start time of the sync sample closest to `target_time`.
+/// `sample_durations` holds the (sample_count, sample_delta) runs of the stts box and
+/// `sync_samples` the ascending sample ids of the stss box, `None` meaning every sample is a sync sample.
+pub fn nearest_sync_sample(
+    sample_durations: &[(u32, u32)],
+    sync_samples: Option<&[u32]>,
+    target_time: u64,
+) -> Option<(u32, u64)> {
+    let mut nearest: Option<(u32, u64)> = None;
+    let mut sample_id: u32 = 1;
+    let mut start_time: u64 = 0;
+    let mut sync_index = 0;
+
+    for (sample_count, sample_delta) in sample_durations {
+        for _ in 0..*sample_count {
+            let is_sync = match sync_samples {
+                Some(sync_samples) => {
+                    while sync_index < sync_samples.len() && sync_samples[sync_index] < sample_id {
+                        sync_index += 1;
+                    }
+                    sync_index < sync_samples.len() && sync_samples[sync_index] == sample_id
+                }
+                None => true,
+            };
+            if is_sync {
+                match nearest {
+                    // start times only grow, so once the distance increases the nearest one was found
+                    Some((_, nearest_time))
+                        if nearest_time.abs_diff(target_time) <= start_time.abs_diff(target_time) =>
+                    {
+                        return nearest;
+                    }
+                    _ => nearest = Some((sample_id, start_time)),
+                }
+            }
+            sample_id += 1;
+            start_time += *sample_delta as u64;
+        }
+    }
+    nearest
+}
+
+/// Converts length prefixed NAL units as stored in MP4 samples into an Annex B byte stream.
+pub fn avcc_to_annex_b(sample: &[u8], nal_length_size: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if !(1..=4).contains(&nal_length_size) {
+        return Err(format!("Invalid NAL unit length size {}", nal_length_size).into());
+    }
+
+    let mut annex_b = Vec::with_capacity(sample.len() + 16);
+    let mut offset = 0;
+    while offset < sample.len() {
+        if offset + nal_length_size > sample.len() {
+            return Err("Truncated NAL unit length in sample".into());
+        }
+        let nal_length = sample[offset..offset + nal_length_size]
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+        offset += nal_length_size;
+        if offset + nal_length > sample.len() {
+            return Err("NAL unit exceeds sample size".into());
+        }
+        annex_b.extend_from_slice(&ANNEX_B_START_CODE);
+        annex_b.extend_from_slice(&sample[offset..offset + nal_length]);
+        offset += nal_length;
+    }
+    Ok(annex_b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,8 +409,11 @@ mod tests {
         let mp4_parser = Mp4Parser::new();
         let (container_meta, video_track, audio_track, subtitle_track) = 
             mp4_parser.parse_from_file("assets/nature2.mp4").unwrap();
-        assert_eq!(container_meta.file_size_in_kb, 5862561);
-        // assert_eq!(container_meta.duration,);
+        assert_eq!(container_meta.file_size_bytes, 5862561);
+        assert!(!container_meta.major_brand.is_empty());
+        assert!(!container_meta.compatible_brands.is_empty());
+        assert_eq!(container_meta.mime_type, "video/mp4");
+        assert!(container_meta.duration_in_ms > 0);
         let video_track_unwrapped = video_track.unwrap(); 
 
         // video
@@ -192,6 +422,8 @@ mod tests {
         assert_eq!(video_track_unwrapped.frame_rate, 25);
         assert_eq!(video_track_unwrapped.width, 1280);
         assert_eq!(video_track_unwrapped.height, 720);
+        assert!(video_track_unwrapped.timescale > 0);
+        assert!(video_track_unwrapped.duration_in_ms <= container_meta.duration_in_ms);
 
         // audio
         let audio_track_unwrapped = audio_track.unwrap(); 
@@ -202,4 +434,37 @@ mod tests {
         // subtitle
         assert_eq!(subtitle_track, None);
     }
+
+    #[test]
+    fn test_mime_type_from_brands() {
+        let compatible_brands = vec![Some(String::from("isom")), Some(String::from("mp42"))];
+        assert_eq!(mime_type_from_brands("mp42", &compatible_brands, true), "video/mp4");
+        assert_eq!(mime_type_from_brands("M4A ", &compatible_brands, false), "audio/mp4");
+        assert_eq!(mime_type_from_brands("isom", &compatible_brands, false), "audio/mp4");
+        assert_eq!(mime_type_from_brands("qt  ", &[Some(String::from("qt  "))], true), "video/quicktime");
+        assert_eq!(mime_type_from_brands("3gp5", &[], true), "video/3gpp");
+    }
+
+    #[test]
+    fn test_nearest_sync_sample() {
+        // 250 samples of 40 ms at a timescale of 1000 with a keyframe every 50 samples (2 s)
+        let sample_durations = vec![(250, 40)];
+        let sync_samples = vec![1, 51, 101, 151, 201];
+
+        assert_eq!(nearest_sync_sample(&sample_durations, Some(&sync_samples), 0), Some((1, 0)));
+        assert_eq!(nearest_sync_sample(&sample_durations, Some(&sync_samples), 2900), Some((51, 2000)));
+        assert_eq!(nearest_sync_sample(&sample_durations, Some(&sync_samples), 3100), Some((101, 4000)));
+        assert_eq!(nearest_sync_sample(&sample_durations, Some(&sync_samples), 60000), Some((201, 8000)));
+        assert_eq!(nearest_sync_sample(&sample_durations, None, 3110), Some((79, 3120)));
+        assert_eq!(nearest_sync_sample(&[], None, 3100), None);
+    }
+
+    #[test]
+    fn test_avcc_to_annex_b() {
+        let sample = vec![0x00, 0x00, 0x00, 0x02, 0x65, 0x88, 0x00, 0x00, 0x00, 0x01, 0x06];
+        let annex_b = avcc_to_annex_b(&sample, 4).unwrap();
+        assert_eq!(annex_b, vec![0x00, 0x00, 0x00, 0x01, 0x65, 0x88, 0x00, 0x00, 0x00, 0x01, 0x06]);
+        assert!(avcc_to_annex_b(&sample[..5], 4).is_err());
+        assert!(avcc_to_annex_b(&sample, 0).is_err());
+    }
 }
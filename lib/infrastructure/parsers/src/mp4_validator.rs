@@ -0,0 +1,348 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use tracing::warn;
+
+// scanned for signatures of other formats, e.g. PDF readers accept a header within the first 1024 bytes
+const HEAD_SCAN_SIZE: u64 = 1024;
+// ZIP readers locate the end of central directory record within the trailing 22 bytes plus a comment of up to 64 KiB
+const TAIL_SCAN_SIZE: u64 = 22 + 65535;
+// bounds the work spent on a single level of the box tree
+const MAX_BOX_COUNT: usize = 10_000;
+
+const HEAD_SIGNATURES: [(&[u8], &str); 7] = [
+    (b"%PDF-", "PDF"),
+    (b"PK\x03\x04", "ZIP"),
+    (b"<html", "HTML"),
+    (b"<script", "HTML"),
+    (b"<svg", "SVG"),
+    (b"<?xml", "XML"),
+    (b"<?php", "PHP"),
+];
+const ZIP_END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+
+/// Reason for rejecting a file before it is parsed, `code` identifies the check which failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mp4ValidationError {
+    pub code: &'static str,
+    pub reason: String,
+}
+
+impl Mp4ValidationError {
+    pub const UNREADABLE: &'static str = "unreadable";
+    pub const UNRECOGNIZED_FORMAT: &'static str = "unrecognized_format";
+    pub const MALFORMED_BOX_STRUCTURE: &'static str = "malformed_box_structure";
+    pub const MISSING_BOX: &'static str = "missing_box";
+    pub const TOO_MANY_TRACKS: &'static str = "too_many_tracks";
+    pub const POLYGLOT: &'static str = "polyglot";
+
+    fn new(code: &'static str, reason: String) -> Self {
+        Mp4ValidationError { code, reason }
+    }
+}
+
+impl fmt::Display for Mp4ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for Mp4ValidationError {}
+
+impl From<std::io::Error> for Mp4ValidationError {
+    fn from(err: std::io::Error) -> Self {
+        Mp4ValidationError::new(Mp4ValidationError::UNREADABLE, format!("Failed to read the file: {}", err))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BoxHeader {
+    pub(crate) box_type: [u8; 4],
+    pub(crate) offset: u64,
+    pub(crate) header_size: u64,
+    pub(crate) size: u64,
+}
+
+impl BoxHeader {
+    pub(crate) fn name(&self) -> String {
+        String::from_utf8_lossy(&self.box_type).to_string()
+    }
+
+    pub(crate) fn content_offset(&self) -> u64 {
+        self.offset + self.header_size
+    }
+
+    pub(crate) fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+/// Sanity checks of the ISO base media file format structure, cheap enough to run on every upload
+/// before the file is stored or handed over to the parser.
+#[derive(Clone)]
+pub struct Mp4Validator {
+    pub max_track_count: usize,
+}
+
+impl Mp4Validator {
+    pub fn new() -> Self {
+        Mp4Validator { max_track_count: 16 }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn validate_file(&self, filename: &str) -> Result<(), Mp4ValidationError> {
+        let result = File::open(filename)
+            .and_then(|f| Ok((f.metadata()?.len(), f)))
+            .map_err(Mp4ValidationError::from)
+            .and_then(|(size, f)| self.validate(&mut BufReader::new(f), size));
+        if let Err(err) = &result {
+            metrics::counter!("mms_mp4_validation_failures_total", "code" => err.code).increment(1);
+            warn!(code = err.code, reason = %err.reason, "Rejected MP4");
+        }
+        result
+    }
+
+    /// Checks the magic bytes, that boxes tile the file and their parents exactly, the presence of the boxes
+    /// required for parsing, the number of tracks and signatures of other formats hidden in the file.
+    pub fn validate<R: Read + Seek>(&self, reader: &mut R, size: u64) -> Result<(), Mp4ValidationError> {
+        let head = read_at(reader, 0, size.min(HEAD_SCAN_SIZE))?;
+        if head.len() < 16 || &head[4..8] != b"ftyp" {
+            return Err(Mp4ValidationError::new(
+                Mp4ValidationError::UNRECOGNIZED_FORMAT,
+                String::from("The file does not start with an ftyp box"),
+            ));
+        }
+        check_signatures(&head)?;
+        let tail_size = size.min(TAIL_SCAN_SIZE);
+        let tail = read_at(reader, size - tail_size, tail_size)?;
+        if contains(&tail, ZIP_END_OF_CENTRAL_DIRECTORY) {
+            return Err(Mp4ValidationError::new(
+                Mp4ValidationError::POLYGLOT,
+                String::from("The file ends with a ZIP archive"),
+            ));
+        }
+
+        let boxes = read_boxes(reader, 0, size)?;
+        let moov = single_box(&boxes, b"moov", "the file")?;
+        single_box(&boxes, b"ftyp", "the file")?;
+        let moov_boxes = read_boxes(reader, moov.content_offset(), moov.end())?;
+        single_box(&moov_boxes, b"mvhd", "moov")?;
+        let traks: Vec<&BoxHeader> = moov_boxes.iter().filter(|b| &b.box_type == b"trak").collect();
+        if traks.is_empty() {
+            return Err(Mp4ValidationError::new(
+                Mp4ValidationError::MISSING_BOX,
+                String::from("The moov box contains no trak box"),
+            ));
+        }
+        if traks.len() > self.max_track_count {
+            return Err(Mp4ValidationError::new(
+                Mp4ValidationError::TOO_MANY_TRACKS,
+                format!("The file has {} tracks, at most {} are accepted", traks.len(), self.max_track_count),
+            ));
+        }
+        for trak in traks {
+            let trak_boxes = read_boxes(reader, trak.content_offset(), trak.end())?;
+            single_box(&trak_boxes, b"tkhd", "trak")?;
+            single_box(&trak_boxes, b"mdia", "trak")?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Mp4Validator {
+    fn default() -> Self {
+        Mp4Validator::new()
+    }
+}
+
+pub(crate) fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, len: u64) -> Result<Vec<u8>, Mp4ValidationError> {
+    let mut buffer = vec![0u8; len as usize];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+fn check_signatures(head: &[u8]) -> Result<(), Mp4ValidationError> {
+    match HEAD_SIGNATURES.iter().find(|(signature, _)| contains(head, signature)) {
+        Some((_, format)) => Err(Mp4ValidationError::new(
+            Mp4ValidationError::POLYGLOT,
+            format!("The file contains a {} header", format),
+        )),
+        None => Ok(()),
+    }
+}
+
+// reads the headers of the boxes between `start` and `end`, which must be covered exactly
+pub(crate) fn read_boxes<R: Read + Seek>(reader: &mut R, start: u64, end: u64) -> Result<Vec<BoxHeader>, Mp4ValidationError> {
+    let mut boxes = Vec::new();
+    let mut offset = start;
+    while offset < end {
+        if boxes.len() == MAX_BOX_COUNT {
+            return Err(malformed(format!("More than {} boxes at offset {}", MAX_BOX_COUNT, start)));
+        }
+        if end - offset < 8 {
+            return Err(malformed(format!("{} trailing bytes at offset {}", end - offset, offset)));
+        }
+        let header = read_at(reader, offset, 8)?;
+        let box_type = [header[4], header[5], header[6], header[7]];
+        let (size, header_size) = match u32::from_be_bytes([header[0], header[1], header[2], header[3]]) {
+            // extends to the end of the parent
+            0 => (end - offset, 8),
+            1 => {
+                if end - offset < 16 {
+                    return Err(malformed(format!("Truncated box header at offset {}", offset)));
+                }
+                let largesize = read_at(reader, offset + 8, 8)?;
+                (u64::from_be_bytes(largesize.try_into().unwrap()), 16)
+            }
+            size => (size as u64, 8),
+        };
+        let box_header = BoxHeader { box_type, offset, header_size, size };
+        if !box_type.iter().all(|c| (0x20..=0x7e).contains(c) || *c == 0xa9) {
+            return Err(malformed(format!("Invalid box type {:?} at offset {}", box_header.name(), offset)));
+        }
+        if size < header_size || size > end - offset {
+            return Err(malformed(format!(
+                "The {} box at offset {} has a size of {} bytes exceeding its parent", box_header.name(), offset, size)));
+        }
+        offset += size;
+        boxes.push(box_header);
+    }
+    Ok(boxes)
+}
+
+fn single_box<'a>(boxes: &'a [BoxHeader], box_type: &[u8; 4], parent: &str) -> Result<&'a BoxHeader, Mp4ValidationError> {
+    let mut matching = boxes.iter().filter(|b| &b.box_type == box_type);
+    let name = String::from_utf8_lossy(box_type);
+    match (matching.next(), matching.next()) {
+        (Some(found), None) => Ok(found),
+        (None, _) => Err(Mp4ValidationError::new(
+            Mp4ValidationError::MISSING_BOX,
+            format!("{} contains no {} box", capitalize(parent), name),
+        )),
+        (Some(_), Some(_)) => Err(malformed(format!("{} contains more than one {} box", capitalize(parent), name))),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn malformed(reason: String) -> Mp4ValidationError {
+    Mp4ValidationError::new(Mp4ValidationError::MALFORMED_BOX_STRUCTURE, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_boxes::mp4_box;
+    use std::io::Cursor;
+
+    fn trak() -> Vec<u8> {
+        [mp4_box(b"tkhd", &[0; 84]), mp4_box(b"mdia", &[0; 32])].concat()
+    }
+
+    fn mp4(track_count: usize, mdat: &[u8]) -> Vec<u8> {
+        let mut moov = mp4_box(b"mvhd", &[0; 100]);
+        for _ in 0..track_count {
+            moov.extend(mp4_box(b"trak", &trak()));
+        }
+        [mp4_box(b"ftyp", b"isom\0\0\x02\0isomiso2avc1mp41"), mp4_box(b"moov", &moov), mp4_box(b"mdat", mdat)].concat()
+    }
+
+    fn validate(bytes: &[u8]) -> Result<(), Mp4ValidationError> {
+        Mp4Validator::new().validate(&mut Cursor::new(bytes), bytes.len() as u64)
+    }
+
+    fn code_of(bytes: &[u8]) -> &'static str {
+        validate(bytes).unwrap_err().code
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_mp4_validator() {
+        assert_eq!(validate(&mp4(2, &[7; 256])), Ok(()));
+
+        // A last box of size 0 extends to the end of the file, 64-bit sizes are followed
+        let mut open_ended = mp4(1, &[]);
+        let mdat_offset = open_ended.len() - 8;
+        open_ended[mdat_offset..mdat_offset + 4].copy_from_slice(&[0; 4]);
+        open_ended.extend([7; 64]);
+        assert_eq!(validate(&open_ended), Ok(()));
+        let mut largesize = mp4(1, &[]);
+        largesize.truncate(largesize.len() - 8);
+        largesize.extend(1u32.to_be_bytes());
+        largesize.extend(b"mdat");
+        largesize.extend(80u64.to_be_bytes());
+        largesize.extend([7; 64]);
+        assert_eq!(validate(&largesize), Ok(()));
+
+        // Magic bytes
+        assert_eq!(code_of(b"GIF89a0123456789abcdef"), Mp4ValidationError::UNRECOGNIZED_FORMAT);
+        assert_eq!(code_of(&mp4(1, &[])[..12]), Mp4ValidationError::UNRECOGNIZED_FORMAT);
+
+        // Box structure
+        let mut truncated = mp4(1, &[7; 256]);
+        truncated.truncate(truncated.len() - 10);
+        assert_eq!(code_of(&truncated), Mp4ValidationError::MALFORMED_BOX_STRUCTURE);
+        let mut trailing = mp4(1, &[7; 256]);
+        trailing.extend([0; 4]);
+        assert_eq!(code_of(&trailing), Mp4ValidationError::MALFORMED_BOX_STRUCTURE);
+        let mut invalid_type = mp4(1, &[]);
+        let mdat_offset = invalid_type.len() - 4;
+        invalid_type[mdat_offset] = 0;
+        assert_eq!(code_of(&invalid_type), Mp4ValidationError::MALFORMED_BOX_STRUCTURE);
+        let without_moov = [mp4_box(b"ftyp", b"isom\0\0\x02\0"), mp4_box(b"mdat", &[7; 16])].concat();
+        assert_eq!(validate(&without_moov).unwrap_err().reason, "The file contains no moov box");
+        assert_eq!(code_of(&mp4(0, &[])), Mp4ValidationError::MISSING_BOX);
+        let incomplete_trak = [
+            mp4_box(b"ftyp", b"isom\0\0\x02\0"),
+            mp4_box(b"moov", &[mp4_box(b"mvhd", &[0; 100]), mp4_box(b"trak", &mp4_box(b"tkhd", &[0; 84]))].concat()),
+        ].concat();
+        assert_eq!(validate(&incomplete_trak).unwrap_err().reason, "Trak contains no mdia box");
+        assert_eq!(code_of(&mp4(17, &[])), Mp4ValidationError::TOO_MANY_TRACKS);
+
+        // Polyglots
+        let mut pdf = mp4(1, &[]);
+        pdf.extend(mp4_box(b"free", b"%PDF-1.7"));
+        assert_eq!(code_of(&pdf), Mp4ValidationError::POLYGLOT);
+        assert_eq!(code_of(&mp4(1, b"<HTML><body>")), Mp4ValidationError::POLYGLOT);
+        let mut zip = vec![7; 2048];
+        zip.extend(b"PK\x05\x06");
+        zip.extend([0; 18]);
+        assert_eq!(code_of(&mp4(1, &zip)), Mp4ValidationError::POLYGLOT);
+    }
+}
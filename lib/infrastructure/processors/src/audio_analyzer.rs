@@ -0,0 +1,308 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+use std::fs::File;
+
+use serde::Serialize;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::default::formats::IsoMp4Reader;
+use tracing::info;
+
+use crate::loudness_meter::{Loudness, LoudnessMeter};
+
+const MIN_SAMPLES_PER_PIXEL: u64 = 256;
+
+/// Raised for audio tracks without a pure Rust decoder, e.g. Opus which symphonia can demux but not decode.
+#[derive(Debug)]
+pub struct UnsupportedAudioCodecError {
+    pub track_id: u32,
+}
+
+impl fmt::Display for UnsupportedAudioCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Codec of audio track {} can not be decoded", self.track_id)
+    }
+}
+
+impl std::error::Error for UnsupportedAudioCodecError {}
+
+/// Downmixed min/max peaks in the JSON format of the BBC audiowaveform tool understood by player scrubbers like peaks.js.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Waveform {
+    pub version: u32,
+    pub channels: u32,
+    pub sample_rate: u32,
+    pub samples_per_pixel: u64,
+    pub bits: u32,
+    pub length: usize,
+    pub data: Vec<i8>, // min and max of every pixel, interleaved
+}
+
+impl Waveform {
+    pub fn to_json(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+pub struct WaveformBuilder {
+    channels: usize,
+    sample_rate: u32,
+    samples_per_pixel: u64,
+    position: u64,
+    min: f32,
+    max: f32,
+    data: Vec<i8>,
+}
+
+impl WaveformBuilder {
+    /// Chooses the resolution so that `frames` frames yield at most `max_pixels` pixels.
+    pub fn new(channels: usize, sample_rate: u32, frames: u64, max_pixels: usize) -> Self {
+        let samples_per_pixel = frames.div_ceil(max_pixels.max(1) as u64).max(MIN_SAMPLES_PER_PIXEL);
+        WaveformBuilder {
+            channels,
+            sample_rate,
+            samples_per_pixel,
+            position: 0,
+            min: 0.0,
+            max: 0.0,
+            data: Vec::new(),
+        }
+    }
+
+    /// Adds interleaved samples in the range -1.0..=1.0, channels are downmixed by averaging.
+    pub fn add_samples(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let sample = frame.iter().sum::<f32>() / self.channels as f32;
+            self.min = self.min.min(sample);
+            self.max = self.max.max(sample);
+            self.position += 1;
+            if self.position == self.samples_per_pixel {
+                self.push_pixel();
+            }
+        }
+    }
+
+    fn push_pixel(&mut self) {
+        let quantize = |sample: f32| (sample * 127.0).round().clamp(-128.0, 127.0) as i8;
+        self.data.push(quantize(self.min));
+        self.data.push(quantize(self.max));
+        self.position = 0;
+        self.min = 0.0;
+        self.max = 0.0;
+    }
+
+    pub fn finish(mut self) -> Waveform {
+        if self.position > 0 {
+            self.push_pixel();
+        }
+        Waveform {
+            version: 2,
+            channels: 1,
+            sample_rate: self.sample_rate,
+            samples_per_pixel: self.samples_per_pixel,
+            bits: 8,
+            length: self.data.len() / 2,
+            data: self.data,
+        }
+    }
+}
+
+pub struct AudioAnalysis {
+    pub loudness: Loudness,
+    pub waveform: Waveform,
+}
+
+#[derive(Clone)]
+pub struct AudioAnalyzer {}
+
+impl AudioAnalyzer {
+    pub fn new() -> Self {
+        AudioAnalyzer {}
+    }
+
+    /// Decodes the first audio track of an MP4 file in a single pass, only AAC is supported by the bundled decoders.
+    #[tracing::instrument(skip(self))]
+    pub fn analyze_file(&self, filename: &str, waveform_pixels: usize) -> Result<AudioAnalysis, Box<dyn std::error::Error>> {
+        let media_source_stream = MediaSourceStream::new(Box::new(File::open(filename)?), Default::default());
+        let mut reader = IsoMp4Reader::try_new(media_source_stream, &FormatOptions::default())?;
+        // video and subtitle tracks lack a sample rate
+        let track = reader.tracks().iter()
+            .filter(|track| track.codec_params.sample_rate.is_some())
+            .min_by_key(|track| track.id)
+            .ok_or_else(|| format!("No audio track found in {}", filename))?;
+        let track_id = track.id;
+        if track.codec_params.codec == CODEC_TYPE_NULL {
+            return Err(Box::new(UnsupportedAudioCodecError { track_id }));
+        }
+        let mut decoder = match symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default()) {
+            Ok(decoder) => decoder,
+            Err(SymphoniaError::Unsupported(_)) => return Err(Box::new(UnsupportedAudioCodecError { track_id })),
+            Err(err) => return Err(Box::new(err)),
+        };
+        let channels = track.codec_params.channels.map(|channels| channels.count()).unwrap_or(0);
+        let sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+        if channels == 0 || sample_rate == 0 {
+            return Err(format!("Audio track {} lacks channel layout or sample rate", track_id).into());
+        }
+        let mut loudness_meter = LoudnessMeter::new(channels, sample_rate);
+        let mut waveform_builder = WaveformBuilder::new(
+            channels, sample_rate, track.codec_params.n_frames.unwrap_or(0), waveform_pixels);
+
+        let mut sample_buffer: Option<SampleBuffer<f32>> = None;
+        loop {
+            let packet = match reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(Box::new(err)),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            let decoded = match decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // a corrupt packet is skipped instead of failing the whole track
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(err) => return Err(Box::new(err)),
+            };
+            if sample_buffer.as_ref().is_none_or(|buffer| buffer.capacity() < decoded.capacity() * channels) {
+                sample_buffer = Some(SampleBuffer::new(decoded.capacity() as u64, *decoded.spec()));
+            }
+            let buffer = sample_buffer.as_mut().unwrap();
+            buffer.copy_interleaved_ref(decoded);
+            loudness_meter.add_samples(buffer.samples());
+            waveform_builder.add_samples(buffer.samples());
+        }
+
+        let audio_analysis = AudioAnalysis {
+            loudness: loudness_meter.finish(),
+            waveform: waveform_builder.finish(),
+        };
+        info!("Successfully analyzed audio track {} of {}: {:?}", track_id, filename, audio_analysis.loudness);
+        Ok(audio_analysis)
+    }
+}
+
+impl Default for AudioAnalyzer {
+    fn default() -> Self {
+        AudioAnalyzer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_audio_analyzer() {
+        // 1000 stereo frames rising from silence to full scale with 256 frames per pixel
+        let mut waveform_builder = WaveformBuilder::new(2, 48000, 1000, 2);
+        let samples: Vec<f32> = (0..1000).flat_map(|n| [n as f32 / 999.0, -(n as f32) / 999.0]).collect();
+        waveform_builder.add_samples(&samples[..1000]);
+        waveform_builder.add_samples(&samples[1000..]);
+        let waveform = waveform_builder.finish();
+        assert_eq!(waveform.samples_per_pixel, 500);
+        assert_eq!(waveform.length, 2);
+        assert_eq!(waveform.data, vec![0, 0, 0, 0]);
+
+        let mut waveform_builder = WaveformBuilder::new(1, 48000, 1000, 1000);
+        let samples: Vec<f32> = (0..1000).map(|n| if n % 2 == 0 { 1.0 } else { -0.5 }).collect();
+        waveform_builder.add_samples(&samples);
+        let waveform = waveform_builder.finish();
+        assert_eq!(waveform.samples_per_pixel, 256);
+        assert_eq!(waveform.length, 4);
+        assert_eq!(&waveform.data[..2], &[-64, 127]);
+        let json = waveform.to_json().unwrap();
+        assert!(json.starts_with("{\"version\":2,\"channels\":1,\"sample_rate\":48000,\"samples_per_pixel\":256,\"bits\":8,\"length\":4,\"data\":[-64,127"));
+
+        let audio_analyzer = AudioAnalyzer::new();
+        assert!(audio_analyzer.analyze_file("assets/does-not-exist.mp4", 1000).is_err());
+
+        // symphonia demuxes Opus tracks but has no decoder for them
+        let file_name = std::env::temp_dir().join(format!("audio-analyzer-opus-{}.mp4", std::process::id()));
+        std::fs::write(&file_name, opus_mp4()).unwrap();
+        let result = audio_analyzer.analyze_file(file_name.to_str().unwrap(), 1000);
+        std::fs::remove_file(&file_name).unwrap();
+        match result {
+            Err(err) => assert!(err.is::<UnsupportedAudioCodecError>(), "{}", err),
+            Ok(_) => panic!("Opus tracks must not be analyzed"),
+        }
+    }
+
+    fn mp4_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut mp4_box = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        mp4_box.extend_from_slice(box_type);
+        mp4_box.extend_from_slice(payload);
+        mp4_box
+    }
+
+    fn full_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        mp4_box(box_type, &[&[0u8; 4][..], payload].concat())
+    }
+
+    /// An MP4 with a single Opus track without samples.
+    fn opus_mp4() -> Vec<u8> {
+        let matrix: Vec<u8> = [0x0001_0000u32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000]
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        let mvhd = full_box(b"mvhd", &[
+            &[0u8; 8][..], &1000u32.to_be_bytes(), &0u32.to_be_bytes(), &0x0001_0000u32.to_be_bytes(),
+            &0x0100u16.to_be_bytes(), &[0u8; 10], &matrix, &[0u8; 24], &2u32.to_be_bytes(),
+        ].concat());
+        let tkhd = full_box(b"tkhd", &[
+            &[0u8; 8][..], &1u32.to_be_bytes(), &[0u8; 4], &0u32.to_be_bytes(), &[0u8; 8], &[0u8; 4],
+            &0x0100u16.to_be_bytes(), &[0u8; 2], &matrix, &[0u8; 8],
+        ].concat());
+        let mdhd = full_box(b"mdhd", &[&[0u8; 8][..], &48000u32.to_be_bytes(), &0u32.to_be_bytes(), &[0x55, 0xc4, 0, 0]].concat());
+        let hdlr = full_box(b"hdlr", &[&[0u8; 4][..], b"soun", &[0u8; 12], b"\0"].concat());
+        // version, output channel count, pre-skip, input sample rate, output gain and channel mapping family
+        let d_ops = mp4_box(b"dOps", &[&[0u8, 2][..], &312u16.to_be_bytes(), &48000u32.to_be_bytes(), &[0u8; 2], &[0u8]].concat());
+        let opus = mp4_box(b"Opus", &[
+            &[0u8; 6][..], &1u16.to_be_bytes(), &[0u8; 8], &2u16.to_be_bytes(), &16u16.to_be_bytes(), &[0u8; 4],
+            &(48000u32 << 16).to_be_bytes(), &d_ops,
+        ].concat());
+        let stbl = mp4_box(b"stbl", &[
+            full_box(b"stsd", &[&1u32.to_be_bytes()[..], &opus].concat()),
+            full_box(b"stts", &0u32.to_be_bytes()),
+            full_box(b"stsc", &0u32.to_be_bytes()),
+            full_box(b"stsz", &[0u8; 8]),
+            full_box(b"stco", &0u32.to_be_bytes()),
+        ].concat());
+        let dinf = mp4_box(b"dinf", &full_box(b"dref", &[&1u32.to_be_bytes()[..], &mp4_box(b"url ", &[0, 0, 0, 1])].concat()));
+        let minf = mp4_box(b"minf", &[full_box(b"smhd", &[0u8; 4]), dinf, stbl].concat());
+        let mdia = mp4_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = mp4_box(b"trak", &[tkhd, mdia].concat());
+        [
+            mp4_box(b"ftyp", &[&b"isom"[..], &0u32.to_be_bytes(), b"isomiso2mp41"].concat()),
+            mp4_box(b"moov", &[mvhd, trak].concat()),
+            mp4_box(b"mdat", &[]),
+        ].concat()
+    }
+}
@@ -0,0 +1,109 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use image::RgbImage;
+use tracing::info;
+use parsers::mp4_parser::Keyframe;
+
+pub trait KeyframeDecoder: Send + Sync {
+    fn decode(&self, keyframe: &Keyframe) -> Result<RgbImage, Box<dyn std::error::Error>>;
+}
+
+/// Decodes keyframes by piping the Annex B byte stream through a decoder program accepting ffmpeg
+/// command line arguments. The program is not shipped with the service and must be provided by the deployment.
+#[derive(Clone)]
+pub struct CommandKeyframeDecoder {
+    program: String,
+}
+
+impl CommandKeyframeDecoder {
+    pub fn with_program(program: &str) -> Self {
+        CommandKeyframeDecoder {
+            program: String::from(program),
+        }
+    }
+}
+
+impl KeyframeDecoder for CommandKeyframeDecoder {
+    fn decode(&self, keyframe: &Keyframe) -> Result<RgbImage, Box<dyn std::error::Error>> {
+        let mut child = Command::new(&self.program)
+            .args([
+                "-hide_banner", "-loglevel", "error",
+                "-f", "h264", "-i", "pipe:0",
+                "-frames:v", "1",
+                "-f", "rawvideo", "-pix_fmt", "rgb24", "pipe:1",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // feed stdin from a separate thread so a full stdout pipe can not dead lock the decoder
+        let mut stdin = child.stdin.take().ok_or("Failed to open stdin of keyframe decoder")?;
+        let bytes = keyframe.bytes.clone();
+        let writer = thread::spawn(move || stdin.write_all(&bytes));
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+
+        if !output.status.success() {
+            return Err(format!(
+                "Keyframe decoder {} failed: {}",
+                self.program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        let image = RgbImage::from_raw(keyframe.width as u32, keyframe.height as u32, output.stdout)
+            .ok_or("Decoded frame does not match the dimensions of the video track")?;
+        info!(
+            "Successfully decoded keyframe #{} of track #{} at {} ms",
+            keyframe.sample_id, keyframe.track_id, keyframe.time_in_ms
+        );
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_command_keyframe_decoder_with_missing_program() {
+        let keyframe_decoder = CommandKeyframeDecoder::with_program("./non-existing-keyframe-decoder");
+        let keyframe = Keyframe {
+            track_id: 1,
+            sample_id: 1,
+            time_in_ms: 0,
+            width: 2,
+            height: 2,
+            bytes: vec![0x00, 0x00, 0x00, 0x01, 0x65],
+        };
+        assert!(keyframe_decoder.decode(&keyframe).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::f64::consts::PI;
+
+// loudness of a block whose channels are all silent
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const INTEGRATED_RELATIVE_GATE_LU: f64 = -10.0;
+const RANGE_RELATIVE_GATE_LU: f64 = -20.0;
+// energy is accumulated in 100 ms steps, momentary blocks span 4 and short-term blocks 30 of them
+const STEPS_PER_SECOND: usize = 10;
+const STEPS_PER_MOMENTARY_BLOCK: usize = 4;
+const STEPS_PER_SHORT_TERM_BLOCK: usize = 30;
+// true peak is measured on the signal upsampled by this factor
+const OVERSAMPLING: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+/// Loudness according to ITU-R BS.1770-4 and EBU R128 / Tech 3342.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Loudness {
+    // `None` if the programme is silent, i.e. no block passes the absolute gate
+    pub integrated_loudness_lufs: Option<f64>,
+    pub loudness_range_lu: f64,
+    pub true_peak_dbtp: f64,
+}
+
+// second order IIR filter in transposed direct form II
+#[derive(Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[1] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[2] * y;
+        y
+    }
+}
+
+// K-weighting, a high shelf modelling the head followed by a high pass, for any sample rate
+fn k_weighting(sample_rate: u32) -> (Biquad, Biquad) {
+    let sample_rate = sample_rate as f64;
+
+    let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+    (shelf, high_pass)
+}
+
+// channel weights of BS.1770 assuming the usual order L, R, C, LFE, Ls, Rs
+fn channel_weight(channels: usize, channel: usize) -> f64 {
+    match (channels, channel) {
+        (6.., 3) => 0.0,
+        (5.., 4 | 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+// windowed sinc low pass interpolating between the original samples
+fn oversampling_filter() -> Vec<f64> {
+    let taps = OVERSAMPLING * TAPS_PER_PHASE;
+    let center = (taps - 1) as f64 / 2.0;
+    (0..taps)
+        .map(|n| {
+            let t = (n as f64 - center) / OVERSAMPLING as f64;
+            let sinc = if t == 0.0 { 1.0 } else { (PI * t).sin() / (PI * t) };
+            // Blackman window
+            let w = 0.42 - 0.5 * (2.0 * PI * n as f64 / (taps - 1) as f64).cos()
+                + 0.08 * (4.0 * PI * n as f64 / (taps - 1) as f64).cos();
+            sinc * w
+        })
+        .collect()
+}
+
+fn loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// Measures integrated loudness, loudness range and true peak of interleaved samples in a single pass.
+pub struct LoudnessMeter {
+    channels: usize,
+    samples_per_step: usize,
+    filters: Vec<(Biquad, Biquad)>,
+    // weighted mean square of every completed 100 ms step
+    step_energies: Vec<f64>,
+    step_sums: Vec<f64>,
+    step_position: usize,
+    oversampling_filter: Vec<f64>,
+    histories: Vec<Vec<f64>>,
+    true_peak: f64,
+}
+
+impl LoudnessMeter {
+    pub fn new(channels: usize, sample_rate: u32) -> Self {
+        LoudnessMeter {
+            channels,
+            samples_per_step: (sample_rate as usize / STEPS_PER_SECOND).max(1),
+            filters: (0..channels).map(|_| k_weighting(sample_rate)).collect(),
+            step_energies: Vec::new(),
+            step_sums: vec![0.0; channels],
+            step_position: 0,
+            oversampling_filter: oversampling_filter(),
+            histories: vec![vec![0.0; TAPS_PER_PHASE]; channels],
+            true_peak: 0.0,
+        }
+    }
+
+    /// Adds interleaved samples in the range -1.0..=1.0, a trailing incomplete frame is ignored.
+    pub fn add_samples(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                let sample = *sample as f64;
+                let (shelf, high_pass) = &mut self.filters[channel];
+                let weighted = high_pass.process(shelf.process(sample));
+                self.step_sums[channel] += weighted * weighted;
+                self.measure_true_peak(channel, sample);
+            }
+            self.step_position += 1;
+            if self.step_position == self.samples_per_step {
+                let energy = self.step_sums.iter().enumerate()
+                    .map(|(channel, sum)| channel_weight(self.channels, channel) * sum / self.samples_per_step as f64)
+                    .sum();
+                self.step_energies.push(energy);
+                self.step_sums.iter_mut().for_each(|sum| *sum = 0.0);
+                self.step_position = 0;
+            }
+        }
+    }
+
+    fn measure_true_peak(&mut self, channel: usize, sample: f64) {
+        let history = &mut self.histories[channel];
+        history.rotate_right(1);
+        history[0] = sample;
+        for phase in 0..OVERSAMPLING {
+            let interpolated: f64 = history.iter().enumerate()
+                .map(|(tap, x)| self.oversampling_filter[phase + tap * OVERSAMPLING] * x)
+                .sum();
+            self.true_peak = self.true_peak.max(interpolated.abs());
+        }
+        self.true_peak = self.true_peak.max(sample.abs());
+    }
+
+    // mean energy of every block spanning `steps` steps, advancing by one step
+    fn block_energies(&self, steps: usize) -> Vec<f64> {
+        if self.step_energies.len() < steps {
+            return Vec::new();
+        }
+        self.step_energies.windows(steps)
+            .map(|window| window.iter().sum::<f64>() / steps as f64)
+            .collect()
+    }
+
+    pub fn finish(&self) -> Loudness {
+        // integrated loudness, gated absolutely and then relative to the absolutely gated mean
+        let momentary_energies: Vec<f64> = self.block_energies(STEPS_PER_MOMENTARY_BLOCK).into_iter()
+            .filter(|energy| loudness(*energy) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        let integrated_loudness_lufs = match momentary_energies.is_empty() {
+            true => None,
+            false => {
+                let mean_energy = momentary_energies.iter().sum::<f64>() / momentary_energies.len() as f64;
+                let relative_gate = loudness(mean_energy) + INTEGRATED_RELATIVE_GATE_LU;
+                let gated: Vec<f64> = momentary_energies.into_iter()
+                    .filter(|energy| loudness(*energy) > relative_gate)
+                    .collect();
+                Some(loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+            }
+        };
+
+        // loudness range, the spread between the 10th and 95th percentile of gated short-term loudness
+        let short_term_energies: Vec<f64> = self.block_energies(STEPS_PER_SHORT_TERM_BLOCK).into_iter()
+            .filter(|energy| loudness(*energy) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        let loudness_range_lu = match short_term_energies.is_empty() {
+            true => 0.0,
+            false => {
+                let mean_energy = short_term_energies.iter().sum::<f64>() / short_term_energies.len() as f64;
+                let relative_gate = loudness(mean_energy) + RANGE_RELATIVE_GATE_LU;
+                let mut short_term_loudness: Vec<f64> = short_term_energies.into_iter()
+                    .map(loudness)
+                    .filter(|short_term_loudness| *short_term_loudness > relative_gate)
+                    .collect();
+                short_term_loudness.sort_by(|a, b| a.total_cmp(b));
+                let percentile = |p: f64| short_term_loudness[((short_term_loudness.len() - 1) as f64 * p).round() as usize];
+                percentile(0.95) - percentile(0.10)
+            }
+        };
+
+        Loudness {
+            integrated_loudness_lufs,
+            loudness_range_lu,
+            true_peak_dbtp: 20.0 * self.true_peak.max(1e-10).log10(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amplitude: f64, frequency: f64, sample_rate: u32, seconds: f64, channels: usize) -> Vec<f32> {
+        (0..(sample_rate as f64 * seconds) as usize)
+            .flat_map(|n| {
+                let sample = (amplitude * (2.0 * PI * frequency * n as f64 / sample_rate as f64).sin()) as f32;
+                std::iter::repeat_n(sample, channels)
+            })
+            .collect()
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_loudness_meter() {
+        // a 1 kHz sine at -20 dBFS on a single channel measures -23 LUFS, twice the energy on two channels
+        let mut loudness_meter = LoudnessMeter::new(1, 48000);
+        loudness_meter.add_samples(&sine(0.1, 1000.0, 48000, 10.0, 1));
+        let loudness = loudness_meter.finish();
+        assert!((loudness.integrated_loudness_lufs.unwrap() + 23.01).abs() < 0.1, "{:?}", loudness);
+        assert!((loudness.true_peak_dbtp + 20.0).abs() < 0.1, "{:?}", loudness);
+        assert!(loudness.loudness_range_lu.abs() < 0.1, "{:?}", loudness);
+
+        let mut loudness_meter = LoudnessMeter::new(2, 44100);
+        loudness_meter.add_samples(&sine(0.1, 1000.0, 44100, 10.0, 2));
+        let loudness = loudness_meter.finish();
+        assert!((loudness.integrated_loudness_lufs.unwrap() + 20.0).abs() < 0.1, "{:?}", loudness);
+
+        // silence is gated, so it neither lowers the integrated loudness nor widens the range
+        let mut loudness_meter = LoudnessMeter::new(1, 48000);
+        loudness_meter.add_samples(&vec![0.0; 48000 * 10]);
+        loudness_meter.add_samples(&sine(0.1, 1000.0, 48000, 10.0, 1));
+        let loudness = loudness_meter.finish();
+        assert!((loudness.integrated_loudness_lufs.unwrap() + 23.01).abs() < 0.2, "{:?}", loudness);
+
+        // two levels 10 dB apart span a loudness range of about 10 LU
+        let mut loudness_meter = LoudnessMeter::new(1, 48000);
+        loudness_meter.add_samples(&sine(0.1, 1000.0, 48000, 20.0, 1));
+        loudness_meter.add_samples(&sine(0.0316, 1000.0, 48000, 20.0, 1));
+        let loudness = loudness_meter.finish();
+        assert!((loudness.loudness_range_lu - 10.0).abs() < 0.5, "{:?}", loudness);
+
+        // inter-sample peaks exceed the sample peak of a sine at a quarter of the sample rate shifted by 45 degrees
+        let mut loudness_meter = LoudnessMeter::new(1, 48000);
+        let samples: Vec<f32> = (0..48000).map(|n| (0.5 * (PI / 2.0 * n as f64 + PI / 4.0).sin()) as f32).collect();
+        loudness_meter.add_samples(&samples);
+        let loudness = loudness_meter.finish();
+        assert!(loudness.true_peak_dbtp > 20.0 * (0.5f64 / 2f64.sqrt()).log10() + 2.0, "{:?}", loudness);
+
+        let loudness = LoudnessMeter::new(2, 48000).finish();
+        assert_eq!(loudness.integrated_loudness_lufs, None);
+    }
+}
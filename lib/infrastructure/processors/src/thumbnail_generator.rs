@@ -0,0 +1,229 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+use std::sync::Arc;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::{self, FilterType};
+use image::{ExtendedColorType, RgbImage};
+use tracing::info;
+use parsers::mp4_parser::Mp4Parser;
+
+use crate::keyframe_decoder::KeyframeDecoder;
+
+const JPEG_QUALITY: u8 = 85;
+
+pub struct Poster {
+    pub time_in_ms: u64,
+    pub width: u32,
+    pub height: u32,
+    pub jpeg_bytes: Vec<u8>,
+    pub webp_bytes: Vec<u8>,
+}
+
+pub struct SpriteSheet {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub width: u32,
+    pub height: u32,
+    pub jpeg_bytes: Vec<u8>,
+    pub web_vtt: String, // WebVTT thumbnail track referencing tiles of the sprite sheet trough `#xywh=` fragments
+}
+
+#[derive(Clone)]
+pub struct ThumbnailGenerator {
+    mp4_parser: Mp4Parser,
+    keyframe_decoder: Arc<dyn KeyframeDecoder>,
+}
+
+impl ThumbnailGenerator {
+    pub fn new(keyframe_decoder: Arc<dyn KeyframeDecoder>) -> Self {
+        ThumbnailGenerator {
+            mp4_parser: Mp4Parser::new(),
+            keyframe_decoder,
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn generate_poster(&self, filename: &str, time_in_ms: u64) -> Result<Poster, Box<dyn std::error::Error>> {
+        let keyframe = self.mp4_parser.read_keyframe(filename, time_in_ms)?;
+        let image = self.keyframe_decoder.decode(&keyframe)?;
+
+        let poster = Poster {
+            time_in_ms: keyframe.time_in_ms,
+            width: image.width(),
+            height: image.height(),
+            jpeg_bytes: encode_jpeg(&image)?,
+            webp_bytes: encode_webp(&image)?,
+        };
+        info!("Successfully generated poster at {} ms from {}", poster.time_in_ms, filename);
+        Ok(poster)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn generate_sprite_sheet(
+        &self,
+        filename: &str,
+        duration_in_ms: u64,
+        interval_in_ms: u64,
+        columns: u32,
+        tile_width: u32,
+        sprite_name: &str,
+    ) -> Result<SpriteSheet, Box<dyn std::error::Error>> {
+        if interval_in_ms == 0 {
+            return Err("Sprite sheet interval must be greater than 0".into());
+        }
+        let times_in_ms: Vec<u64> = (0..duration_in_ms.max(1)).step_by(interval_in_ms as usize).collect();
+        let keyframes = self.mp4_parser.read_keyframes(filename, &times_in_ms)?;
+
+        // neighbouring cues often map to the same keyframe, which then only needs to be decoded once
+        let mut frames: Vec<RgbImage> = Vec::with_capacity(keyframes.len());
+        let mut previous_sample_id = None;
+        for keyframe in keyframes.iter() {
+            if previous_sample_id == Some(keyframe.sample_id) {
+                let previous_frame = frames.last().unwrap().clone();
+                frames.push(previous_frame);
+            } else {
+                frames.push(self.keyframe_decoder.decode(keyframe)?);
+                previous_sample_id = Some(keyframe.sample_id);
+            }
+        }
+
+        let sprite_sheet =
+            compose_sprite_sheet(&frames, duration_in_ms, interval_in_ms, columns, tile_width, sprite_name)?;
+        info!(
+            "Successfully generated sprite sheet with {} tiles from {}",
+            frames.len(), filename
+        );
+        Ok(sprite_sheet)
+    }
+}
+
+/// Widens `interval_in_ms` to whole seconds such that a sprite sheet of `duration_in_ms` has at most `max_frames` tiles,
+/// as every tile may need a keyframe to be decoded.
+pub fn sprite_interval_in_ms(duration_in_ms: u64, interval_in_ms: u64, max_frames: u32) -> u64 {
+    let min_interval_in_ms = duration_in_ms.div_ceil(max_frames.max(1) as u64).div_ceil(1000) * 1000;
+    interval_in_ms.max(min_interval_in_ms)
+}
+
+/// Places the frames row by row into a single image and creates the WebVTT cues pointing at each tile.
+pub fn compose_sprite_sheet(
+    frames: &[RgbImage],
+    duration_in_ms: u64,
+    interval_in_ms: u64,
+    columns: u32,
+    tile_width: u32,
+    sprite_name: &str,
+) -> Result<SpriteSheet, Box<dyn std::error::Error>> {
+    let first_frame = frames.first().ok_or("No frames to compose a sprite sheet from")?;
+    if columns == 0 || tile_width == 0 {
+        return Err("Sprite sheet columns and tile width must be greater than 0".into());
+    }
+    let tile_height = ((tile_width as u64 * first_frame.height() as u64) / first_frame.width().max(1) as u64).max(1) as u32;
+    let columns = columns.min(frames.len() as u32);
+    let rows = (frames.len() as u32).div_ceil(columns);
+
+    let mut sprite = RgbImage::new(columns * tile_width, rows * tile_height);
+    let mut web_vtt = String::from("WEBVTT\n");
+    for (index, frame) in frames.iter().enumerate() {
+        let x = (index as u32 % columns) * tile_width;
+        let y = (index as u32 / columns) * tile_height;
+        let tile = imageops::resize(frame, tile_width, tile_height, FilterType::Triangle);
+        imageops::replace(&mut sprite, &tile, x as i64, y as i64);
+
+        let start_in_ms = index as u64 * interval_in_ms;
+        let end_in_ms = (start_in_ms + interval_in_ms).min(duration_in_ms.max(start_in_ms + 1));
+        web_vtt.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            web_vtt_timestamp(start_in_ms),
+            web_vtt_timestamp(end_in_ms),
+            sprite_name,
+            x,
+            y,
+            tile_width,
+            tile_height
+        ));
+    }
+
+    Ok(SpriteSheet {
+        tile_width,
+        tile_height,
+        width: sprite.width(),
+        height: sprite.height(),
+        jpeg_bytes: encode_jpeg(&sprite)?,
+        web_vtt,
+    })
+}
+
+pub fn web_vtt_timestamp(time_in_ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        time_in_ms / 3_600_000,
+        (time_in_ms / 60_000) % 60,
+        (time_in_ms / 1000) % 60,
+        time_in_ms % 1000
+    )
+}
+
+fn encode_jpeg(image: &RgbImage) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY).encode_image(image)?;
+    Ok(bytes)
+}
+
+fn encode_webp(image: &RgbImage) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    WebPEncoder::new_lossless(&mut bytes).encode(image.as_raw(), image.width(), image.height(), ExtendedColorType::Rgb8)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_compose_sprite_sheet() {
+        let frames: Vec<RgbImage> = (0..5)
+            .map(|index| RgbImage::from_pixel(320, 180, image::Rgb([index * 50, 0, 0])))
+            .collect();
+
+        let sprite_sheet = compose_sprite_sheet(&frames, 45000, 10000, 3, 160, "sprite.jpg").unwrap();
+        assert_eq!(sprite_sheet.tile_width, 160);
+        assert_eq!(sprite_sheet.tile_height, 90);
+        assert_eq!(sprite_sheet.width, 480);
+        assert_eq!(sprite_sheet.height, 180);
+        assert!(sprite_sheet.jpeg_bytes.starts_with(&[0xFF, 0xD8]));
+        assert!(sprite_sheet.web_vtt.starts_with("WEBVTT\n"));
+        assert!(sprite_sheet.web_vtt.contains("00:00:00.000 --> 00:00:10.000\nsprite.jpg#xywh=0,0,160,90\n"));
+        assert!(sprite_sheet.web_vtt.contains("00:00:30.000 --> 00:00:40.000\nsprite.jpg#xywh=0,90,160,90\n"));
+        assert!(sprite_sheet.web_vtt.ends_with("00:00:40.000 --> 00:00:45.000\nsprite.jpg#xywh=160,90,160,90\n"));
+
+        assert!(compose_sprite_sheet(&[], 45000, 10000, 3, 160, "sprite.jpg").is_err());
+        assert_eq!(sprite_interval_in_ms(45000, 10000, 100), 10000);
+        assert_eq!(sprite_interval_in_ms(7_200_000, 10000, 100), 72000);
+        assert_eq!(sprite_interval_in_ms(7_200_500, 10000, 100), 73000);
+        assert_eq!(web_vtt_timestamp(3_723_004), "01:02:03.004");
+    }
+}
@@ -0,0 +1,280 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tracing::info;
+
+/// One step of the rendition ladder. The source is scaled to fit into `width` x `height` keeping its
+/// aspect ratio, sources smaller than the box are not upscaled.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RenditionProfile {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub video_bit_rate_kbps: u32,
+    pub audio_bit_rate_kbps: u32,
+}
+
+impl RenditionProfile {
+    pub fn new(name: &str, width: u32, height: u32, video_bit_rate_kbps: u32, audio_bit_rate_kbps: u32) -> Self {
+        RenditionProfile {
+            name: String::from(name),
+            width,
+            height,
+            video_bit_rate_kbps,
+            audio_bit_rate_kbps,
+        }
+    }
+
+    /// 1080p, 720p and 480p H.264 with AAC audio
+    pub fn default_ladder() -> Vec<Self> {
+        vec![
+            RenditionProfile::new("1080p", 1920, 1080, 5000, 128),
+            RenditionProfile::new("720p", 1280, 720, 2800, 128),
+            RenditionProfile::new("480p", 854, 480, 1400, 96),
+        ]
+    }
+}
+
+pub trait VideoEncoder: Send + Sync {
+    /// Encodes the source into an H.264/AAC MP4 file of exactly `width` x `height` pixels.
+    fn encode(
+        &self,
+        source_file_name: &str,
+        target_file_name: &str,
+        width: u32,
+        height: u32,
+        profile: &RenditionProfile,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Encodes renditions with a local encoder program which is expected to accept ffmpeg
+/// command line arguments (e.g. the bundled `ffmpeg` binary).
+#[derive(Clone)]
+pub struct CommandVideoEncoder {
+    program: String,
+}
+
+impl CommandVideoEncoder {
+    pub fn new() -> Self {
+        let program = std::env::var("VIDEO_ENCODER_PROGRAM").unwrap_or(String::from("ffmpeg"));
+        CommandVideoEncoder { program }
+    }
+
+    pub fn with_program(program: &str) -> Self {
+        CommandVideoEncoder {
+            program: String::from(program),
+        }
+    }
+}
+
+impl Default for CommandVideoEncoder {
+    fn default() -> Self {
+        CommandVideoEncoder::new()
+    }
+}
+
+impl VideoEncoder for CommandVideoEncoder {
+    fn encode(
+        &self,
+        source_file_name: &str,
+        target_file_name: &str,
+        width: u32,
+        height: u32,
+        profile: &RenditionProfile,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let output = Command::new(&self.program)
+            .args(["-hide_banner", "-loglevel", "error", "-y", "-i", source_file_name])
+            // the first video and, if present, the first audio track
+            .args(["-map", "0:v:0", "-map", "0:a:0?"])
+            .args(["-vf", &format!("scale={}:{}", width, height)])
+            .args(["-c:v", "libx264", "-profile:v", "high", "-pix_fmt", "yuv420p"])
+            .args(["-b:v", &format!("{}k", profile.video_bit_rate_kbps)])
+            .args(["-maxrate", &format!("{}k", profile.video_bit_rate_kbps)])
+            .args(["-bufsize", &format!("{}k", 2 * profile.video_bit_rate_kbps)])
+            .args(["-c:a", "aac", "-b:a", &format!("{}k", profile.audio_bit_rate_kbps)])
+            .args(["-movflags", "+faststart", "-f", "mp4", target_file_name])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Video encoder {} failed: {}",
+                self.program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+pub struct EncodedRendition {
+    pub profile: RenditionProfile,
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub file_size_bytes: u64,
+}
+
+#[derive(Clone)]
+pub struct Transcoder {
+    video_encoder: Arc<dyn VideoEncoder>,
+}
+
+impl Transcoder {
+    pub fn new(video_encoder: Arc<dyn VideoEncoder>) -> Self {
+        Transcoder { video_encoder }
+    }
+
+    /// Returns the profiles of the ladder not upscaling the source together with the dimensions of
+    /// their renditions, rounded to even numbers as required by 4:2:0 chroma subsampling.
+    pub fn plan_ladder(source_width: u32, source_height: u32, ladder: &[RenditionProfile]) -> Vec<(RenditionProfile, u32, u32)> {
+        if source_width == 0 || source_height == 0 {
+            return Vec::new();
+        }
+        ladder
+            .iter()
+            .filter_map(|profile| {
+                let scale = f64::min(
+                    profile.width as f64 / source_width as f64,
+                    profile.height as f64 / source_height as f64,
+                );
+                if scale > 1.0 {
+                    return None;
+                }
+                let even = |length: u32| -> u32 { ((length as f64 * scale / 2.0).round() as u32 * 2).max(2) };
+                Some((profile.clone(), even(source_width), even(source_height)))
+            })
+            .collect()
+    }
+
+    /// Encodes every planned rendition of the source into `<target_dir>/<profile name>.mp4`. Renditions
+    /// encoded before a failure are removed again.
+    #[tracing::instrument(skip(self, ladder))]
+    pub fn transcode(
+        &self,
+        source_file_name: &str,
+        target_dir: &str,
+        source_width: u32,
+        source_height: u32,
+        ladder: &[RenditionProfile],
+    ) -> Result<Vec<EncodedRendition>, Box<dyn std::error::Error>> {
+        let mut encoded_renditions: Vec<EncodedRendition> = Vec::new();
+        for (profile, width, height) in Self::plan_ladder(source_width, source_height, ladder) {
+            let file_name = Path::new(target_dir).join(format!("{}.mp4", profile.name)).to_string_lossy().to_string();
+            let file_size_bytes = self.video_encoder
+                .encode(source_file_name, &file_name, width, height, &profile)
+                .and_then(|_| Ok(fs::metadata(&file_name)?.len()));
+            let file_size_bytes = match file_size_bytes {
+                Ok(file_size_bytes) => file_size_bytes,
+                Err(err) => {
+                    let _ = fs::remove_file(&file_name);
+                    for encoded_rendition in encoded_renditions.iter() {
+                        let _ = fs::remove_file(&encoded_rendition.file_name);
+                    }
+                    return Err(format!("Failed to encode rendition {}: {}", profile.name, err).into());
+                }
+            };
+            info!("Successfully encoded rendition {} ({}x{}) of {}", profile.name, width, height, source_file_name);
+            encoded_renditions.push(EncodedRendition { profile, file_name, width, height, file_size_bytes });
+        }
+        Ok(encoded_renditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // writes the requested dimensions instead of encoding, failing for the profile named `fail_on`
+    struct FakeVideoEncoder {
+        fail_on: Option<String>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl VideoEncoder for FakeVideoEncoder {
+        fn encode(&self, _: &str, target_file_name: &str, width: u32, height: u32, profile: &RenditionProfile)
+            -> Result<(), Box<dyn std::error::Error>> {
+            self.calls.lock().unwrap().push(profile.name.clone());
+            fs::write(target_file_name, format!("{}x{}", width, height))?;
+            match self.fail_on.as_deref() == Some(profile.name.as_str()) {
+                true => Err("encoder crashed".into()),
+                false => Ok(()),
+            }
+        }
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_transcoder() {
+        let ladder = RenditionProfile::default_ladder();
+
+        // no upscaling, other aspect ratios fit into the box of the profile
+        let planned: Vec<(String, u32, u32)> = Transcoder::plan_ladder(1280, 720, &ladder)
+            .into_iter()
+            .map(|(profile, width, height)| (profile.name, width, height))
+            .collect();
+        assert_eq!(planned, vec![(String::from("720p"), 1280, 720), (String::from("480p"), 854, 480)]);
+        let planned = Transcoder::plan_ladder(1080, 1920, &ladder);
+        assert_eq!((planned[0].1, planned[0].2), (608, 1080));
+        assert_eq!((planned[2].1, planned[2].2), (270, 480));
+        assert!(Transcoder::plan_ladder(320, 240, &ladder).is_empty());
+        assert!(Transcoder::plan_ladder(0, 0, &ladder).is_empty());
+
+        let target_dir = std::env::temp_dir().join(format!("transcoder-{}", std::process::id()));
+        fs::create_dir_all(&target_dir).unwrap();
+        let target_dir = target_dir.to_string_lossy().to_string();
+
+        let video_encoder = Arc::new(FakeVideoEncoder { fail_on: None, calls: Mutex::new(Vec::new()) });
+        let transcoder = Transcoder::new(video_encoder.clone());
+        let encoded_renditions = transcoder.transcode("source.mp4", &target_dir, 1920, 1080, &ladder).unwrap();
+        assert_eq!(encoded_renditions.len(), 3);
+        assert_eq!(*video_encoder.calls.lock().unwrap(), vec!["1080p", "720p", "480p"]);
+        assert!(encoded_renditions[1].file_name.ends_with("720p.mp4"));
+        assert_eq!(fs::read_to_string(&encoded_renditions[1].file_name).unwrap(), "1280x720");
+        assert_eq!(encoded_renditions[1].file_size_bytes, 8);
+        for encoded_rendition in encoded_renditions.iter() {
+            fs::remove_file(&encoded_rendition.file_name).unwrap();
+        }
+
+        // a failing step removes the renditions encoded so far
+        let video_encoder = Arc::new(FakeVideoEncoder { fail_on: Some(String::from("720p")), calls: Mutex::new(Vec::new()) });
+        let transcoder = Transcoder::new(video_encoder.clone());
+        let result = transcoder.transcode("source.mp4", &target_dir, 1920, 1080, &ladder);
+        assert!(result.err().unwrap().to_string().contains("720p"));
+        assert_eq!(*video_encoder.calls.lock().unwrap(), vec!["1080p", "720p"]);
+        assert_eq!(fs::read_dir(&target_dir).unwrap().count(), 0);
+        fs::remove_dir_all(&target_dir).unwrap();
+
+        let command_video_encoder = CommandVideoEncoder::with_program("./non-existing-video-encoder");
+        assert!(command_video_encoder.encode("source.mp4", "target.mp4", 2, 2, &ladder[0]).is_err());
+    }
+}
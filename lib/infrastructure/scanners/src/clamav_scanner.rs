@@ -0,0 +1,258 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::content_scanner::{ContentScanner, ScanError, ScanVerdict};
+
+const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClamAvConfig {
+    // `host:port` of clamd's TCP socket, or `unix:<path>` of its local socket
+    pub address: String,
+    pub timeout_in_secs: u64,
+    // must stay below clamd's StreamMaxLength in total, chunks are sent as they are read
+    pub chunk_size_bytes: usize,
+}
+
+impl Default for ClamAvConfig {
+    fn default() -> Self {
+        ClamAvConfig {
+            address: String::from("127.0.0.1:3310"),
+            timeout_in_secs: 60,
+            chunk_size_bytes: 64 * 1024,
+        }
+    }
+}
+
+trait Connection: Read + Write {}
+
+impl<T: Read + Write> Connection for T {}
+
+/// Streams files to a ClamAV daemon with the INSTREAM command, see
+/// https://docs.clamav.net/manual/Usage/Scanning.html#clamd
+#[derive(Clone)]
+pub struct ClamAvScanner {
+    config: ClamAvConfig,
+}
+
+impl ClamAvScanner {
+    pub const NAME: &'static str = "clamav";
+
+    pub fn new(config: &ClamAvConfig) -> Self {
+        ClamAvScanner {
+            config: config.clone(),
+        }
+    }
+
+    fn connect(&self) -> Result<Box<dyn Connection>, ScanError> {
+        let timeout = Some(Duration::from_secs(self.config.timeout_in_secs));
+        match self.config.address.strip_prefix(UNIX_SOCKET_PREFIX) {
+            #[cfg(unix)]
+            Some(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            Some(_) => Err(ScanError::new("Unix sockets are not supported on this platform")),
+            None => {
+                let stream = TcpStream::connect(&self.config.address)?;
+                stream.set_read_timeout(timeout)?;
+                stream.set_write_timeout(timeout)?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+
+    /// Sends the content in length prefixed chunks terminated by an empty chunk and parses the reply.
+    pub fn scan_stream<R: Read>(&self, reader: &mut R) -> Result<ScanVerdict, ScanError> {
+        let mut connection = self.connect()?;
+        connection.write_all(b"zINSTREAM\0")?;
+        // clamd answers and closes the connection early if the stream exceeds StreamMaxLength
+        if let Err(err) = send_chunks(&mut connection, reader, self.config.chunk_size_bytes.max(1)) {
+            return match read_reply(&mut connection) {
+                Ok(reply) if !reply.is_empty() => parse_reply(&reply),
+                _ => Err(err),
+            };
+        }
+        parse_reply(&read_reply(&mut connection)?)
+    }
+}
+
+impl ContentScanner for ClamAvScanner {
+    fn name(&self) -> &'static str {
+        ClamAvScanner::NAME
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn scan_file(&self, filename: &str) -> Result<ScanVerdict, ScanError> {
+        let mut file = File::open(filename)?;
+        let scan_verdict = self.scan_stream(&mut file)?;
+        match &scan_verdict {
+            ScanVerdict::Infected { signature } => warn!(%signature, "Found malware"),
+            _ => info!(verdict = scan_verdict.as_str(), "Scanned file"),
+        }
+        Ok(scan_verdict)
+    }
+
+    fn check_health(&self) -> Result<(), ScanError> {
+        let mut connection = self.connect()?;
+        connection.write_all(b"zPING\0")?;
+        match read_reply(&mut connection)?.as_str() {
+            "PONG" => Ok(()),
+            reply => Err(ScanError::new(&format!("Unexpected reply to PING: {}", reply))),
+        }
+    }
+}
+
+fn send_chunks<R: Read>(connection: &mut Box<dyn Connection>, reader: &mut R, chunk_size_bytes: usize) -> Result<(), ScanError> {
+    let mut buffer = vec![0u8; chunk_size_bytes];
+    loop {
+        let len = reader.read(&mut buffer)?;
+        connection.write_all(&(len as u32).to_be_bytes())?;
+        if len == 0 {
+            return Ok(());
+        }
+        connection.write_all(&buffer[..len])?;
+    }
+}
+
+// replies of `z` prefixed commands are terminated by a null byte
+fn read_reply(connection: &mut Box<dyn Connection>) -> Result<String, ScanError> {
+    let mut reply = Vec::new();
+    let mut byte = [0u8; 1];
+    while connection.read(&mut byte)? == 1 && byte[0] != 0 {
+        reply.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&reply).trim().to_string())
+}
+
+// e.g. `stream: OK`, `stream: Eicar-Test-Signature FOUND` or `INSTREAM size limit exceeded. ERROR`
+fn parse_reply(reply: &str) -> Result<ScanVerdict, ScanError> {
+    let result = reply.strip_prefix("stream: ").unwrap_or(reply);
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected {
+            signature: String::from(signature),
+        })
+    } else {
+        Err(ScanError::new(&format!("clamd failed to scan the stream: {}", reply)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::thread;
+
+    const EICAR_MARKER: &[u8] = b"EICAR-STANDARD-ANTIVIRUS-TEST-FILE";
+
+    // Answers PING and INSTREAM like clamd, flagging streams containing the EICAR marker
+    fn spawn_clamd_stub(connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut command = Vec::new();
+                let mut byte = [0u8; 1];
+                while stream.read(&mut byte).unwrap() == 1 && byte[0] != 0 {
+                    command.push(byte[0]);
+                }
+                let reply: &[u8] = match command.as_slice() {
+                    b"zPING" => b"PONG\0",
+                    b"zINSTREAM" => {
+                        let mut content = Vec::new();
+                        loop {
+                            let mut len = [0u8; 4];
+                            stream.read_exact(&mut len).unwrap();
+                            let len = u32::from_be_bytes(len) as usize;
+                            if len == 0 {
+                                break;
+                            }
+                            let mut chunk = vec![0u8; len];
+                            stream.read_exact(&mut chunk).unwrap();
+                            content.extend(chunk);
+                        }
+                        match content.windows(EICAR_MARKER.len()).any(|window| window == EICAR_MARKER) {
+                            true => b"stream: Eicar-Test-Signature FOUND\0",
+                            false => b"stream: OK\0",
+                        }
+                    }
+                    _ => b"UNKNOWN COMMAND\0",
+                };
+                stream.write_all(reply).unwrap();
+            }
+        });
+        address
+    }
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_clam_av_scanner() {
+        let config = ClamAvConfig {
+            address: spawn_clamd_stub(3),
+            chunk_size_bytes: 16,
+            ..Default::default()
+        };
+        let clam_av_scanner = ClamAvScanner::new(&config);
+
+        assert!(clam_av_scanner.check_health().is_ok());
+        let clean = vec![7u8; 100];
+        assert_eq!(clam_av_scanner.scan_stream(&mut Cursor::new(clean)).unwrap(), ScanVerdict::Clean);
+        // the marker spans several chunks
+        let infected = [b"X5O!P%@AP[4\\PZX54(P^)7CC)7}$".as_slice(), EICAR_MARKER, b"!$H+H*"].concat();
+        assert_eq!(
+            clam_av_scanner.scan_stream(&mut Cursor::new(infected)).unwrap(),
+            ScanVerdict::Infected { signature: String::from("Eicar-Test-Signature") }
+        );
+
+        assert!(parse_reply("INSTREAM size limit exceeded. ERROR").is_err());
+        assert!(clam_av_scanner.scan_file("./non-existing.mp4").is_err());
+
+        // An unreachable daemon is an error rather than a verdict
+        let config = ClamAvConfig {
+            address: String::from("127.0.0.1:1"),
+            ..config
+        };
+        let clam_av_scanner = ClamAvScanner::new(&config);
+        assert!(clam_av_scanner.check_health().is_err());
+        assert!(clam_av_scanner.scan_stream(&mut Cursor::new(vec![0u8; 8])).is_err());
+    }
+}
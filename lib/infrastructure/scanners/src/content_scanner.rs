@@ -0,0 +1,128 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use std::fmt;
+
+/// Outcome of scanning a file, infected files must not be published.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { signature: String },
+    NotScanned,
+}
+
+impl ScanVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanVerdict::Clean => "clean",
+            ScanVerdict::Infected { .. } => "infected",
+            ScanVerdict::NotScanned => "not_scanned",
+        }
+    }
+}
+
+/// The scanner could not reach a verdict, e.g. because it is unreachable or refused the file.
+#[derive(Debug)]
+pub struct ScanError {
+    message: String,
+}
+
+impl ScanError {
+    pub fn new(message: &str) -> Self {
+        ScanError {
+            message: String::from(message),
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+impl From<std::io::Error> for ScanError {
+    fn from(err: std::io::Error) -> Self {
+        ScanError::new(&err.to_string())
+    }
+}
+
+/// Scans uploaded files before they are handed over to the blob storage.
+/// Implementations block, callers on an async runtime have to use `spawn_blocking`.
+pub trait ContentScanner: Send + Sync {
+    /// Identifies the scanner in logs, metrics and readiness checks
+    fn name(&self) -> &'static str;
+
+    fn scan_file(&self, filename: &str) -> Result<ScanVerdict, ScanError>;
+
+    fn check_health(&self) -> Result<(), ScanError> {
+        Ok(())
+    }
+}
+
+/// Default if no scanner is configured, accepting every file as not scanned.
+#[derive(Clone)]
+pub struct NoopContentScanner {}
+
+impl NoopContentScanner {
+    pub const NAME: &'static str = "none";
+
+    pub fn new() -> Self {
+        NoopContentScanner {}
+    }
+}
+
+impl Default for NoopContentScanner {
+    fn default() -> Self {
+        NoopContentScanner::new()
+    }
+}
+
+impl ContentScanner for NoopContentScanner {
+    fn name(&self) -> &'static str {
+        NoopContentScanner::NAME
+    }
+
+    fn scan_file(&self, _filename: &str) -> Result<ScanVerdict, ScanError> {
+        Ok(ScanVerdict::NotScanned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_noop_content_scanner() {
+        let content_scanner: Box<dyn ContentScanner> = Box::new(NoopContentScanner::new());
+        assert_eq!(content_scanner.name(), "none");
+        assert_eq!(content_scanner.scan_file("./non-existing.mp4").unwrap(), ScanVerdict::NotScanned);
+        assert!(content_scanner.check_health().is_ok());
+        assert_eq!(ScanVerdict::Infected { signature: String::from("Eicar-Test-Signature") }.as_str(), "infected");
+        assert_eq!(ScanVerdict::Clean.as_str(), "clean");
+    }
+}
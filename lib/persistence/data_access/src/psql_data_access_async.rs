@@ -27,12 +27,15 @@
 
 use diesel::prelude::*;
 use diesel_async::{RunQueryDsl, AsyncConnection, AsyncPgConnection};
+use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel::{
     Connection, PgConnection
 };
-use diesel_async::pooled_connection::{bb8::Pool, AsyncDieselConnectionManager};
+use diesel_async::pooled_connection::{bb8::{Pool, PooledConnection}, AsyncDieselConnectionManager};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
-use log::info;
+use chrono::{DateTime, Utc};
+use tracing::info;
+use serde::Deserialize;
 use models::{
     container_meta::ContainerMeta,
     track::{self, VideoTrack, AudioTrack, SubtitleTrack},
@@ -41,37 +44,105 @@ use uuid::Uuid;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../../domain/models/migrations");
 
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PsqlConfig {
+    pub url: String,
+    pub pool_size: u32,
+}
+
+impl Default for PsqlConfig {
+    fn default() -> Self {
+        PsqlConfig {
+            url: String::from(""),
+            pool_size: 10,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct PsqlDataAccess {
     pub connection_pool: bb8::Pool<AsyncDieselConnectionManager<AsyncPgConnection>>,
+    max_pool_size: u32,
+    // without a tenant, connections bypass row level security. Reserved for authentication and background jobs.
+    tenant_id: Option<String>,
 }
 
 impl PsqlDataAccess {
-    pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not found in .cfg");
-        let config = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
-        let connection_pool = Pool::builder().build(config).await.unwrap();
+    #[tracing::instrument(skip_all)]
+    pub async fn new(psql_config: &PsqlConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = AsyncDieselConnectionManager::<AsyncPgConnection>::new(&psql_config.url);
+        let connection_pool = Pool::builder().max_size(psql_config.pool_size).build(config).await?;
 
         // migrations at compile time     
         // NOTE: workaround for async_diesel     
-        let migration_database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not found in .cfg");
-        let mut migration_pg_connection = PgConnection::establish(&migration_database_url).
-            unwrap_or_else(|_| panic!("Error connecting to {}", migration_database_url));
+        let mut migration_pg_connection = PgConnection::establish(&psql_config.url)?;
         info!("About to migrate datbase tables");
-        migration_pg_connection.run_pending_migrations(MIGRATIONS).unwrap();
+        migration_pg_connection.run_pending_migrations(MIGRATIONS).map_err(|err| err.to_string())?;
 
         Ok(PsqlDataAccess {
-            connection_pool: connection_pool
+            connection_pool: connection_pool,
+            max_pool_size: psql_config.pool_size,
+            tenant_id: None,
         })
     }
 
+    /// Shares the connection pool, restricting every statement to the rows of the tenant.
+    pub fn for_tenant(&self, tenant_id: &str) -> Self {
+        PsqlDataAccess {
+            connection_pool: self.connection_pool.clone(),
+            max_pool_size: self.max_pool_size,
+            tenant_id: Some(String::from(tenant_id)),
+        }
+    }
+
+    /// Pooled connections keep their session settings, hence the tenant is set on every checkout.
+    async fn get_connection(&self) -> Result<PooledConnection<'_, AsyncPgConnection>, diesel::result::Error> {
+        use diesel::sql_types::Text;
+
+        let started = std::time::Instant::now();
+        let pg_connection = self.connection_pool.get().await;
+        metrics::histogram!("mms_db_connection_acquire_duration_seconds").record(started.elapsed().as_secs_f64());
+        let mut pg_connection = pg_connection.map_err(|err| {
+            metrics::counter!("mms_db_connection_acquire_errors_total").increment(1);
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UnableToSendCommand, Box::new(err.to_string()))
+        })?;
+        let (tenant, bypass_tenant_isolation) = match &self.tenant_id {
+            Some(tenant) => (tenant.as_str(), "off"),
+            None => ("", "on"),
+        };
+        diesel::sql_query("SELECT set_config('app.tenant_id', $1, false), set_config('app.bypass_tenant_isolation', $2, false)")
+            .bind::<Text, _>(tenant)
+            .bind::<Text, _>(bypass_tenant_isolation)
+            .execute(&mut pg_connection).await?;
+        Ok(pg_connection)
+    }
+
+    /// Publishes the pool utilization as gauges, sampled whenever metrics are scraped.
+    pub fn record_pool_metrics(&self) {
+        let state = self.connection_pool.state();
+        metrics::gauge!("mms_db_pool_max_connections").set(self.max_pool_size as f64);
+        metrics::gauge!("mms_db_pool_connections").set(state.connections as f64);
+        metrics::gauge!("mms_db_pool_idle_connections").set(state.idle_connections as f64);
+    }
+
+    /// Checks out a pooled connection and runs a trivial statement.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn check_health(&self) -> Result<(), diesel::result::Error> {
+        let mut pg_connection = self.get_connection().await?;
+        diesel::sql_query("SELECT 1").execute(&mut pg_connection).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn insert_video_track(
         &self,
         video_track: &models::track::VideoTrack,
     ) -> Result<models::track::VideoTrack, diesel::result::Error> {
         use models::schema::video_track;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::insert_into(video_track::table)
         .values(video_track)
         .returning(models::track::VideoTrack::as_returning())
@@ -84,13 +155,14 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn insert_audio_track(
         &self,
         audio_track: &models::track::AudioTrack,
     ) -> Result<models::track::AudioTrack, diesel::result::Error> {
         use models::schema::audio_track;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::insert_into(audio_track::table)
         .values(audio_track)
         .returning(models::track::AudioTrack::as_returning())
@@ -103,13 +175,14 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn insert_subtitlte_track(
         &self,
         subtitle_track: &models::track::SubtitleTrack,
     ) -> Result<models::track::SubtitleTrack, diesel::result::Error> {
         use models::schema::subtitle_track;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::insert_into(subtitle_track::table)
         .values(subtitle_track)
         .returning(models::track::SubtitleTrack::as_returning())
@@ -122,13 +195,14 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn insert_container_meta(
         &self,
         in_container_meta: &models::container_meta::ContainerMeta,
     ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
         use models::schema::container_meta;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::insert_into(container_meta::table)
         .values(in_container_meta)
         .returning(models::container_meta::ContainerMeta::as_returning())
@@ -141,13 +215,214 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_thumbnail(
+        &self,
+        in_thumbnail: &models::thumbnail::Thumbnail,
+    ) -> Result<models::thumbnail::Thumbnail, diesel::result::Error> {
+        use models::schema::thumbnail;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(thumbnail::table)
+        .values(in_thumbnail)
+        .returning(models::thumbnail::Thumbnail::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted thumbnail {} for container_meta_id {}",
+            in_thumbnail.name, in_thumbnail.container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_rendition(
+        &self,
+        in_rendition: &models::rendition::Rendition,
+    ) -> Result<models::rendition::Rendition, diesel::result::Error> {
+        use models::schema::rendition;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(rendition::table)
+        .values(in_rendition)
+        .returning(models::rendition::Rendition::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted rendition {} for container_meta_id {}",
+            in_rendition.name, in_rendition.container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_chapters(
+        &self,
+        in_chapters: &[models::chapter::Chapter],
+    ) -> Result<Vec<models::chapter::Chapter>, diesel::result::Error> {
+        use models::schema::chapter;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(chapter::table)
+        .values(in_chapters)
+        .returning(models::chapter::Chapter::as_returning())
+        .get_results(&mut pg_connection).await?;
+
+        info!("Successfully inserted {} chapters", result.len());
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_container_version(
+        &self,
+        in_container_version: &models::container_version::ContainerVersion,
+    ) -> Result<models::container_version::ContainerVersion, diesel::result::Error> {
+        use models::schema::container_version;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(container_version::table)
+        .values(in_container_version)
+        .returning(models::container_version::ContainerVersion::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted version {} of container_meta_id {}",
+            in_container_version.version, in_container_version.container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_api_key(
+        &self,
+        in_api_key: &models::api_key::ApiKey,
+    ) -> Result<models::api_key::ApiKey, diesel::result::Error> {
+        use models::schema::api_key;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(api_key::table)
+        .values(in_api_key)
+        .returning(models::api_key::ApiKey::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted api key {} for subject {}",
+            in_api_key.key_prefix, in_api_key.subject
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_usage_entry(
+        &self,
+        in_usage_entry: &models::usage_entry::UsageEntry,
+    ) -> Result<models::usage_entry::UsageEntry, diesel::result::Error> {
+        use models::schema::usage_ledger;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(usage_ledger::table)
+        .values(in_usage_entry)
+        .returning(models::usage_entry::UsageEntry::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted usage entry of {} bytes for container_meta_id {}",
+            in_usage_entry.bytes_delta, in_usage_entry.container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn insert_quarantined_upload(
+        &self,
+        in_quarantined_upload: &models::quarantined_upload::QuarantinedUpload,
+    ) -> Result<models::quarantined_upload::QuarantinedUpload, diesel::result::Error> {
+        use models::schema::quarantined_upload;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(quarantined_upload::table)
+        .values(in_quarantined_upload)
+        .returning(models::quarantined_upload::QuarantinedUpload::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully inserted quarantined upload {} of {}",
+            in_quarantined_upload.id, in_quarantined_upload.original_file_name
+        );
+        Ok(result)
+    }
+
+    /// Sums the usage entries of the tenant, optionally narrowed to a subject or a container.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_usage(
+        &self,
+        tenant: &str,
+        in_subject: Option<&str>,
+        in_container_meta_id: Option<&Uuid>,
+    ) -> Result<models::usage_entry::Usage, diesel::result::Error> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::BigInt;
+        use models::schema::usage_ledger::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let mut query = usage_ledger
+            .filter(tenant_id.eq(tenant))
+            .into_boxed();
+        if let Some(in_subject) = in_subject {
+            query = query.filter(subject.eq(in_subject));
+        }
+        if let Some(in_container_meta_id) = in_container_meta_id {
+            query = query.filter(container_meta_id.eq(in_container_meta_id));
+        }
+        // SUM of BIGINT yields NUMERIC in Postgres
+        let (bytes, container_count, duration_in_ms) = query
+            .select((
+                sql::<BigInt>("COALESCE(SUM(bytes_delta), 0)::BIGINT"),
+                sql::<BigInt>("COALESCE(SUM(container_count_delta), 0)::BIGINT"),
+                sql::<BigInt>("COALESCE(SUM(duration_in_ms_delta), 0)::BIGINT"),
+            ))
+            .get_result::<(i64, i64, i64)>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved usage of tenant {}", tenant);
+        Ok(models::usage_entry::Usage { bytes, container_count, duration_in_ms })
+    }
+
+    /// Grants the permission or replaces the permission previously granted to the grantee.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn upsert_container_grant(
+        &self,
+        in_container_grant: &models::container_grant::ContainerGrant,
+    ) -> Result<models::container_grant::ContainerGrant, diesel::result::Error> {
+        use models::schema::container_grant::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::insert_into(container_grant)
+        .values(in_container_grant)
+        .on_conflict((container_meta_id, grantee))
+        .do_update()
+        .set((
+            permission.eq(&in_container_grant.permission),
+            granted_by.eq(&in_container_grant.granted_by),
+            date_time_created.eq(&in_container_grant.date_time_created),
+        ))
+        .returning(models::container_grant::ContainerGrant::as_returning())
+        .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully granted {} on container_meta_id {} to {}",
+            in_container_grant.permission, in_container_grant.container_meta_id, in_container_grant.grantee
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn get_video_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<models::track::VideoTrack, diesel::result::Error> {
         use models::schema::video_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = video_track
             .filter(id.eq(track_id))
             .first::<models::track::VideoTrack>(&mut pg_connection).await?;
@@ -159,13 +434,14 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn get_audio_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<models::track::AudioTrack, diesel::result::Error> {
         use models::schema::audio_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = audio_track
             .filter(id.eq(track_id))
             .first::<models::track::AudioTrack>(&mut pg_connection).await?;
@@ -177,13 +453,14 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn get_subtitle_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<models::track::SubtitleTrack, diesel::result::Error> {
         use models::schema::subtitle_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = subtitle_track
             .filter(id.eq(track_id))
             .first::<models::track::SubtitleTrack>(&mut pg_connection).await?;
@@ -195,15 +472,17 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn get_container_meta_by_id(
         &self,
         container_meta_id: &Uuid,
     ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
         use models::schema::container_meta::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = container_meta
             .filter(id.eq(container_meta_id))
+            .filter(deleted_at.is_null())
             .first::<models::container_meta::ContainerMeta>(&mut pg_connection).await?;
 
         info!(
@@ -213,6 +492,324 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn find_container_meta_by_id(
+        &self,
+        container_meta_id: &Uuid,
+    ) -> Result<Option<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_meta
+            .filter(id.eq(container_meta_id))
+            .filter(deleted_at.is_null())
+            .first::<models::container_meta::ContainerMeta>(&mut pg_connection).await
+            .optional()?;
+
+        info!(
+            "Successfully looked up container metainformation by container_meta_id {}",
+            container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn find_container_meta_by_id_including_deleted(
+        &self,
+        container_meta_id: &Uuid,
+    ) -> Result<Option<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_meta
+            .filter(id.eq(container_meta_id))
+            .first::<models::container_meta::ContainerMeta>(&mut pg_connection).await
+            .optional()?;
+
+        info!(
+            "Successfully looked up container metainformation including deleted ones by container_meta_id {}",
+            container_meta_id
+        );
+        Ok(result)
+    }
+
+    /// Lists containers owned by or shared with the subject, or all containers if `all` is set.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_metas_visible_to(
+        &self,
+        tenant: &str,
+        subject: &str,
+        all: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_grant;
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        // filtering explicitly as well keeps tenants apart when connected as a role bypassing row level security
+        let mut query = container_meta
+            .filter(tenant_id.eq(tenant))
+            .filter(deleted_at.is_null())
+            .into_boxed();
+        if !all {
+            let shared_container_meta_ids = container_grant::table
+                .filter(container_grant::grantee.eq(subject))
+                .select(container_grant::container_meta_id);
+            query = query.filter(owner_id.eq(subject).or(id.eq_any(shared_container_meta_ids)));
+        }
+        let result = query
+            .order(date_time_created.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<models::container_meta::ContainerMeta>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} container metainformation visible to {}", result.len(), subject);
+        Ok(result)
+    }
+
+    /// Lists the quarantined uploads of the tenant, most recent first.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_quarantined_uploads(
+        &self,
+        tenant: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<models::quarantined_upload::QuarantinedUpload>, diesel::result::Error> {
+        use models::schema::quarantined_upload::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = quarantined_upload
+            .filter(tenant_id.eq(tenant))
+            .order(date_time_created.desc())
+            .limit(limit)
+            .offset(offset)
+            .load::<models::quarantined_upload::QuarantinedUpload>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} quarantined uploads", result.len());
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_metas_deleted_before(
+        &self,
+        deleted_before: &DateTime<Utc>,
+    ) -> Result<Vec<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_meta
+            .filter(deleted_at.lt(deleted_before))
+            .load::<models::container_meta::ContainerMeta>(&mut pg_connection).await?;
+
+        info!(
+            "Successfully retrieved {} container metainformation deleted before {}",
+            result.len(), deleted_before
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_metas_needing_backfill(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_meta
+            .filter(needs_backfill.eq(true))
+            .order(date_time_created.asc())
+            .limit(limit)
+            .load::<models::container_meta::ContainerMeta>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} container metainformation needing a backfill", result.len());
+        Ok(result)
+    }
+
+    /// Oldest containers still waiting for their renditions. Containers awaiting a backfill have no
+    /// reliable object key yet and soft deleted ones are purged anyway.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_metas_pending_transcoding(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_meta
+            .filter(transcoding_status.eq("pending"))
+            .filter(needs_backfill.eq(false))
+            .filter(deleted_at.is_null())
+            .order(date_time_created.asc())
+            .limit(limit)
+            .load::<models::container_meta::ContainerMeta>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} container metainformation pending transcoding", result.len());
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_audio_tracks_pending_analysis(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<models::track::AudioTrack>, diesel::result::Error> {
+        use models::schema::{audio_track, container_meta};
+
+        let mut pg_connection = self.get_connection().await?;
+        let analyzable_container_meta_ids = container_meta::table
+            .filter(container_meta::needs_backfill.eq(false))
+            .filter(container_meta::deleted_at.is_null())
+            .select(container_meta::id);
+        let result = audio_track::table
+            .filter(audio_track::analysis_status.eq("pending"))
+            .filter(audio_track::container_meta_id.eq_any(analyzable_container_meta_ids))
+            .limit(limit)
+            .load::<models::track::AudioTrack>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} audio tracks pending analysis", result.len());
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn find_active_api_key_by_prefix(
+        &self,
+        in_key_prefix: &str,
+    ) -> Result<Option<models::api_key::ApiKey>, diesel::result::Error> {
+        use models::schema::api_key::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = api_key
+            .filter(key_prefix.eq(in_key_prefix))
+            .filter(revoked_at.is_null())
+            .first::<models::api_key::ApiKey>(&mut pg_connection).await
+            .optional()?;
+
+        info!("Successfully looked up api key {}", in_key_prefix);
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_grants_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<Vec<models::container_grant::ContainerGrant>, diesel::result::Error> {
+        use models::schema::container_grant::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_grant
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .order(date_time_created.asc())
+            .load::<models::container_grant::ContainerGrant>(&mut pg_connection).await?;
+
+        info!("Successfully retrieved {} grants of {}", result.len(), in_container_meta_id);
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_thumbnails_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<Vec<models::thumbnail::Thumbnail>, diesel::result::Error> {
+        use models::schema::thumbnail::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = thumbnail
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .order(time_offset_in_ms.asc())
+            .load::<models::thumbnail::Thumbnail>(&mut pg_connection).await?;
+
+        info!(
+            "Successfully retrieved {} thumbnails by container_meta_id {}",
+            result.len(), in_container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_renditions_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<Vec<models::rendition::Rendition>, diesel::result::Error> {
+        use models::schema::rendition::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = rendition
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .order(height.desc())
+            .load::<models::rendition::Rendition>(&mut pg_connection).await?;
+
+        info!(
+            "Successfully retrieved {} renditions by container_meta_id {}",
+            result.len(), in_container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_chapters_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<Vec<models::chapter::Chapter>, diesel::result::Error> {
+        use models::schema::chapter::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = chapter
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .order(start_time_in_ms.asc())
+            .load::<models::chapter::Chapter>(&mut pg_connection).await?;
+
+        info!(
+            "Successfully retrieved {} chapters by container_meta_id {}",
+            result.len(), in_container_meta_id
+        );
+        Ok(result)
+    }
+
+    /// Versions of a container, the latest first.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn get_container_versions_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<Vec<models::container_version::ContainerVersion>, diesel::result::Error> {
+        use models::schema::container_version::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_version
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .order(version.desc())
+            .load::<models::container_version::ContainerVersion>(&mut pg_connection).await?;
+
+        info!(
+            "Successfully retrieved {} versions by container_meta_id {}",
+            result.len(), in_container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn find_container_version(
+        &self,
+        in_container_meta_id: &Uuid,
+        in_version: i32,
+    ) -> Result<Option<models::container_version::ContainerVersion>, diesel::result::Error> {
+        use models::schema::container_version::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = container_version
+            .filter(container_meta_id.eq(in_container_meta_id))
+            .filter(version.eq(in_version))
+            .first::<models::container_version::ContainerVersion>(&mut pg_connection).await
+            .optional()?;
+
+        info!(
+            "Successfully looked up version {} of container_meta_id {}",
+            in_version, in_container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn update_video_track_by_id(
         &self,
         track_id: &Uuid,
@@ -220,7 +817,7 @@ impl PsqlDataAccess {
     ) -> Result<models::track::VideoTrack, diesel::result::Error> {
         use models::schema::video_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::update(video_track.filter(id.eq(track_id)))
             .set((
                 name.eq(&in_track.name),
@@ -229,6 +826,9 @@ impl PsqlDataAccess {
                 height.eq(&in_track.height),
                 bit_rate.eq(&in_track.bit_rate),
                 frame_rate.eq(&in_track.frame_rate),
+                duration_in_ms.eq(&in_track.duration_in_ms),
+                timescale.eq(&in_track.timescale),
+                mp4_track_id.eq(&in_track.mp4_track_id),
             ))
             .returning(models::track::VideoTrack::as_returning())
             .get_result(&mut pg_connection).await?;
@@ -240,6 +840,7 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn update_audio_track_by_id(
         &self,
         track_id: &Uuid,
@@ -247,7 +848,7 @@ impl PsqlDataAccess {
     ) -> Result<models::track::AudioTrack, diesel::result::Error> {
         use models::schema::audio_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::update(audio_track.filter(id.eq(track_id)))
             .set((
                 name.eq(&in_track.name),
@@ -255,6 +856,9 @@ impl PsqlDataAccess {
                 bit_rate.eq(&in_track.bit_rate),
                 channel_config.eq(&in_track.channel_config),
                 sample_frequenz.eq(&in_track.sample_frequenz),
+                duration_in_ms.eq(&in_track.duration_in_ms),
+                timescale.eq(&in_track.timescale),
+                mp4_track_id.eq(&in_track.mp4_track_id),
             ))
             .returning(models::track::AudioTrack::as_returning())
             .get_result(&mut pg_connection).await?;
@@ -266,6 +870,7 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn update_subtitle_track_by_id(
         &self,
         track_id: &Uuid,
@@ -273,11 +878,14 @@ impl PsqlDataAccess {
     ) -> Result<models::track::SubtitleTrack, diesel::result::Error> {
         use models::schema::subtitle_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let result = diesel::update(subtitle_track.filter(id.eq(track_id)))
             .set((
                 name.eq(&in_track.name),
                 media_type.eq(&in_track.media_type),
+                duration_in_ms.eq(&in_track.duration_in_ms),
+                timescale.eq(&in_track.timescale),
+                mp4_track_id.eq(&in_track.mp4_track_id),
             ))
             .returning(models::track::SubtitleTrack::as_returning())
             .get_result(&mut pg_connection).await?;
@@ -289,6 +897,7 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn update_container_meta_by_id(
         &self,
         container_meta_id: &Uuid,
@@ -296,8 +905,8 @@ impl PsqlDataAccess {
     ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
         use models::schema::container_meta::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
-        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)))
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)).filter(deleted_at.is_null()))
             .set((
                 date_time_created.eq(&in_container_meta.date_time_created),
                 date_time_updated.eq(&in_container_meta.date_time_updated),
@@ -307,8 +916,8 @@ impl PsqlDataAccess {
                 video_track_id.eq(&in_container_meta.video_track_id),
                 audio_track_id.eq(&in_container_meta.audio_track_id),
                 subtitle_track_id.eq(&in_container_meta.subtitle_track_id),
-                file_size_in_kb.eq(&in_container_meta.file_size_in_kb),
-                duration.eq(&in_container_meta.duration),
+                file_size_bytes.eq(&in_container_meta.file_size_bytes),
+                duration_in_ms.eq(&in_container_meta.duration_in_ms),
             ))
             .returning(models::container_meta::ContainerMeta::as_returning())
             .get_result(&mut pg_connection).await?;
@@ -320,13 +929,247 @@ impl PsqlDataAccess {
         Ok(result)
     }
 
+    /// Stores the values recomputed from the blob and clears `needs_backfill`, regardless of soft deletion.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn update_backfilled_container_meta_by_id(
+        &self,
+        container_meta_id: &Uuid,
+        in_container_meta: &models::container_meta::ContainerMeta,
+    ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)))
+            .set((
+                file_size_bytes.eq(&in_container_meta.file_size_bytes),
+                duration_in_ms.eq(&in_container_meta.duration_in_ms),
+                original_file_name.eq(&in_container_meta.original_file_name),
+                mime_type.eq(&in_container_meta.mime_type),
+                major_brand.eq(&in_container_meta.major_brand),
+                compatible_brands.eq(&in_container_meta.compatible_brands),
+                object_key.eq(&in_container_meta.object_key),
+                web_optimized.eq(&in_container_meta.web_optimized),
+                needs_backfill.eq(false),
+            ))
+            .returning(models::container_meta::ContainerMeta::as_returning())
+            .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully backfilled container metainformation by container_meta_id {}",
+            container_meta_id
+        );
+        Ok(result)
+    }
+
+    /// Points the container to another version of its file, replacing everything derived from the file.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn update_container_file_by_id(
+        &self,
+        container_meta_id: &Uuid,
+        in_container_meta: &models::container_meta::ContainerMeta,
+    ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)).filter(deleted_at.is_null()))
+            .set((
+                date_time_updated.eq(&in_container_meta.date_time_updated),
+                video_track_id.eq(&in_container_meta.video_track_id),
+                audio_track_id.eq(&in_container_meta.audio_track_id),
+                subtitle_track_id.eq(&in_container_meta.subtitle_track_id),
+                file_size_bytes.eq(&in_container_meta.file_size_bytes),
+                duration_in_ms.eq(&in_container_meta.duration_in_ms),
+                original_file_name.eq(&in_container_meta.original_file_name),
+                mime_type.eq(&in_container_meta.mime_type),
+                major_brand.eq(&in_container_meta.major_brand),
+                compatible_brands.eq(&in_container_meta.compatible_brands),
+                storage_backend.eq(&in_container_meta.storage_backend),
+                object_key.eq(&in_container_meta.object_key),
+                scan_verdict.eq(&in_container_meta.scan_verdict),
+                transcoding_status.eq(&in_container_meta.transcoding_status),
+                web_optimized.eq(&in_container_meta.web_optimized),
+                version.eq(&in_container_meta.version),
+            ))
+            .returning(models::container_meta::ContainerMeta::as_returning())
+            .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully set version {} of container_meta_id {}",
+            in_container_meta.version, container_meta_id
+        );
+        Ok(result)
+    }
+
+    /// Points the container and its current version to the file rewritten with the container's title, description
+    /// and tags and books the change of size, within one transaction.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn update_rewritten_container_file_by_id(
+        &self,
+        container_meta_id: &Uuid,
+        in_container_meta: &models::container_meta::ContainerMeta,
+        in_usage_entry: &models::usage_entry::UsageEntry,
+    ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
+        use models::schema::{container_meta, container_version, usage_ledger};
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = pg_connection.transaction::<_, diesel::result::Error, _>(|pg_connection| async move {
+            diesel::insert_into(usage_ledger::table)
+                .values(in_usage_entry)
+                .execute(pg_connection).await?;
+            diesel::update(
+                container_version::table
+                    .filter(container_version::container_meta_id.eq(container_meta_id))
+                    .filter(container_version::version.eq(in_container_meta.version)),
+            )
+                .set((
+                    container_version::file_size_bytes.eq(&in_container_meta.file_size_bytes),
+                    container_version::object_key.eq(&in_container_meta.object_key),
+                ))
+                .execute(pg_connection).await?;
+            diesel::update(
+                container_meta::table
+                    .filter(container_meta::id.eq(container_meta_id))
+                    .filter(container_meta::deleted_at.is_null()),
+            )
+                .set((
+                    container_meta::date_time_updated.eq(&in_container_meta.date_time_updated),
+                    container_meta::title.eq(&in_container_meta.title),
+                    container_meta::description.eq(&in_container_meta.description),
+                    container_meta::tags.eq(&in_container_meta.tags),
+                    container_meta::file_size_bytes.eq(&in_container_meta.file_size_bytes),
+                    container_meta::object_key.eq(&in_container_meta.object_key),
+                ))
+                .returning(models::container_meta::ContainerMeta::as_returning())
+                .get_result(pg_connection).await
+        }.scope_boxed()).await?;
+
+        info!(
+            "Successfully set the rewritten file of version {} of container_meta_id {}",
+            in_container_meta.version, container_meta_id
+        );
+        Ok(result)
+    }
+
+    /// Sets the transcoding status regardless of soft deletion, without touching `date_time_updated`.
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn update_transcoding_status_by_id(
+        &self,
+        container_meta_id: &Uuid,
+        in_transcoding_status: &str,
+    ) -> Result<models::container_meta::ContainerMeta, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)))
+            .set(transcoding_status.eq(in_transcoding_status))
+            .returning(models::container_meta::ContainerMeta::as_returning())
+            .get_result(&mut pg_connection).await?;
+
+        info!(
+            "Successfully set transcoding status {} of container_meta_id {}",
+            in_transcoding_status, container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn update_audio_analysis_by_id(
+        &self,
+        track_id: &Uuid,
+        in_audio_track: &models::track::AudioTrack,
+    ) -> Result<models::track::AudioTrack, diesel::result::Error> {
+        use models::schema::audio_track::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(audio_track.filter(id.eq(track_id)))
+            .set((
+                analysis_status.eq(&in_audio_track.analysis_status),
+                integrated_loudness_lufs.eq(in_audio_track.integrated_loudness_lufs),
+                true_peak_dbtp.eq(in_audio_track.true_peak_dbtp),
+                loudness_range_lu.eq(in_audio_track.loudness_range_lu),
+                waveform.eq(&in_audio_track.waveform),
+            ))
+            .returning(models::track::AudioTrack::as_returning())
+            .get_result(&mut pg_connection).await?;
+
+        info!("Successfully set analysis status {} of audio track {}", in_audio_track.analysis_status, track_id);
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn soft_delete_container_meta_by_id(
+        &self,
+        container_meta_id: &Uuid,
+    ) -> Result<Option<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)).filter(deleted_at.is_null()))
+            .set(deleted_at.eq(Some(Utc::now())))
+            .returning(models::container_meta::ContainerMeta::as_returning())
+            .get_result(&mut pg_connection).await
+            .optional()?;
+
+        info!(
+            "Successfully soft deleted container metainformation by container_meta_id {}",
+            container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn restore_container_meta_by_id(
+        &self,
+        container_meta_id: &Uuid,
+    ) -> Result<Option<models::container_meta::ContainerMeta>, diesel::result::Error> {
+        use models::schema::container_meta::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(container_meta.filter(id.eq(container_meta_id)).filter(deleted_at.is_not_null()))
+            .set(deleted_at.eq(None::<DateTime<Utc>>))
+            .returning(models::container_meta::ContainerMeta::as_returning())
+            .get_result(&mut pg_connection).await
+            .optional()?;
+
+        info!(
+            "Successfully restored container metainformation by container_meta_id {}",
+            container_meta_id
+        );
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn revoke_api_key_by_id(
+        &self,
+        api_key_id: &Uuid,
+        in_subject: &str,
+    ) -> Result<Option<models::api_key::ApiKey>, diesel::result::Error> {
+        use models::schema::api_key::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let result = diesel::update(
+            api_key
+                .filter(id.eq(api_key_id))
+                .filter(subject.eq(in_subject))
+                .filter(revoked_at.is_null()),
+        )
+            .set(revoked_at.eq(Some(Utc::now())))
+            .returning(models::api_key::ApiKey::as_returning())
+            .get_result(&mut pg_connection).await
+            .optional()?;
+
+        info!("Successfully revoked api key {}", api_key_id);
+        Ok(result)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn delete_video_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<(), diesel::result::Error> {
         use models::schema::video_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let rows_deleted =
             diesel::delete(video_track.filter(id.eq(track_id))).execute(&mut pg_connection).await?;
 
@@ -334,13 +1177,14 @@ impl PsqlDataAccess {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn delete_audio_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<(), diesel::result::Error> {
         use models::schema::audio_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let rows_deleted =
             diesel::delete(audio_track.filter(id.eq(track_id))).execute(&mut pg_connection).await?;
 
@@ -348,13 +1192,14 @@ impl PsqlDataAccess {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn delete_subtitle_track_by_id(
         &self,
         track_id: &Uuid,
     ) -> Result<(), diesel::result::Error> {
         use models::schema::subtitle_track::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let rows_deleted =
             diesel::delete(subtitle_track.filter(id.eq(track_id))).execute(&mut pg_connection).await?;
 
@@ -362,19 +1207,136 @@ impl PsqlDataAccess {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_tracks_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::{audio_track, subtitle_track, video_track};
+
+        let mut pg_connection = self.get_connection().await?;
+        let video_tracks_deleted = diesel::delete(video_track::table.filter(video_track::container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+        let audio_tracks_deleted = diesel::delete(audio_track::table.filter(audio_track::container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+        let subtitle_tracks_deleted = diesel::delete(subtitle_track::table.filter(subtitle_track::container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!(
+            "Successfully deleted {} video, {} audio and {} subtitle tracks of {}",
+            video_tracks_deleted, audio_tracks_deleted, subtitle_tracks_deleted, in_container_meta_id
+        );
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
     pub async fn delete_container_meta_by_id(
         &self,
         container_meta_id: &Uuid,
     ) -> Result<(), diesel::result::Error> {
         use models::schema::container_meta::dsl::*;
 
-        let mut pg_connection = self.connection_pool.get().await.unwrap();
+        let mut pg_connection = self.get_connection().await?;
         let rows_deleted = diesel::delete(container_meta.filter(id.eq(container_meta_id)))
             .execute(&mut pg_connection).await?;
 
         info!("Successfully deleted {}", container_meta_id);
         Ok(())
     }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_container_grant(
+        &self,
+        in_container_meta_id: &Uuid,
+        in_grantee: &str,
+    ) -> Result<bool, diesel::result::Error> {
+        use models::schema::container_grant::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(
+            container_grant
+                .filter(container_meta_id.eq(in_container_meta_id))
+                .filter(grantee.eq(in_grantee)),
+        )
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} grants of {} to {}", rows_deleted, in_container_meta_id, in_grantee);
+        Ok(rows_deleted > 0)
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_container_grants_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::container_grant::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(container_grant.filter(container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} grants of {}", rows_deleted, in_container_meta_id);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_thumbnails_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::thumbnail::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(thumbnail.filter(container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} thumbnails of {}", rows_deleted, in_container_meta_id);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_renditions_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::rendition::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(rendition.filter(container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} renditions of {}", rows_deleted, in_container_meta_id);
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_chapters_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::chapter::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(chapter.filter(container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} chapters of {}", rows_deleted, in_container_meta_id);
+        Ok(())
+    }
+    #[tracing::instrument(skip_all, fields(tenant_id = self.tenant_id.as_deref()))]
+    pub async fn delete_container_versions_by_container_meta_id(
+        &self,
+        in_container_meta_id: &Uuid,
+    ) -> Result<(), diesel::result::Error> {
+        use models::schema::container_version::dsl::*;
+
+        let mut pg_connection = self.get_connection().await?;
+        let rows_deleted = diesel::delete(container_version.filter(container_meta_id.eq(in_container_meta_id)))
+            .execute(&mut pg_connection).await?;
+
+        info!("Successfully deleted {} versions of {}", rows_deleted, in_container_meta_id);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -383,6 +1345,13 @@ mod tests {
 
     use super::*;
 
+    fn psql_config() -> PsqlConfig {
+        PsqlConfig {
+            url: std::env::var("DATABASE_URL").expect("DATABASE_URL not found in .cfg"),
+            ..Default::default()
+        }
+    }
+
     // In order to run the test execute: `RUST_LOG=info cargo test`
     #[tokio::test]
     async fn test_psql_data_access_methods_for_track() -> Result<(), Box<dyn std::error::Error>>{
@@ -391,7 +1360,7 @@ mod tests {
         let env_file_path = "./assets/psql-secrets.dev.cfg";
         dotenv::from_path(env_file_path).ok();
         
-        let psql_data_access = Box::new(PsqlDataAccess::new().await.unwrap());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant("tenant-a"));
 
         // file metainformation
         let mut video_track = VideoTrack {
@@ -403,6 +1372,10 @@ mod tests {
             height: 720,
             bit_rate: 1850,
             frame_rate: 60,
+            duration_in_ms: 30033,
+            timescale: 90000,
+            tenant_id: String::from("tenant-a"),
+            mp4_track_id: 1,
         };
 
         // [C]reate
@@ -428,6 +1401,308 @@ mod tests {
             psql_data_access.delete_video_track_by_id(&video_track.id).await;
         assert!(result.is_ok());
 
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_api_key() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap());
+        assert!(psql_data_access.check_health().await.is_ok());
+
+        let mut api_key = <models::api_key::ApiKey as models::model::Model>::new();
+        api_key.id = Uuid::new_v4();
+        api_key.name = String::from("ci upload");
+        api_key.subject = String::from("user-1");
+        api_key.key_prefix = api_key.id.simple().to_string()[..8].to_string();
+        api_key.key_hash = String::from("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+        api_key.tenant_id = String::from("tenant-a");
+
+        // [C]reate
+        let insert_result = psql_data_access.insert_api_key(&api_key).await;
+        assert!(insert_result.is_ok());
+
+        // [R]ead
+        let find_result = psql_data_access.find_active_api_key_by_prefix(&api_key.key_prefix).await;
+        assert_eq!(find_result.unwrap().unwrap().subject, "user-1");
+
+        // Revoke, only the owning subject may revoke a key
+        let revoke_result = psql_data_access.revoke_api_key_by_id(&api_key.id, "user-2").await;
+        assert!(revoke_result.unwrap().is_none());
+        let revoke_result = psql_data_access.revoke_api_key_by_id(&api_key.id, "user-1").await;
+        assert!(revoke_result.unwrap().is_some());
+        let find_result = psql_data_access.find_active_api_key_by_prefix(&api_key.key_prefix).await;
+        assert!(find_result.unwrap().is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_usage_ledger() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut usage_entry = <models::usage_entry::UsageEntry as models::model::Model>::new();
+        usage_entry.id = Uuid::new_v4();
+        usage_entry.tenant_id = tenant.clone();
+        usage_entry.subject = String::from("user-1");
+        usage_entry.container_meta_id = Uuid::new_v4();
+        usage_entry.bytes_delta = 5862561;
+        usage_entry.container_count_delta = 1;
+        usage_entry.duration_in_ms_delta = 30033;
+
+        // [C]reate
+        let insert_result = psql_data_access.insert_usage_entry(&usage_entry).await;
+        assert!(insert_result.is_ok());
+
+        // [R]ead
+        let usage = psql_data_access.get_usage(&tenant, None, None).await?;
+        assert_eq!(usage.bytes, 5862561);
+        assert_eq!(usage.container_count, 1);
+        let usage = psql_data_access.get_usage(&tenant, Some("user-2"), None).await?;
+        assert!(usage.is_empty());
+        let usage = psql_data_access.get_usage(&tenant, Some("user-1"), Some(&usage_entry.container_meta_id)).await?;
+        assert_eq!(usage.duration_in_ms, 30033);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_quarantined_upload() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut quarantined_upload = <models::quarantined_upload::QuarantinedUpload as models::model::Model>::new();
+        quarantined_upload.id = Uuid::new_v4();
+        quarantined_upload.tenant_id = tenant.clone();
+        quarantined_upload.subject = String::from("user-1");
+        quarantined_upload.original_file_name = String::from("invoice.mp4");
+        quarantined_upload.object_key = format!("quarantine/{}/invoice.mp4", quarantined_upload.id);
+        quarantined_upload.file_size_bytes = 4096;
+        quarantined_upload.reason_code = String::from("polyglot");
+        quarantined_upload.reason = String::from("The file contains a PDF header");
+
+        // [C]reate
+        let insert_result = psql_data_access.insert_quarantined_upload(&quarantined_upload).await;
+        assert!(insert_result.is_ok());
+
+        // [R]ead
+        let quarantined_uploads = psql_data_access.get_quarantined_uploads(&tenant, 10, 0).await?;
+        assert_eq!(quarantined_uploads.len(), 1);
+        assert_eq!(quarantined_uploads[0].reason_code, "polyglot");
+        let quarantined_uploads = psql_data_access.get_quarantined_uploads(&tenant, 10, 1).await?;
+        assert!(quarantined_uploads.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_audio_analysis() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut container_meta = <ContainerMeta as models::model::Model>::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.tenant_id = tenant.clone();
+        let mut audio_track = <AudioTrack as models::model::Model>::new();
+        audio_track.id = Uuid::new_v4();
+        audio_track.container_meta_id = container_meta.id;
+        audio_track.tenant_id = tenant.clone();
+        container_meta.audio_track_id = audio_track.id;
+        psql_data_access.insert_container_meta(&container_meta).await?;
+        psql_data_access.insert_audio_track(&audio_track).await?;
+        let pending_audio_tracks = psql_data_access.get_audio_tracks_pending_analysis(1000).await?;
+        assert!(pending_audio_tracks.iter().any(|pending_audio_track| pending_audio_track.id == audio_track.id));
+
+        // [U]pdate
+        audio_track.analysis_status = String::from("completed");
+        audio_track.integrated_loudness_lufs = Some(-23.0);
+        audio_track.true_peak_dbtp = Some(-1.0);
+        audio_track.loudness_range_lu = Some(7.5);
+        audio_track.waveform = Some(String::from("{\"version\":2}"));
+        let updated_audio_track = psql_data_access.update_audio_analysis_by_id(&audio_track.id, &audio_track).await?;
+        assert_eq!(updated_audio_track, audio_track);
+        let pending_audio_tracks = psql_data_access.get_audio_tracks_pending_analysis(1000).await?;
+        assert!(!pending_audio_tracks.iter().any(|pending_audio_track| pending_audio_track.id == audio_track.id));
+
+        psql_data_access.delete_tracks_by_container_meta_id(&container_meta.id).await?;
+        psql_data_access.delete_container_meta_by_id(&container_meta.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_rendition() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut container_meta = <ContainerMeta as models::model::Model>::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.tenant_id = tenant.clone();
+        psql_data_access.insert_container_meta(&container_meta).await?;
+        let pending_container_metas = psql_data_access.get_container_metas_pending_transcoding(1000).await?;
+        assert!(pending_container_metas.iter().any(|pending_container_meta| pending_container_meta.id == container_meta.id));
+
+        for (name, height) in [("480p", 480), ("720p", 720)] {
+            let mut rendition = <models::rendition::Rendition as models::model::Model>::new();
+            rendition.id = Uuid::new_v4();
+            rendition.container_meta_id = container_meta.id;
+            rendition.tenant_id = tenant.clone();
+            rendition.name = String::from(name);
+            rendition.blob_name = format!("{}/renditions/{}.mp4", container_meta.id, name);
+            rendition.mime_type = String::from("video/mp4");
+            rendition.height = height;
+            // [C]reate
+            let insert_result = psql_data_access.insert_rendition(&rendition).await;
+            assert!(insert_result.is_ok());
+        }
+
+        // [R]ead
+        let renditions = psql_data_access.get_renditions_by_container_meta_id(&container_meta.id).await?;
+        assert_eq!(renditions.iter().map(|rendition| rendition.name.as_str()).collect::<Vec<&str>>(), vec!["720p", "480p"]);
+
+        // [U]pdate
+        let updated_container_meta = psql_data_access.update_transcoding_status_by_id(&container_meta.id, "completed").await?;
+        assert_eq!(updated_container_meta.transcoding_status, "completed");
+        let pending_container_metas = psql_data_access.get_container_metas_pending_transcoding(1000).await?;
+        assert!(!pending_container_metas.iter().any(|pending_container_meta| pending_container_meta.id == container_meta.id));
+
+        // [D]elete
+        psql_data_access.delete_renditions_by_container_meta_id(&container_meta.id).await?;
+        assert!(psql_data_access.get_renditions_by_container_meta_id(&container_meta.id).await?.is_empty());
+        psql_data_access.delete_container_meta_by_id(&container_meta.id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_chapter() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut container_meta = <ContainerMeta as models::model::Model>::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.tenant_id = tenant.clone();
+        psql_data_access.insert_container_meta(&container_meta).await?;
+
+        let chapters: Vec<models::chapter::Chapter> = [("Main", 25000, 60000), ("Intro", 0, 25000)]
+            .into_iter()
+            .map(|(title, start_time_in_ms, end_time_in_ms)| {
+                let mut chapter = <models::chapter::Chapter as models::model::Model>::new();
+                chapter.id = Uuid::new_v4();
+                chapter.container_meta_id = container_meta.id;
+                chapter.tenant_id = tenant.clone();
+                chapter.title = String::from(title);
+                chapter.start_time_in_ms = start_time_in_ms;
+                chapter.end_time_in_ms = end_time_in_ms;
+                chapter
+            })
+            .collect();
+        // [C]reate
+        let inserted_chapters = psql_data_access.insert_chapters(&chapters).await?;
+        assert_eq!(inserted_chapters.len(), 2);
+
+        // [R]ead
+        let retrieved_chapters = psql_data_access.get_chapters_by_container_meta_id(&container_meta.id).await?;
+        assert_eq!(retrieved_chapters.iter().map(|chapter| chapter.title.as_str()).collect::<Vec<&str>>(), vec!["Intro", "Main"]);
+
+        // [D]elete
+        psql_data_access.delete_chapters_by_container_meta_id(&container_meta.id).await?;
+        assert!(psql_data_access.get_chapters_by_container_meta_id(&container_meta.id).await?.is_empty());
+        psql_data_access.delete_container_meta_by_id(&container_meta.id).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psql_data_access_methods_for_container_version() -> Result<(), Box<dyn std::error::Error>>{
+        let _ = env_logger::try_init();
+
+        let env_file_path = "./assets/psql-secrets.dev.cfg";
+        dotenv::from_path(env_file_path).ok();
+
+        let tenant = format!("tenant-{}", Uuid::new_v4().simple());
+        let psql_data_access = Box::new(PsqlDataAccess::new(&psql_config()).await.unwrap().for_tenant(&tenant));
+
+        let mut container_meta = <ContainerMeta as models::model::Model>::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.tenant_id = tenant.clone();
+        container_meta.object_key = container_meta.id.to_string() + "/simple_container.mp4";
+        psql_data_access.insert_container_meta(&container_meta).await?;
+
+        // [C]reate
+        for version in 1..=2 {
+            let mut container_version = <models::container_version::ContainerVersion as models::model::Model>::new();
+            container_version.id = Uuid::new_v4();
+            container_version.container_meta_id = container_meta.id;
+            container_version.tenant_id = tenant.clone();
+            container_version.version = version;
+            container_version.object_key = format!("{}/versions/{}/simple_container.mp4", container_meta.id, version);
+            psql_data_access.insert_container_version(&container_version).await?;
+        }
+
+        // [R]ead
+        let container_versions = psql_data_access.get_container_versions_by_container_meta_id(&container_meta.id).await?;
+        assert_eq!(container_versions.iter().map(|container_version| container_version.version).collect::<Vec<i32>>(), vec![2, 1]);
+        let container_version = psql_data_access.find_container_version(&container_meta.id, 2).await?.unwrap();
+        assert_eq!(container_version.object_key, format!("{}/versions/2/simple_container.mp4", container_meta.id));
+        assert!(psql_data_access.find_container_version(&container_meta.id, 3).await?.is_none());
+
+        // [U]pdate
+        container_meta.version = container_version.version;
+        container_meta.object_key = container_version.object_key.clone();
+        let updated_container_meta = psql_data_access.update_container_file_by_id(&container_meta.id, &container_meta).await?;
+        assert_eq!(updated_container_meta.version, 2);
+        assert_eq!(updated_container_meta.object_key, container_version.object_key);
+        container_meta.title = String::from("Rewritten");
+        container_meta.file_size_bytes = 4096;
+        container_meta.object_key = format!("{}/versions/2/rewritten/simple_container.mp4", container_meta.id);
+        let mut usage_entry = <models::usage_entry::UsageEntry as models::model::Model>::new();
+        usage_entry.id = Uuid::new_v4();
+        usage_entry.tenant_id = tenant.clone();
+        usage_entry.container_meta_id = container_meta.id;
+        usage_entry.bytes_delta = 4096 - container_version.file_size_bytes;
+        let updated_container_meta = psql_data_access
+            .update_rewritten_container_file_by_id(&container_meta.id, &container_meta, &usage_entry)
+            .await?;
+        assert_eq!(updated_container_meta.title, "Rewritten");
+        assert_eq!(updated_container_meta.object_key, container_meta.object_key);
+        let container_version = psql_data_access.find_container_version(&container_meta.id, 2).await?.unwrap();
+        assert_eq!(container_version.file_size_bytes, 4096);
+        assert_eq!(container_version.object_key, container_meta.object_key);
+
+        // [D]elete
+        psql_data_access.delete_container_versions_by_container_meta_id(&container_meta.id).await?;
+        assert!(psql_data_access.get_container_versions_by_container_meta_id(&container_meta.id).await?.is_empty());
+        psql_data_access.delete_container_meta_by_id(&container_meta.id).await?;
+
         Ok(())
     }
 }
@@ -0,0 +1,77 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::api_key::ApiKey;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateApiKeyDto {
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiKeyDto {
+    pub id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub date_time_created: String,
+    // only set in the response creating the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+impl From<&ApiKey> for ApiKeyDto {
+    fn from(api_key: &ApiKey) -> Self {
+        ApiKeyDto {
+            id: api_key.id.to_string(),
+            name: api_key.name.clone(),
+            key_prefix: api_key.key_prefix.clone(),
+            date_time_created: api_key.date_time_created.to_rfc3339(),
+            api_key: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_api_key_dto_from_api_key() {
+        let mut api_key = ApiKey::new();
+        api_key.id = Uuid::new_v4();
+        api_key.name = String::from("ci upload");
+        api_key.key_prefix = String::from("0a1b2c3d");
+        api_key.key_hash = String::from("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+
+        let api_key_dto = ApiKeyDto::from(&api_key);
+        assert_eq!(api_key_dto.id, api_key.id.to_string());
+        assert_eq!(api_key_dto.name, "ci upload");
+        assert_eq!(api_key_dto.key_prefix, "0a1b2c3d");
+        assert_eq!(api_key_dto.api_key, None);
+    }
+}
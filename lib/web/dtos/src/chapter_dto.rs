@@ -0,0 +1,76 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::chapter::Chapter;
+use serde::{Deserialize, Serialize};
+
+// a chapter without an end lasts until the next chapter starts or the container ends
+#[derive(Deserialize, Debug)]
+pub struct UpdateChapterDto {
+    pub title: String,
+    pub start_time_in_ms: i64,
+    pub end_time_in_ms: Option<i64>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ChapterDto {
+    pub id: String,
+    pub title: String,
+    pub start_time_in_ms: i64,
+    pub end_time_in_ms: i64,
+}
+
+impl From<&Chapter> for ChapterDto {
+    fn from(chapter: &Chapter) -> Self {
+        ChapterDto {
+            id: chapter.id.to_string(),
+            title: chapter.title.clone(),
+            start_time_in_ms: chapter.start_time_in_ms,
+            end_time_in_ms: chapter.end_time_in_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_chapter_dto_from_chapter() {
+        let mut chapter = Chapter::new();
+        chapter.id = Uuid::new_v4();
+        chapter.container_meta_id = Uuid::new_v4();
+        chapter.title = String::from("Intro");
+        chapter.start_time_in_ms = 0;
+        chapter.end_time_in_ms = 25000;
+
+        let chapter_dto = ChapterDto::from(&chapter);
+        assert_eq!(chapter_dto.id, chapter.id.to_string());
+        assert_eq!(chapter_dto.title, "Intro");
+        assert_eq!((chapter_dto.start_time_in_ms, chapter_dto.end_time_in_ms), (0, 25000));
+    }
+}
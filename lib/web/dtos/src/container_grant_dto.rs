@@ -0,0 +1,82 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+use models::container_grant::ContainerGrant;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateContainerGrantDto {
+    pub grantee: String,
+    // `view` or `edit`
+    pub permission: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContainerGrantDto {
+    pub id: String,
+    pub container_meta_id: String,
+    pub grantee: String,
+    pub permission: String,
+    pub granted_by: String,
+    pub date_time_created: String,
+}
+
+impl From<&ContainerGrant> for ContainerGrantDto {
+    fn from(container_grant: &ContainerGrant) -> Self {
+        ContainerGrantDto {
+            id: container_grant.id.to_string(),
+            container_meta_id: container_grant.container_meta_id.to_string(),
+            grantee: container_grant.grantee.clone(),
+            permission: container_grant.permission.clone(),
+            granted_by: container_grant.granted_by.clone(),
+            date_time_created: container_grant.date_time_created.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::{container_grant::PERMISSION_EDIT, model::Model};
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_container_grant_dto_from_container_grant() {
+        let mut container_grant = ContainerGrant::new();
+        container_grant.id = Uuid::new_v4();
+        container_grant.container_meta_id = Uuid::new_v4();
+        container_grant.grantee = String::from("user-2");
+        container_grant.permission = String::from(PERMISSION_EDIT);
+        container_grant.granted_by = String::from("user-1");
+
+        let container_grant_dto = ContainerGrantDto::from(&container_grant);
+        assert_eq!(container_grant_dto.id, container_grant.id.to_string());
+        assert_eq!(container_grant_dto.container_meta_id, container_grant.container_meta_id.to_string());
+        assert_eq!(container_grant_dto.grantee, "user-2");
+        assert_eq!(container_grant_dto.permission, "edit");
+        assert_eq!(container_grant_dto.granted_by, "user-1");
+    }
+}
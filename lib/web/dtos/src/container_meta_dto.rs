@@ -0,0 +1,134 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::container_meta::ContainerMeta;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContainerMetaDto {
+    pub id: String,
+    pub date_time_created: String,
+    pub date_time_updated: String,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub original_file_name: String,
+    pub mime_type: String,
+    pub major_brand: String,
+    pub compatible_brands: Vec<String>,
+    pub file_size_bytes: i64,
+    pub duration_ms: i64,
+    pub owner_id: String,
+    pub scan_verdict: String,
+    pub transcoding_status: String,
+    pub media_created_at: Option<String>,
+    pub web_optimized: bool,
+    pub parent_container_id: Option<String>,
+    pub version: i32,
+    pub url: String,
+}
+
+// the clip is widened to the surrounding keyframes, without a title the one of the source is used
+#[derive(Deserialize, Debug)]
+pub struct CreateClipDto {
+    pub start_time_in_ms: u64,
+    pub end_time_in_ms: u64,
+    pub title: Option<String>,
+}
+
+// fields left out of the request body stay unchanged
+#[derive(Deserialize, Debug)]
+pub struct UpdateContainerMetaDto {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl From<&ContainerMeta> for ContainerMetaDto {
+    fn from(container_meta: &ContainerMeta) -> Self {
+        ContainerMetaDto {
+            id: container_meta.id.to_string(),
+            date_time_created: container_meta.date_time_created.to_rfc3339(),
+            date_time_updated: container_meta.date_time_updated.to_rfc3339(),
+            title: container_meta.title.clone(),
+            description: container_meta.description.clone(),
+            tags: container_meta.tags.iter().flatten().cloned().collect(),
+            original_file_name: container_meta.original_file_name.clone(),
+            mime_type: container_meta.mime_type.clone(),
+            major_brand: container_meta.major_brand.clone(),
+            compatible_brands: container_meta.compatible_brands.iter().flatten().cloned().collect(),
+            file_size_bytes: container_meta.file_size_bytes,
+            duration_ms: container_meta.duration_in_ms,
+            owner_id: container_meta.owner_id.clone(),
+            scan_verdict: container_meta.scan_verdict.clone(),
+            transcoding_status: container_meta.transcoding_status.clone(),
+            media_created_at: container_meta.media_created_at.map(|media_created_at| media_created_at.to_rfc3339()),
+            web_optimized: container_meta.web_optimized,
+            parent_container_id: container_meta.parent_container_id.map(|parent_container_id| parent_container_id.to_string()),
+            version: container_meta.version,
+            url: format!("/api/v1/mms/containers/{}/file", container_meta.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_container_meta_dto_from_container_meta() {
+        let mut container_meta = ContainerMeta::new();
+        container_meta.id = Uuid::new_v4();
+        container_meta.tags = vec![Some(String::from("Nature")), None];
+        container_meta.original_file_name = String::from("nature2.mp4");
+        container_meta.mime_type = String::from("video/mp4");
+        container_meta.major_brand = String::from("isom");
+        container_meta.compatible_brands = vec![Some(String::from("isom")), Some(String::from("mp41"))];
+        container_meta.object_key = container_meta.id.to_string() + "/nature2.mp4";
+        container_meta.file_size_bytes = 5862561;
+        container_meta.duration_in_ms = 30033;
+        container_meta.owner_id = String::from("user-1");
+
+        let container_meta_dto = ContainerMetaDto::from(&container_meta);
+        assert_eq!(container_meta_dto.id, container_meta.id.to_string());
+        assert_eq!(container_meta_dto.tags, vec![String::from("Nature")]);
+        assert_eq!(container_meta_dto.original_file_name, "nature2.mp4");
+        assert_eq!(container_meta_dto.mime_type, "video/mp4");
+        assert_eq!(container_meta_dto.major_brand, "isom");
+        assert_eq!(container_meta_dto.compatible_brands.len(), 2);
+        assert_eq!(container_meta_dto.file_size_bytes, 5862561);
+        assert_eq!(container_meta_dto.duration_ms, 30033);
+        assert_eq!(container_meta_dto.owner_id, "user-1");
+        assert_eq!(container_meta_dto.scan_verdict, "not_scanned");
+        assert_eq!(container_meta_dto.transcoding_status, "pending");
+        assert_eq!(container_meta_dto.media_created_at, None);
+        assert!(!container_meta_dto.web_optimized);
+        assert_eq!(container_meta_dto.parent_container_id, None);
+        assert_eq!(container_meta_dto.version, 1);
+        assert_eq!(container_meta_dto.url, format!("/api/v1/mms/containers/{}/file", container_meta.id));
+    }
+}
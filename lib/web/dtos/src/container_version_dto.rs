@@ -0,0 +1,94 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::container_version::ContainerVersion;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ContainerVersionDto {
+    pub version: i32,
+    pub original_file_name: String,
+    pub mime_type: String,
+    pub file_size_bytes: i64,
+    pub duration_ms: i64,
+    pub scan_verdict: String,
+    pub web_optimized: bool,
+    pub created_by: String,
+    pub date_time_created: String,
+    pub url: String,
+}
+
+impl From<&ContainerVersion> for ContainerVersionDto {
+    fn from(container_version: &ContainerVersion) -> Self {
+        ContainerVersionDto {
+            version: container_version.version,
+            original_file_name: container_version.original_file_name.clone(),
+            mime_type: container_version.mime_type.clone(),
+            file_size_bytes: container_version.file_size_bytes,
+            duration_ms: container_version.duration_in_ms,
+            scan_verdict: container_version.scan_verdict.clone(),
+            web_optimized: container_version.web_optimized,
+            created_by: container_version.created_by.clone(),
+            date_time_created: container_version.date_time_created.to_rfc3339(),
+            url: format!(
+                "/api/v1/mms/containers/{}/file?version={}",
+                container_version.container_meta_id, container_version.version
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_container_version_dto_from_container_version() {
+        let mut container_version = ContainerVersion::new();
+        container_version.id = Uuid::new_v4();
+        container_version.container_meta_id = Uuid::new_v4();
+        container_version.version = 2;
+        container_version.original_file_name = String::from("nature2-final.mp4");
+        container_version.mime_type = String::from("video/mp4");
+        container_version.file_size_bytes = 5862561;
+        container_version.duration_in_ms = 30033;
+        container_version.created_by = String::from("user-1");
+
+        let container_version_dto = ContainerVersionDto::from(&container_version);
+        assert_eq!(container_version_dto.version, 2);
+        assert_eq!(container_version_dto.original_file_name, "nature2-final.mp4");
+        assert_eq!(container_version_dto.mime_type, "video/mp4");
+        assert_eq!(container_version_dto.file_size_bytes, 5862561);
+        assert_eq!(container_version_dto.duration_ms, 30033);
+        assert_eq!(container_version_dto.scan_verdict, "not_scanned");
+        assert_eq!(container_version_dto.created_by, "user-1");
+        assert_eq!(
+            container_version_dto.url,
+            format!("/api/v1/mms/containers/{}/file?version=2", container_version.container_meta_id)
+        );
+    }
+}
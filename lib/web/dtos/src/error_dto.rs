@@ -0,0 +1,55 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+use serde::Serialize;
+
+/// Body of error responses which the client can act on, `error` being a stable machine readable code.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ErrorDto {
+    pub error: String,
+    pub message: String,
+}
+
+impl ErrorDto {
+    pub fn new(error: &str, message: &str) -> Self {
+        ErrorDto {
+            error: String::from(error),
+            message: String::from(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_error_dto() {
+        let error_dto = ErrorDto::new("quota_exceeded", "The user storage quota of 100 would be exceeded");
+        assert_eq!(error_dto.error, "quota_exceeded");
+        assert_eq!(error_dto.message, "The user storage quota of 100 would be exceeded");
+    }
+}
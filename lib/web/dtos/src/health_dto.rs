@@ -0,0 +1,87 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct HealthCheckDto {
+    pub name: String,
+    pub status: String,
+}
+
+/// Body of `/healthz` and `/readyz`. Failure details are logged rather than exposed to unauthenticated callers.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct HealthDto {
+    pub status: String,
+    pub checks: Vec<HealthCheckDto>,
+}
+
+fn status(is_healthy: bool) -> String {
+    String::from(if is_healthy { "up" } else { "down" })
+}
+
+impl HealthCheckDto {
+    pub fn new(name: &str, is_healthy: bool) -> Self {
+        HealthCheckDto {
+            name: String::from(name),
+            status: status(is_healthy),
+        }
+    }
+}
+
+impl HealthDto {
+    pub fn new(checks: Vec<HealthCheckDto>) -> Self {
+        HealthDto {
+            status: status(checks.iter().all(|check| check.status == "up")),
+            checks,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_health_dto() -> Result<(), Box<dyn std::error::Error>> {
+        let health_dto = HealthDto::new(vec![]);
+        assert!(health_dto.is_healthy());
+
+        let health_dto = HealthDto::new(vec![
+            HealthCheckDto::new("database", true),
+            HealthCheckDto::new("blob_storage", false),
+        ]);
+        assert!(!health_dto.is_healthy());
+        assert_eq!(
+            serde_json::to_string(&health_dto)?,
+            r#"{"status":"down","checks":[{"name":"database","status":"up"},{"name":"blob_storage","status":"down"}]}"#
+        );
+        Ok(())
+    }
+}
@@ -1 +1,11 @@
-// pub mod ...;
+pub mod api_key_dto;
+pub mod chapter_dto;
+pub mod container_grant_dto;
+pub mod container_meta_dto;
+pub mod container_version_dto;
+pub mod error_dto;
+pub mod health_dto;
+pub mod quarantined_upload_dto;
+pub mod thumbnail_dto;
+pub mod rendition_dto;
+pub mod usage_dto;
@@ -0,0 +1,80 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::quarantined_upload::QuarantinedUpload;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct QuarantinedUploadDto {
+    pub id: String,
+    pub subject: String,
+    pub original_file_name: String,
+    pub object_key: String,
+    pub file_size_bytes: i64,
+    pub reason_code: String,
+    pub reason: String,
+    pub date_time_created: String,
+}
+
+impl From<&QuarantinedUpload> for QuarantinedUploadDto {
+    fn from(quarantined_upload: &QuarantinedUpload) -> Self {
+        QuarantinedUploadDto {
+            id: quarantined_upload.id.to_string(),
+            subject: quarantined_upload.subject.clone(),
+            original_file_name: quarantined_upload.original_file_name.clone(),
+            object_key: quarantined_upload.object_key.clone(),
+            file_size_bytes: quarantined_upload.file_size_bytes,
+            reason_code: quarantined_upload.reason_code.clone(),
+            reason: quarantined_upload.reason.clone(),
+            date_time_created: quarantined_upload.date_time_created.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_quarantined_upload_dto_from_quarantined_upload() {
+        let mut quarantined_upload = QuarantinedUpload::new();
+        quarantined_upload.id = Uuid::new_v4();
+        quarantined_upload.subject = String::from("user-1");
+        quarantined_upload.original_file_name = String::from("invoice.mp4");
+        quarantined_upload.object_key = format!("quarantine/{}/invoice.mp4", quarantined_upload.id);
+        quarantined_upload.file_size_bytes = 4096;
+        quarantined_upload.reason_code = String::from("polyglot");
+        quarantined_upload.reason = String::from("The file contains a PDF header");
+
+        let quarantined_upload_dto = QuarantinedUploadDto::from(&quarantined_upload);
+        assert_eq!(quarantined_upload_dto.id, quarantined_upload.id.to_string());
+        assert_eq!(quarantined_upload_dto.original_file_name, "invoice.mp4");
+        assert_eq!(quarantined_upload_dto.file_size_bytes, 4096);
+        assert_eq!(quarantined_upload_dto.reason_code, "polyglot");
+        assert_eq!(quarantined_upload_dto.date_time_created, quarantined_upload.date_time_created.to_rfc3339());
+    }
+}
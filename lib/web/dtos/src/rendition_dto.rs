@@ -0,0 +1,90 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+use models::rendition::Rendition;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RenditionDto {
+    pub name: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub video_bit_rate_kbps: i32,
+    pub audio_bit_rate_kbps: i32,
+    pub file_size_bytes: i64,
+    pub url: String,
+}
+
+impl From<&Rendition> for RenditionDto {
+    fn from(rendition: &Rendition) -> Self {
+        RenditionDto {
+            name: rendition.name.clone(),
+            mime_type: rendition.mime_type.clone(),
+            width: rendition.width,
+            height: rendition.height,
+            video_bit_rate_kbps: rendition.video_bit_rate_kbps,
+            audio_bit_rate_kbps: rendition.audio_bit_rate_kbps,
+            file_size_bytes: rendition.file_size_bytes,
+            url: format!(
+                "/api/v1/mms/containers/{}/renditions/{}",
+                rendition.container_meta_id, rendition.name
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_rendition_dto_from_rendition() {
+        let mut rendition = Rendition::new();
+        rendition.id = Uuid::new_v4();
+        rendition.container_meta_id = Uuid::new_v4();
+        rendition.name = String::from("480p");
+        rendition.blob_name = rendition.container_meta_id.to_string() + "/renditions/480p.mp4";
+        rendition.mime_type = String::from("video/mp4");
+        rendition.width = 854;
+        rendition.height = 480;
+        rendition.video_bit_rate_kbps = 1400;
+        rendition.audio_bit_rate_kbps = 96;
+        rendition.file_size_bytes = 5242880;
+
+        let rendition_dto = RenditionDto::from(&rendition);
+        assert_eq!(rendition_dto.name, "480p");
+        assert_eq!(rendition_dto.mime_type, "video/mp4");
+        assert_eq!((rendition_dto.width, rendition_dto.height), (854, 480));
+        assert_eq!((rendition_dto.video_bit_rate_kbps, rendition_dto.audio_bit_rate_kbps), (1400, 96));
+        assert_eq!(rendition_dto.file_size_bytes, 5242880);
+        assert_eq!(
+            rendition_dto.url,
+            format!("/api/v1/mms/containers/{}/renditions/480p", rendition.container_meta_id)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+use models::thumbnail::Thumbnail;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ThumbnailDto {
+    pub name: String,
+    pub mime_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub time_offset_in_ms: i64,
+    pub url: String,
+}
+
+impl From<&Thumbnail> for ThumbnailDto {
+    fn from(thumbnail: &Thumbnail) -> Self {
+        ThumbnailDto {
+            name: thumbnail.name.clone(),
+            mime_type: thumbnail.mime_type.clone(),
+            width: thumbnail.width,
+            height: thumbnail.height,
+            time_offset_in_ms: thumbnail.time_offset_in_ms,
+            url: format!(
+                "/api/v1/mms/containers/{}/thumbnails/{}",
+                thumbnail.container_meta_id, thumbnail.name
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::model::Model;
+    use uuid::Uuid;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_thumbnail_dto_from_thumbnail() {
+        let mut thumbnail = Thumbnail::new();
+        thumbnail.id = Uuid::new_v4();
+        thumbnail.container_meta_id = Uuid::new_v4();
+        thumbnail.name = String::from("sprite.jpg");
+        thumbnail.blob_name = thumbnail.container_meta_id.to_string() + "/thumbnails/sprite.jpg";
+        thumbnail.mime_type = String::from("image/jpeg");
+        thumbnail.width = 1600;
+        thumbnail.height = 450;
+
+        let thumbnail_dto = ThumbnailDto::from(&thumbnail);
+        assert_eq!(thumbnail_dto.name, "sprite.jpg");
+        assert_eq!(thumbnail_dto.mime_type, "image/jpeg");
+        assert_eq!(thumbnail_dto.width, 1600);
+        assert_eq!(thumbnail_dto.height, 450);
+        assert_eq!(
+            thumbnail_dto.url,
+            format!("/api/v1/mms/containers/{}/thumbnails/sprite.jpg", thumbnail.container_meta_id)
+        );
+    }
+}
@@ -0,0 +1,80 @@
+// The MIT License
+// 
+// Copyright (c) 2024 MGTheTrain
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+
+
+
+use models::usage_entry::Usage;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UsageDto {
+    pub bytes: i64,
+    pub container_count: i64,
+    pub duration_ms: i64,
+}
+
+impl From<&Usage> for UsageDto {
+    fn from(usage: &Usage) -> Self {
+        UsageDto {
+            bytes: usage.bytes,
+            container_count: usage.container_count,
+            duration_ms: usage.duration_in_ms,
+        }
+    }
+}
+
+/// Limits are omitted when unlimited.
+#[derive(Serialize, Debug, PartialEq, Default)]
+pub struct QuotaDto {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_containers: Option<i64>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UsageReportDto {
+    pub tenant_id: String,
+    pub subject: String,
+    pub tenant: UsageDto,
+    pub user: UsageDto,
+    pub tenant_quota: QuotaDto,
+    pub user_quota: QuotaDto,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // In order to run the test execute: `RUST_LOG=info cargo test`
+    #[test]
+    fn test_usage_dto_from_usage() {
+        let usage = Usage { bytes: 5862561, container_count: 1, duration_in_ms: 30033 };
+
+        let usage_dto = UsageDto::from(&usage);
+        assert_eq!(usage_dto.bytes, 5862561);
+        assert_eq!(usage_dto.container_count, 1);
+        assert_eq!(usage_dto.duration_ms, 30033);
+        assert_eq!(serde_json::to_string(&QuotaDto::default()).unwrap(), "{}");
+    }
+}
@@ -22,103 +22,1915 @@
 
 // See web::data example: https://github.com/actix/examples/blob/master/databases/diesel/src/main.rs
 
-use actix_multipart::{
-    form::{tempfile::TempFile, MultipartForm},
-    Multipart,
-};
+mod telemetry;
+
+use actix_multipart::{Field, Multipart};
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, ContentDisposition};
+use actix_web::http::Method;
 use actix_web::{
-    middleware, post,
-    web::{self, Bytes},
-    App, Error, HttpResponse, HttpResponseBuilder, HttpServer,
+    delete, get, middleware, patch, post, put, web, App, HttpMessage, HttpRequest, HttpResponse,
+    HttpServer,
 };
+use futures_util::StreamExt;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use models::principal::{AuthenticationMethod, Principal};
+use serde::Deserialize;
+use services::authorization::AuthorizationError;
+use services::byte_range::{ByteRange, RangeNotSatisfiableError};
+use services::chapters::ChapterValidationError;
+use services::clipping::ClipRejectedError;
+use services::quota::QuotaExceededError;
+use services::rate_limiter::RateLimiter;
+use services::scanning::ScanUnavailableError;
+use services::track_export::TrackExportRejectedError;
+use services::upload_validation::{self, UploadRejection};
 
-use std::io::Read;
-use std::{
-    fmt,
-    fs::{self, File},
-};
+use std::fs;
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+struct VersionQuery {
+    version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+fn list_parameters_from(query: &ListQuery) -> services::update_parameters::ListParameters {
+    let mut list_parameters = services::update_parameters::ListParameters::new();
+    if let Some(limit) = query.limit {
+        list_parameters.limit = limit.clamp(1, 500);
+    }
+    if let Some(offset) = query.offset {
+        list_parameters.offset = offset.max(0);
+    }
+    list_parameters
+}
+
+// multipart field carrying the uploaded files, repeated for each file
+const UPLOAD_FIELD_NAME: &str = "file";
+const UPLOAD_TITLE_FIELD_NAME: &str = "title";
+const UPLOAD_DESCRIPTION_FIELD_NAME: &str = "description";
+const UPLOAD_TAGS_FIELD_NAME: &str = "tags";
+
+/// Token buckets of the rate limited routes, shared by all workers.
+struct RateLimiters {
+    upload: RateLimiter,
+    download: RateLimiter,
+}
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// probed by the orchestrator and scraped by Prometheus, which hold no credentials.
+// Hence these must not be exposed beyond the cluster.
+const UNAUTHENTICATED_PATHS: [&str; 3] = ["/healthz", "/readyz", "/metrics"];
+
+// upper bounds in seconds of the latency histograms, uploads and downloads of large files taking minutes
+const HISTOGRAM_BUCKETS: [f64; 15] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+// bounds each dependency check of `/readyz`
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs the request within a span carrying its request id, taken from `X-Request-Id` if the caller,
+/// e.g. a gateway, provides a valid one and generated otherwise. The id is returned in the response.
+async fn trace_request(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.path(),
+        subject = tracing::field::Empty,
+        tenant_id = tracing::field::Empty,
+        status = tracing::field::Empty,
+    );
+
+    let started = std::time::Instant::now();
+    let mut res = next.call(req).instrument(span.clone()).await?;
+    span.record("status", res.status().as_u16());
+    span.in_scope(|| {
+        tracing::info!(
+            duration_in_ms = started.elapsed().as_millis() as u64,
+            "Finished request"
+        )
+    });
+    // valid header value, since it is either validated or a generated uuid
+    if let Ok(header_value) = header::HeaderValue::from_str(&request_id) {
+        res.headers_mut().insert(
+            header::HeaderName::from_static(REQUEST_ID_HEADER),
+            header_value,
+        );
+    }
+    Ok(res)
+}
+
+// rejects ids which would allow injecting into logs or headers
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= 128
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c))
+}
+
+/// Counts requests and records their latency by method, route pattern and status.
+/// The route pattern, e.g. `/api/v1/mms/containers/{id}`, keeps the label cardinality bounded.
+async fn record_http_metrics(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let started = std::time::Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .match_pattern()
+        .unwrap_or_else(|| String::from("unmatched"));
+    let result = next.call(req).await;
+    let status = match &result {
+        Ok(res) => res.status().as_u16().to_string(),
+        Err(err) => err.as_response_error().status_code().as_u16().to_string(),
+    };
+    let labels = [("method", method), ("route", route), ("status", status)];
+    metrics::counter!("mms_http_requests_total", &labels).increment(1);
+    metrics::histogram!("mms_http_request_duration_seconds", &labels)
+        .record(started.elapsed().as_secs_f64());
+    result
+}
 
-#[derive(Debug, MultipartForm)]
-struct UploadForm {
-    #[multipart(rename = "file")]
-    files: Vec<TempFile>,
+/// Accepts `Authorization: Bearer <jwt>` or `X-Api-Key: <api key>` and attaches the principal to the request.
+/// The tenant is taken from the credential. Credentials not bound to a tenant stay in the default tenant unless their
+/// subject is configured in `tenancy.cross_tenant_subjects`, which may select a tenant with `X-Tenant-Id`.
+async fn authenticate(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if UNAUTHENTICATED_PATHS.contains(&req.path()) {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+    let authentication_service = req
+        .app_data::<web::Data<services::authentication_service::AuthenticationService>>()
+        .unwrap()
+        .clone();
+    let bearer_token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(String::from);
+    let api_key = req
+        .headers()
+        .get("X-Api-Key")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+    let requested_tenant_id = req
+        .headers()
+        .get("X-Tenant-Id")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let principal = match (bearer_token, api_key) {
+        (Some(bearer_token), _) => authentication_service.authenticate_bearer_token(&bearer_token),
+        (None, Some(api_key)) => authentication_service.authenticate_api_key(&api_key).await,
+        (None, None) => Err("No credentials provided".into()),
+    };
+    match principal {
+        Ok(mut principal) => {
+            if let Err(err) = services::authorization::resolve_tenant(
+                &mut principal,
+                requested_tenant_id.as_deref(),
+                &authentication_service.cross_tenant_subjects,
+            ) {
+                log::warn!(
+                    "Rejected request of {} to {}: {}",
+                    principal.subject,
+                    req.path(),
+                    err
+                );
+                return Ok(req
+                    .into_response(HttpResponse::Forbidden().finish())
+                    .map_into_right_body());
+            }
+            tracing::Span::current()
+                .record("subject", principal.subject.as_str())
+                .record("tenant_id", principal.resolved_tenant_id());
+            req.extensions_mut().insert(principal);
+            Ok(next.call(req).await?.map_into_left_body())
+        }
+        Err(err) => {
+            log::warn!("Rejected request to {}: {}", req.path(), err);
+            Ok(req
+                .into_response(
+                    HttpResponse::Unauthorized()
+                        .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                        .finish(),
+                )
+                .map_into_right_body())
+        }
+    }
 }
 
+/// Rejects uploads and downloads exceeding the rate limit of the principal with 429, before the body is read.
+/// Requests without a principal are limited by the client address.
+async fn limit_rate(
+    req: ServiceRequest,
+    next: middleware::Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let rate_limiters = req.app_data::<web::Data<RateLimiters>>().unwrap().clone();
+    let rate_limiter = match (req.method(), req.match_pattern().as_deref()) {
+        (&Method::POST, Some("/api/v1/mms/upload"))
+        | (&Method::PUT, Some("/api/v1/mms/containers/{id}/file")) => {
+            Some(("upload", &rate_limiters.upload))
+        }
+        (
+            &Method::GET,
+            Some(
+                "/api/v1/mms/containers/{id}/file"
+                | "/api/v1/mms/containers/{id}/thumbnails/{name}"
+                | "/api/v1/mms/containers/{id}/renditions/{name}"
+                | "/api/v1/mms/containers/{id}/tracks/{track_id}/export",
+            ),
+        ) => Some(("download", &rate_limiters.download)),
+        _ => None,
+    };
+    if let Some((route_class, rate_limiter)) = rate_limiter {
+        let key = match req.extensions().get::<Principal>() {
+            Some(principal) => format!("{}/{}", principal.resolved_tenant_id(), principal.subject),
+            None => req
+                .peer_addr()
+                .map(|peer_addr| peer_addr.ip().to_string())
+                .unwrap_or_default(),
+        };
+        if let Err(retry_after) = rate_limiter.check(&key) {
+            metrics::counter!("mms_rate_limited_requests_total", "route_class" => route_class)
+                .increment(1);
+            log::warn!("Rate limited {} request of {}", route_class, key);
+            let retry_after_in_secs = retry_after.as_secs_f64().ceil() as u64;
+            return Ok(req
+                .into_response(
+                    HttpResponse::TooManyRequests()
+                        .insert_header((header::RETRY_AFTER, retry_after_in_secs.max(1)))
+                        .json(dtos::error_dto::ErrorDto::new(
+                            "rate_limited",
+                            &format!(
+                                "Too many {} requests, retry in {}s",
+                                route_class,
+                                retry_after_in_secs.max(1)
+                            ),
+                        )),
+                )
+                .map_into_right_body());
+        }
+    }
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+/// Prometheus text exposition of the metrics recorded since startup.
+#[get("/metrics")]
+async fn get_metrics(
+    prometheus_handle: web::Data<PrometheusHandle>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> HttpResponse {
+    multimedia_management_service.record_metrics();
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(prometheus_handle.render())
+}
+
+/// Liveness, answering as long as the server accepts requests.
+#[get("/healthz")]
+async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(dtos::health_dto::HealthDto::new(vec![]))
+}
+
+/// Readiness, answering 503 while the database or the blob storage is unreachable.
+#[get("/readyz")]
+async fn readyz(
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> HttpResponse {
+    let health_checks = multimedia_management_service
+        .check_readiness(READINESS_CHECK_TIMEOUT)
+        .await;
+    for health_check in health_checks
+        .iter()
+        .filter(|health_check| !health_check.is_healthy())
+    {
+        log::warn!(
+            "Readiness check {} failed: {}",
+            health_check.name,
+            health_check.error.as_deref().unwrap_or_default()
+        );
+    }
+    let health_dto = dtos::health_dto::HealthDto::new(
+        health_checks
+            .iter()
+            .map(|health_check| {
+                dtos::health_dto::HealthCheckDto::new(&health_check.name, health_check.is_healthy())
+            })
+            .collect(),
+    );
+    match health_dto.is_healthy() {
+        true => HttpResponse::Ok().json(health_dto),
+        false => HttpResponse::ServiceUnavailable().json(health_dto),
+    }
+}
+
+/// Streams each `file` field to the temp dir and stores them once the whole request has been received.
+/// Limits on the number, type and size of files are checked while reading, so a rejected request stores nothing.
+/// The optional `title`, `description` and comma separated `tags` fields apply to every file,
+/// metadata embedded in the files fills in whatever is left empty.
 #[post("/api/v1/mms/upload")]
 async fn upload_blob(
-    MultipartForm(form): MultipartForm<UploadForm>,
+    req: HttpRequest,
+    mut payload: Multipart,
+    principal: web::ReqData<Principal>,
+    upload_config: web::Data<services::app_config::UploadConfig>,
     multimedia_management_service: web::Data<
         services::mutimedia_management_service::MutimediaManagementService,
     >,
 ) -> Result<HttpResponse, actix_web::Error> {
-    for f in form.files {
-        // create ./tmp required for file uploads
-        if let Err(err) = fs::create_dir_all("./tmp") {
-            log::error!("Failed to create directory: {}", err);
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if let Some(content_length) = content_length {
+        if let Err(rejection) =
+            upload_validation::check_request_size(&upload_config, content_length)
+        {
+            return Ok(reject_upload(rejection));
         }
+    }
+    let temp_dir = create_upload_temp_dir(&upload_config);
 
-        let file_name = f.file_name.unwrap();
-        let mut current_dir_str = String::from("");
-        if let Ok(current_dir) = std::env::current_dir() {
-            current_dir_str = current_dir.to_string_lossy().to_string();
+    // (temp file path, file name) of each received file
+    let mut received_files: Vec<(String, String)> = Vec::new();
+    let mut upload_meta_parameters = services::upload_parameters::UploadMetaParameters::new();
+    let received = receive_upload_files(
+        &mut payload,
+        &temp_dir,
+        &upload_config,
+        &mut received_files,
+        &mut upload_meta_parameters,
+    )
+    .await;
+    if !matches!(received, Ok(Ok(()))) {
+        for (path, _) in &received_files {
+            if let Err(delete_error) = std::fs::remove_file(path) {
+                log::error!("Failed to delete the temporary file: {}", delete_error);
+            }
         }
-        let modified_current_dir_str = current_dir_str.replace("\\", "/");
-        let path = format!("{}/tmp/{}", modified_current_dir_str, file_name);
+    }
+    match received {
+        Ok(Ok(())) => {}
+        Ok(Err(rejection)) => return Ok(reject_upload(rejection)),
+        Err(err) => return Err(err),
+    }
+
+    let mut container_meta_dtos = Vec::new();
+    let mut received_files = received_files.into_iter();
+    while let Some((path, file_name)) = received_files.next() {
         let path_clone = path.clone();
         let path_clone_clone = path_clone.clone();
-        log::info!("saving to {}", &path);
-        f.file.persist(path).unwrap();
 
         let mut upload_file_parameters = services::upload_parameters::UploadFileParameters::new();
         upload_file_parameters.file_name = path_clone;
         upload_file_parameters.blob_name = file_name.clone();
 
-        // Some mock data
-        let mut upload_meta_parameters = services::upload_parameters::UploadMetaParameters::new();
-        upload_meta_parameters.title = String::from("Peace");
-        upload_meta_parameters.description = String::from("Peace for the world");
-        upload_meta_parameters.tags = vec![Some(String::from("Nature"))];
-
         let result = multimedia_management_service
             .upload_blob_from_file_and_create_metadata(
+                &principal,
                 &upload_file_parameters,
                 &upload_meta_parameters,
             )
             .await;
 
-        if let Err(err) = result {
-            if let Err(delete_error) = std::fs::remove_file(&path_clone_clone) {
-                log::error!("Failed to delete the temporary file: {}", delete_error);
+        if let Err(delete_error) = std::fs::remove_file(&path_clone_clone) {
+            log::error!("Failed to delete the temporary file: {}", delete_error);
+        }
+        if result.is_err() {
+            // the remaining files are not stored either
+            for (path, _) in received_files.by_ref() {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        match result {
+            Ok(container_meta) => container_meta_dtos.push(
+                dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+            ),
+            Err(err) if err.is::<AuthorizationError>() => {
+                return Ok(HttpResponse::Forbidden().finish());
+            }
+            Err(err) if err.is::<QuotaExceededError>() => {
+                return Ok(
+                    HttpResponse::PayloadTooLarge().json(dtos::error_dto::ErrorDto::new(
+                        "quota_exceeded",
+                        &err.to_string(),
+                    )),
+                );
             }
-            return Ok(HttpResponse::BadRequest().finish());
-        } else {
-            if let Err(delete_error) = std::fs::remove_file(&path_clone_clone) {
+            Err(err) if err.is::<UploadRejection>() => {
+                return Ok(reject_upload(*err.downcast::<UploadRejection>().unwrap()));
+            }
+            Err(err) if err.is::<ScanUnavailableError>() => {
+                log::error!("Failed to scan {}: {}", file_name, err);
+                return Ok(HttpResponse::ServiceUnavailable().json(
+                    dtos::error_dto::ErrorDto::new("scanner_unavailable", &err.to_string()),
+                ));
+            }
+            Err(err) => {
+                log::error!("Failed to upload {}: {}", file_name, err);
+                return Ok(HttpResponse::BadRequest().finish());
+            }
+        }
+    }
+    Ok(HttpResponse::Ok().json(container_meta_dtos))
+}
+
+/// Replaces the file of a container by the single `file` field of the request, keeping its id, title, description
+/// and tags. The previous file stays stored as an older version, see `GET /api/v1/mms/containers/{id}/versions`.
+#[put("/api/v1/mms/containers/{id}/file")]
+async fn replace_container_file(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    mut payload: Multipart,
+    principal: web::ReqData<Principal>,
+    upload_config: web::Data<services::app_config::UploadConfig>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if let Some(content_length) = content_length {
+        if let Err(rejection) =
+            upload_validation::check_request_size(&upload_config, content_length)
+        {
+            return Ok(reject_upload(rejection));
+        }
+    }
+    let temp_dir = create_upload_temp_dir(&upload_config);
+    // a second file is rejected before it is written
+    let mut single_file_upload_config = upload_config.get_ref().clone();
+    single_file_upload_config.max_files_per_request = 1;
+
+    let mut received_files: Vec<(String, String)> = Vec::new();
+    // title, description and tags are kept, metadata fields of the request are ignored
+    let mut upload_meta_parameters = services::upload_parameters::UploadMetaParameters::new();
+    let received = receive_upload_files(
+        &mut payload,
+        &temp_dir,
+        &single_file_upload_config,
+        &mut received_files,
+        &mut upload_meta_parameters,
+    )
+    .await;
+    if !matches!(received, Ok(Ok(()))) {
+        for (path, _) in &received_files {
+            if let Err(delete_error) = std::fs::remove_file(path) {
                 log::error!("Failed to delete the temporary file: {}", delete_error);
             }
         }
     }
-    Ok(HttpResponse::Ok().finish())
+    match received {
+        Ok(Ok(())) => {}
+        Ok(Err(rejection)) => return Ok(reject_upload(rejection)),
+        Err(err) => return Err(err),
+    }
+    let (path, file_name) = match received_files.pop() {
+        Some(received_file) => received_file,
+        None => {
+            return Ok(
+                HttpResponse::BadRequest().json(dtos::error_dto::ErrorDto::new(
+                    "missing_file",
+                    &format!("The request contains no {} field", UPLOAD_FIELD_NAME),
+                )),
+            )
+        }
+    };
+
+    let mut upload_file_parameters = services::upload_parameters::UploadFileParameters::new();
+    upload_file_parameters.file_name = path.clone();
+    upload_file_parameters.blob_name = file_name.clone();
+    let result = multimedia_management_service
+        .replace_container_file_by_id(&principal, &container_meta_id, &upload_file_parameters)
+        .await;
+    if let Err(delete_error) = std::fs::remove_file(&path) {
+        log::error!("Failed to delete the temporary file: {}", delete_error);
+    }
+    match result {
+        Ok(Some(container_meta)) => Ok(HttpResponse::Ok().json(
+            dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) if err.is::<QuotaExceededError>() => Ok(HttpResponse::PayloadTooLarge().json(
+            dtos::error_dto::ErrorDto::new("quota_exceeded", &err.to_string()),
+        )),
+        Err(err) if err.is::<UploadRejection>() => {
+            Ok(reject_upload(*err.downcast::<UploadRejection>().unwrap()))
+        }
+        Err(err) if err.is::<ScanUnavailableError>() => {
+            log::error!("Failed to scan {}: {}", file_name, err);
+            Ok(
+                HttpResponse::ServiceUnavailable().json(dtos::error_dto::ErrorDto::new(
+                    "scanner_unavailable",
+                    &err.to_string(),
+                )),
+            )
+        }
+        Err(err) => {
+            log::error!(
+                "Failed to replace the file of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+// creates the temp dir required for file uploads and returns its absolute path with forward slashes
+fn create_upload_temp_dir(upload_config: &services::app_config::UploadConfig) -> String {
+    if let Err(err) = fs::create_dir_all(&upload_config.temp_dir) {
+        log::error!("Failed to create directory: {}", err);
+    }
+    let mut temp_dir_str = upload_config.temp_dir.clone();
+    if let Ok(temp_dir) = std::path::absolute(&upload_config.temp_dir) {
+        temp_dir_str = temp_dir.to_string_lossy().to_string();
+    }
+    temp_dir_str.replace("\\", "/")
+}
+
+// the file name of a multipart field, if the number of files and its type are accepted
+fn check_upload_field(
+    field: &Field,
+    upload_config: &services::app_config::UploadConfig,
+    file_count: usize,
+) -> Result<String, UploadRejection> {
+    upload_validation::check_file_count(upload_config, file_count)?;
+    let file_name = upload_validation::sanitize_file_name(
+        field
+            .content_disposition()
+            .get_filename()
+            .unwrap_or_default(),
+    )?;
+    let mime_type = upload_validation::resolve_mime_type(
+        field
+            .content_type()
+            .map(|content_type| content_type.essence_str()),
+        &file_name,
+    );
+    upload_validation::check_mime_type(upload_config, &mime_type)?;
+    Ok(file_name)
+}
+
+// writes the `file` fields to the temp dir, adding them to `received_files` as soon as they are created
+// so the caller can clean up after a rejection, and collects the metadata fields into `upload_meta_parameters`
+async fn receive_upload_files(
+    payload: &mut Multipart,
+    temp_dir: &str,
+    upload_config: &services::app_config::UploadConfig,
+    received_files: &mut Vec<(String, String)>,
+    upload_meta_parameters: &mut services::upload_parameters::UploadMetaParameters,
+) -> Result<Result<(), UploadRejection>, actix_web::Error> {
+    let mut request_size_bytes = 0;
+    while let Some(field) = payload.next().await {
+        let mut field = field?;
+        let field_name = field.name().to_string();
+        if matches!(
+            field_name.as_str(),
+            UPLOAD_TITLE_FIELD_NAME | UPLOAD_DESCRIPTION_FIELD_NAME | UPLOAD_TAGS_FIELD_NAME
+        ) {
+            let value = match receive_text_field(
+                &mut field,
+                &field_name,
+                upload_config,
+                &mut request_size_bytes,
+            )
+            .await?
+            {
+                Ok(value) => value,
+                Err(rejection) => return Ok(Err(rejection)),
+            };
+            match field_name.as_str() {
+                UPLOAD_TITLE_FIELD_NAME => upload_meta_parameters.title = value.trim().to_string(),
+                UPLOAD_DESCRIPTION_FIELD_NAME => {
+                    upload_meta_parameters.description = value.trim().to_string()
+                }
+                _ => upload_meta_parameters.tags.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(|tag| Some(tag.to_string())),
+                ),
+            }
+            continue;
+        }
+        if field_name != UPLOAD_FIELD_NAME {
+            continue;
+        }
+        let file_name = match check_upload_field(&field, upload_config, received_files.len() + 1) {
+            Ok(file_name) => file_name,
+            Err(rejection) => return Ok(Err(rejection)),
+        };
+        // prefixed, so concurrent uploads of equally named files don't overwrite each other
+        let path = format!("{}/{}-{}", temp_dir, Uuid::new_v4(), file_name);
+        log::info!("saving to {}", &path);
+        received_files.push((path.clone(), file_name));
+        if let Err(rejection) =
+            receive_upload_file(&mut field, &path, upload_config, &mut request_size_bytes).await?
+        {
+            return Ok(Err(rejection));
+        }
+    }
+    Ok(Ok(()))
+}
+
+// writes the field to `path`, stopping at the first chunk exceeding the file or request size limit
+async fn receive_upload_file(
+    field: &mut Field,
+    path: &str,
+    upload_config: &services::app_config::UploadConfig,
+    request_size_bytes: &mut usize,
+) -> Result<Result<(), UploadRejection>, actix_web::Error> {
+    let mut file = fs::File::create(path)?;
+    let mut file_size_bytes = 0;
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk?;
+        file_size_bytes += chunk.len();
+        *request_size_bytes += chunk.len();
+        if let Err(rejection) = upload_validation::check_file_size(upload_config, file_size_bytes)
+            .and_then(|_| upload_validation::check_request_size(upload_config, *request_size_bytes))
+        {
+            return Ok(Err(rejection));
+        }
+        file.write_all(&chunk)?;
+    }
+    Ok(Ok(()))
+}
+
+// reads a text field, its bytes count towards the request size as well
+async fn receive_text_field(
+    field: &mut Field,
+    name: &str,
+    upload_config: &services::app_config::UploadConfig,
+    request_size_bytes: &mut usize,
+) -> Result<Result<String, UploadRejection>, actix_web::Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk?;
+        *request_size_bytes += chunk.len();
+        if let Err(rejection) = upload_validation::check_field_size(name, bytes.len() + chunk.len())
+            .and_then(|_| upload_validation::check_request_size(upload_config, *request_size_bytes))
+        {
+            return Ok(Err(rejection));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    match String::from_utf8(bytes) {
+        Ok(value) => Ok(Ok(value)),
+        Err(_) => Err(actix_web::error::ErrorBadRequest(format!(
+            "The {} field is not valid UTF-8",
+            name
+        ))),
+    }
+}
+
+fn reject_upload(rejection: UploadRejection) -> HttpResponse {
+    metrics::counter!("mms_upload_rejections_total", "reason" => rejection.code()).increment(1);
+    log::warn!("Rejected upload: {}", rejection);
+    let error_dto = dtos::error_dto::ErrorDto::new(rejection.code(), &rejection.to_string());
+    match rejection {
+        UploadRejection::UnsupportedMediaType { .. } => {
+            HttpResponse::UnsupportedMediaType().json(error_dto)
+        }
+        UploadRejection::InvalidFileName => HttpResponse::BadRequest().json(error_dto),
+        UploadRejection::InvalidContent { .. } => {
+            HttpResponse::UnprocessableEntity().json(error_dto)
+        }
+        _ => HttpResponse::PayloadTooLarge().json(error_dto),
+    }
+}
+
+// only single byte ranges are served, other ranges are ignored and the whole file is returned
+fn to_byte_range(range: header::Range) -> Option<ByteRange> {
+    match range {
+        header::Range::Bytes(byte_range_specs) if byte_range_specs.len() == 1 => {
+            match byte_range_specs[0] {
+                header::ByteRangeSpec::FromTo(start, end) => {
+                    Some(ByteRange::FromTo(start, Some(end)))
+                }
+                header::ByteRangeSpec::From(start) => Some(ByteRange::FromTo(start, None)),
+                header::ByteRangeSpec::Last(length) => Some(ByteRange::Last(length)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Downloads the current file of a container, or a previous one with `?version=`. A single `Range: bytes=` range
+/// is answered with 206 and only that part of the file.
+#[get("/api/v1/mms/containers/{id}/file")]
+async fn download_container_file(
+    path: web::Path<Uuid>,
+    query: web::Query<VersionQuery>,
+    range: Option<web::Header<header::Range>>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    let range = range.and_then(|range| to_byte_range(range.into_inner()));
+    match multimedia_management_service
+        .retrieve_bytes_from_blob_by_id(&principal, &container_meta_id, query.version, range)
+        .await
+    {
+        Ok(Some((container_meta, bytes, Some((start, end))))) => Ok(HttpResponse::PartialContent()
+            .content_type(container_meta.mime_type)
+            .insert_header(ContentDisposition::attachment(
+                container_meta.original_file_name,
+            ))
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                range: Some((start, end)),
+                instance_length: Some(container_meta.file_size_bytes as u64),
+            }))
+            .body(bytes)),
+        Ok(Some((container_meta, bytes, None))) => Ok(HttpResponse::Ok()
+            .content_type(container_meta.mime_type)
+            .insert_header(ContentDisposition::attachment(
+                container_meta.original_file_name,
+            ))
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .body(bytes)),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) if err.is::<RangeNotSatisfiableError>() => {
+            let file_size_bytes = err
+                .downcast_ref::<RangeNotSatisfiableError>()
+                .unwrap()
+                .file_size_bytes;
+            Ok(HttpResponse::RangeNotSatisfiable()
+                .insert_header(header::ContentRange(header::ContentRangeSpec::Bytes {
+                    range: None,
+                    instance_length: Some(file_size_bytes),
+                }))
+                .finish())
+        }
+        Err(err) => {
+            log::error!("Failed to download {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[delete("/api/v1/mms/containers/{id}")]
+async fn delete_container(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .soft_delete_container_by_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(HttpResponse::NoContent().finish()),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!("Failed to delete {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[post("/api/v1/mms/containers/{id}/restore")]
+async fn restore_container(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .restore_container_by_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(HttpResponse::NoContent().finish()),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!("Failed to restore {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/thumbnails")]
+async fn get_thumbnails(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_thumbnails_by_container_meta_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        // without a keyframe decoder only embedded cover art is stored as a thumbnail
+        Ok(Some(thumbnails))
+            if thumbnails.is_empty()
+                && !multimedia_management_service.is_thumbnail_generation_enabled() =>
+        {
+            Ok(HttpResponse::NotImplemented().json(dtos::error_dto::ErrorDto::new(
+                "thumbnails_disabled",
+                "Thumbnail generation is disabled since no keyframe decoder is configured (KEYFRAME_DECODER_PROGRAM)",
+            )))
+        }
+        Ok(Some(thumbnails)) if thumbnails.is_empty() => Ok(HttpResponse::NotFound().finish()),
+        Ok(Some(thumbnails)) => Ok(HttpResponse::Ok().json(
+            thumbnails
+                .iter()
+                .map(dtos::thumbnail_dto::ThumbnailDto::from)
+                .collect::<Vec<dtos::thumbnail_dto::ThumbnailDto>>(),
+        )),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve thumbnails of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/thumbnails/{name}")]
+async fn get_thumbnail(
+    path: web::Path<(Uuid, String)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, name) = path.into_inner();
+    match multimedia_management_service
+        .retrieve_thumbnail_bytes_by_name(&principal, &container_meta_id, &name)
+        .await
+    {
+        Ok(Some((thumbnail, bytes))) => Ok(HttpResponse::Ok()
+            .content_type(thumbnail.mime_type)
+            .body(bytes)),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve thumbnail {} of {}: {}",
+                name,
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/renditions")]
+async fn get_renditions(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_renditions_by_container_meta_id(&principal, &container_meta_id)
+        .await
+    {
+        // renditions of pending containers are still to come, hence an empty list rather than 404
+        Ok(Some((_, renditions))) => Ok(HttpResponse::Ok().json(
+            renditions
+                .iter()
+                .map(dtos::rendition_dto::RenditionDto::from)
+                .collect::<Vec<dtos::rendition_dto::RenditionDto>>(),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve renditions of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/renditions/{name}")]
+async fn get_rendition(
+    path: web::Path<(Uuid, String)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, name) = path.into_inner();
+    match multimedia_management_service
+        .retrieve_rendition_bytes_by_name(&principal, &container_meta_id, &name)
+        .await
+    {
+        Ok(Some((rendition, bytes))) => Ok(HttpResponse::Ok()
+            .content_type(rendition.mime_type)
+            .body(bytes)),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve rendition {} of {}: {}",
+                name,
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Cuts a clip without re-encoding and stores it as a new container linked to its source.
+#[post("/api/v1/mms/containers/{id}/clips")]
+async fn create_clip(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    create_clip_dto: web::Json<dtos::container_meta_dto::CreateClipDto>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    let create_clip_dto = create_clip_dto.into_inner();
+    let mut clip_parameters = services::upload_parameters::ClipParameters::new();
+    clip_parameters.start_time_in_ms = create_clip_dto.start_time_in_ms;
+    clip_parameters.end_time_in_ms = create_clip_dto.end_time_in_ms;
+    clip_parameters.title = create_clip_dto.title.unwrap_or_default();
+    match multimedia_management_service
+        .create_clip_by_id(&principal, &container_meta_id, &clip_parameters)
+        .await
+    {
+        Ok(Some(container_meta)) => Ok(HttpResponse::Created().json(
+            dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) if err.is::<ClipRejectedError>() => {
+            let clip_rejected_error = err.downcast::<ClipRejectedError>().unwrap();
+            let error_dto = dtos::error_dto::ErrorDto::new(
+                &clip_rejected_error.code,
+                &clip_rejected_error.reason,
+            );
+            match clip_rejected_error.code.as_str() {
+                "invalid_range" => Ok(HttpResponse::BadRequest().json(error_dto)),
+                _ => Ok(HttpResponse::UnprocessableEntity().json(error_dto)),
+            }
+        }
+        Err(err) if err.is::<QuotaExceededError>() => Ok(HttpResponse::PayloadTooLarge().json(
+            dtos::error_dto::ErrorDto::new("quota_exceeded", &err.to_string()),
+        )),
+        Err(err) if err.is::<UploadRejection>() => {
+            Ok(reject_upload(*err.downcast::<UploadRejection>().unwrap()))
+        }
+        Err(err) if err.is::<ScanUnavailableError>() => {
+            log::error!("Failed to scan the clip of {}: {}", container_meta_id, err);
+            Ok(
+                HttpResponse::ServiceUnavailable().json(dtos::error_dto::ErrorDto::new(
+                    "scanner_unavailable",
+                    &err.to_string(),
+                )),
+            )
+        }
+        Err(err) => {
+            log::error!("Failed to clip {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Demuxes a single track into a standalone file, M4A for AAC, MP4 for H.264 and WebVTT for tx3g subtitles.
+#[get("/api/v1/mms/containers/{id}/tracks/{track_id}/export")]
+async fn export_track(
+    path: web::Path<(Uuid, Uuid)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, track_id) = path.into_inner();
+    match multimedia_management_service
+        .export_track_by_id(&principal, &container_meta_id, &track_id)
+        .await
+    {
+        // the exported file is deleted once the stream is dropped, whether it was sent completely or not
+        Ok(Some(exported_track_file)) => {
+            let mime_type = exported_track_file.mime_type.clone();
+            let file_name = exported_track_file.file_name.clone();
+            let file_size_bytes = exported_track_file.file_size_bytes;
+            match exported_track_file.into_stream().await {
+                Ok(exported_track_stream) => Ok(HttpResponse::Ok()
+                    .content_type(mime_type)
+                    .insert_header(ContentDisposition::attachment(file_name))
+                    .no_chunking(file_size_bytes)
+                    .streaming(exported_track_stream)),
+                Err(err) => {
+                    log::error!(
+                        "Failed to stream track {} of {}: {}",
+                        track_id,
+                        container_meta_id,
+                        err
+                    );
+                    Ok(HttpResponse::InternalServerError().finish())
+                }
+            }
+        }
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) if err.is::<TrackExportRejectedError>() => {
+            let track_export_rejected_error = err.downcast::<TrackExportRejectedError>().unwrap();
+            let error_dto = dtos::error_dto::ErrorDto::new(
+                &track_export_rejected_error.code,
+                &track_export_rejected_error.reason,
+            );
+            match track_export_rejected_error.code.as_str() {
+                // the stored file no longer contains the track recorded for it
+                "track_not_found" => Ok(HttpResponse::NotFound().json(error_dto)),
+                _ => Ok(HttpResponse::UnprocessableEntity().json(error_dto)),
+            }
+        }
+        Err(err) => {
+            log::error!(
+                "Failed to export track {} of {}: {}",
+                track_id,
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/versions")]
+async fn get_container_versions(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_container_versions_by_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(container_versions)) => Ok(HttpResponse::Ok().json(
+            container_versions
+                .iter()
+                .map(dtos::container_version_dto::ContainerVersionDto::from)
+                .collect::<Vec<dtos::container_version_dto::ContainerVersionDto>>(),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve versions of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Points the container back to a previous version of its file, later versions are kept.
+#[post("/api/v1/mms/containers/{id}/versions/{version}/rollback")]
+async fn rollback_container(
+    path: web::Path<(Uuid, i32)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, version) = path.into_inner();
+    match multimedia_management_service
+        .rollback_container_by_id(&principal, &container_meta_id, version)
+        .await
+    {
+        Ok(Some(container_meta)) => Ok(HttpResponse::Ok().json(
+            dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to roll back {} to version {}: {}",
+                container_meta_id,
+                version,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/chapters")]
+async fn get_chapters(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_chapters_by_container_meta_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(chapters)) => Ok(HttpResponse::Ok().json(
+            chapters
+                .iter()
+                .map(dtos::chapter_dto::ChapterDto::from)
+                .collect::<Vec<dtos::chapter_dto::ChapterDto>>(),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve chapters of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Replaces all chapters of the container, an empty list removes them.
+#[put("/api/v1/mms/containers/{id}/chapters")]
+async fn update_chapters(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    update_chapter_dtos: web::Json<Vec<dtos::chapter_dto::UpdateChapterDto>>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    let update_chapter_parameters: Vec<services::update_parameters::UpdateChapterParameters> =
+        update_chapter_dtos
+            .into_inner()
+            .into_iter()
+            .map(
+                |update_chapter_dto| services::update_parameters::UpdateChapterParameters {
+                    title: update_chapter_dto.title,
+                    start_time_in_ms: update_chapter_dto.start_time_in_ms,
+                    end_time_in_ms: update_chapter_dto.end_time_in_ms,
+                },
+            )
+            .collect();
+    match multimedia_management_service
+        .update_chapters_by_container_meta_id(
+            &principal,
+            &container_meta_id,
+            &update_chapter_parameters,
+        )
+        .await
+    {
+        Ok(Some(chapters)) => Ok(HttpResponse::Ok().json(
+            chapters
+                .iter()
+                .map(dtos::chapter_dto::ChapterDto::from)
+                .collect::<Vec<dtos::chapter_dto::ChapterDto>>(),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) if err.is::<ChapterValidationError>() => Ok(HttpResponse::BadRequest().json(
+            dtos::error_dto::ErrorDto::new("invalid_chapters", &err.to_string()),
+        )),
+        Err(err) => {
+            log::error!(
+                "Failed to update chapters of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// The chapters as a WebVTT chapters track, e.g. for a `<track kind="chapters">` element.
+#[get("/api/v1/mms/containers/{id}/chapters.vtt")]
+async fn get_chapters_webvtt(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_chapters_by_container_meta_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(chapters)) => Ok(HttpResponse::Ok()
+            .content_type("text/vtt; charset=utf-8")
+            .body(services::chapters::to_webvtt(&chapters))),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve chapters of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/audio/{track}/waveform")]
+async fn get_audio_waveform(
+    path: web::Path<(Uuid, Uuid)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, audio_track_id) = path.into_inner();
+    match multimedia_management_service
+        .retrieve_audio_track_by_id(&principal, &container_meta_id, &audio_track_id)
+        .await
+    {
+        // the waveform is stored as audiowaveform JSON and served as is
+        Ok(Some(models::track::AudioTrack {
+            waveform: Some(waveform),
+            ..
+        })) => Ok(HttpResponse::Ok()
+            .content_type("application/json")
+            .body(waveform)),
+        // no pure Rust decoder is available for such tracks, e.g. Opus, so they are not analyzed
+        Ok(Some(audio_track))
+            if audio_track.analysis_status
+                == services::mutimedia_management_service::AUDIO_ANALYSIS_UNSUPPORTED =>
+        {
+            Ok(
+                HttpResponse::UnprocessableEntity().json(dtos::error_dto::ErrorDto::new(
+                    "unsupported_codec",
+                    &format!(
+                        "Audio tracks of type {} are not analyzed since no decoder is available for them, only AAC is supported",
+                        audio_track.media_type
+                    ),
+                )),
+            )
+        }
+        // pending and failed audio tracks have no waveform
+        Ok(_) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve the waveform of audio track {} of {}: {}",
+                audio_track_id,
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers")]
+async fn list_containers(
+    query: web::Query<ListQuery>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    match multimedia_management_service
+        .retrieve_container_metas(&principal, &list_parameters_from(&query))
+        .await
+    {
+        Ok(container_metas) => Ok(HttpResponse::Ok().json(
+            container_metas
+                .iter()
+                .map(dtos::container_meta_dto::ContainerMetaDto::from)
+                .collect::<Vec<dtos::container_meta_dto::ContainerMetaDto>>(),
+        )),
+        Err(err) => {
+            log::error!(
+                "Failed to list containers for {}: {}",
+                principal.subject,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}")]
+async fn get_container(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_container_meta_by_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(container_meta)) => Ok(HttpResponse::Ok().json(
+            dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!("Failed to retrieve {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[patch("/api/v1/mms/containers/{id}")]
+async fn update_container(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    update_container_meta_dto: web::Json<dtos::container_meta_dto::UpdateContainerMetaDto>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    let update_container_meta_dto = update_container_meta_dto.into_inner();
+    let mut update_meta_parameters = services::update_parameters::UpdateMetaParameters::new();
+    update_meta_parameters.title = update_container_meta_dto.title;
+    update_meta_parameters.description = update_container_meta_dto.description;
+    update_meta_parameters.tags = update_container_meta_dto
+        .tags
+        .map(|tags| tags.into_iter().map(Some).collect());
+    match multimedia_management_service
+        .update_container_meta_by_id(&principal, &container_meta_id, &update_meta_parameters)
+        .await
+    {
+        Ok(Some(container_meta)) => Ok(HttpResponse::Ok().json(
+            dtos::container_meta_dto::ContainerMetaDto::from(&container_meta),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!("Failed to update {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/containers/{id}/grants")]
+async fn get_container_grants(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    match multimedia_management_service
+        .retrieve_container_grants_by_id(&principal, &container_meta_id)
+        .await
+    {
+        Ok(Some(container_grants)) => Ok(HttpResponse::Ok().json(
+            container_grants
+                .iter()
+                .map(dtos::container_grant_dto::ContainerGrantDto::from)
+                .collect::<Vec<dtos::container_grant_dto::ContainerGrantDto>>(),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve grants of {}: {}",
+                container_meta_id,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[post("/api/v1/mms/containers/{id}/grants")]
+async fn share_container(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    create_container_grant_dto: web::Json<dtos::container_grant_dto::CreateContainerGrantDto>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let container_meta_id = path.into_inner();
+    if create_container_grant_dto.permission != models::container_grant::PERMISSION_VIEW
+        && create_container_grant_dto.permission != models::container_grant::PERMISSION_EDIT
+    {
+        return Ok(HttpResponse::BadRequest().finish());
+    }
+    match multimedia_management_service
+        .share_container_by_id(
+            &principal,
+            &container_meta_id,
+            &create_container_grant_dto.grantee,
+            &create_container_grant_dto.permission,
+        )
+        .await
+    {
+        Ok(Some(container_grant)) => Ok(HttpResponse::Created().json(
+            dtos::container_grant_dto::ContainerGrantDto::from(&container_grant),
+        )),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!("Failed to share {}: {}", container_meta_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[delete("/api/v1/mms/containers/{id}/grants/{grantee}")]
+async fn unshare_container(
+    path: web::Path<(Uuid, String)>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (container_meta_id, grantee) = path.into_inner();
+    match multimedia_management_service
+        .unshare_container_by_id(&principal, &container_meta_id, &grantee)
+        .await
+    {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to unshare {} from {}: {}",
+                container_meta_id,
+                grantee,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Uploads of the tenant rejected by content validation, with the reason and the quarantined object key.
+#[get("/api/v1/mms/quarantined-uploads")]
+async fn list_quarantined_uploads(
+    query: web::Query<ListQuery>,
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    match multimedia_management_service
+        .retrieve_quarantined_uploads(&principal, &list_parameters_from(&query))
+        .await
+    {
+        Ok(quarantined_uploads) => Ok(HttpResponse::Ok().json(
+            quarantined_uploads
+                .iter()
+                .map(dtos::quarantined_upload_dto::QuarantinedUploadDto::from)
+                .collect::<Vec<dtos::quarantined_upload_dto::QuarantinedUploadDto>>(),
+        )),
+        Err(err) if err.is::<AuthorizationError>() => Ok(HttpResponse::Forbidden().finish()),
+        Err(err) => {
+            log::error!(
+                "Failed to list quarantined uploads for {}: {}",
+                principal.subject,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[get("/api/v1/mms/usage")]
+async fn get_usage(
+    principal: web::ReqData<Principal>,
+    multimedia_management_service: web::Data<
+        services::mutimedia_management_service::MutimediaManagementService,
+    >,
+) -> Result<HttpResponse, actix_web::Error> {
+    match multimedia_management_service
+        .retrieve_usage(&principal)
+        .await
+    {
+        Ok((tenant_usage, user_usage)) => {
+            let quota_parameters = multimedia_management_service
+                .quota_parameters
+                .as_ref()
+                .unwrap();
+            Ok(HttpResponse::Ok().json(dtos::usage_dto::UsageReportDto {
+                tenant_id: String::from(principal.resolved_tenant_id()),
+                subject: principal.subject.clone(),
+                tenant: dtos::usage_dto::UsageDto::from(&tenant_usage),
+                user: dtos::usage_dto::UsageDto::from(&user_usage),
+                tenant_quota: dtos::usage_dto::QuotaDto {
+                    max_bytes: quota_parameters.max_bytes_per_tenant,
+                    max_containers: quota_parameters.max_containers_per_tenant,
+                },
+                user_quota: dtos::usage_dto::QuotaDto {
+                    max_bytes: quota_parameters.max_bytes_per_user,
+                    max_containers: quota_parameters.max_containers_per_user,
+                },
+            }))
+        }
+        Err(err) => {
+            log::error!(
+                "Failed to retrieve usage for {}: {}",
+                principal.subject,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[post("/api/v1/mms/api-keys")]
+async fn create_api_key(
+    principal: web::ReqData<Principal>,
+    create_api_key_dto: web::Json<dtos::api_key_dto::CreateApiKeyDto>,
+    authentication_service: web::Data<services::authentication_service::AuthenticationService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    // a leaked api key must not be able to issue further keys
+    if principal.authentication_method != AuthenticationMethod::Jwt {
+        return Ok(HttpResponse::Forbidden().finish());
+    }
+    match authentication_service
+        .create_api_key(&principal, &create_api_key_dto.name)
+        .await
+    {
+        Ok((api_key, plain_text_api_key)) => {
+            let mut api_key_dto = dtos::api_key_dto::ApiKeyDto::from(&api_key);
+            api_key_dto.api_key = Some(plain_text_api_key);
+            Ok(HttpResponse::Created().json(api_key_dto))
+        }
+        Err(err) => {
+            log::error!(
+                "Failed to create api key for {}: {}",
+                principal.subject,
+                err
+            );
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[delete("/api/v1/mms/api-keys/{id}")]
+async fn revoke_api_key(
+    path: web::Path<Uuid>,
+    principal: web::ReqData<Principal>,
+    authentication_service: web::Data<services::authentication_service::AuthenticationService>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let api_key_id = path.into_inner();
+    match authentication_service
+        .revoke_api_key(&principal, &api_key_id)
+        .await
+    {
+        Ok(Some(_)) => Ok(HttpResponse::NoContent().finish()),
+        Ok(None) => Ok(HttpResponse::NotFound().finish()),
+        Err(err) => {
+            log::error!("Failed to revoke api key {}: {}", api_key_id, err);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Retries `initialize` since dependencies such as the database may become reachable after the service started.
+async fn initialize_with_retries<T, F, Fut>(
+    name: &str,
+    server_config: &services::app_config::ServerConfig,
+    initialize: F,
+) -> T
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, Box<dyn std::error::Error>>>,
+{
+    let mut attempt = 1;
+    loop {
+        match initialize().await {
+            Ok(value) => return value,
+            Err(err) if attempt < server_config.startup_max_attempts => {
+                log::warn!(
+                    "Failed to initialize the {} (attempt {}/{}), retrying in {}s: {}",
+                    name,
+                    attempt,
+                    server_config.startup_max_attempts,
+                    server_config.startup_retry_interval_in_secs,
+                    err
+                );
+                actix_web::rt::time::sleep(Duration::from_secs(
+                    server_config.startup_retry_interval_in_secs,
+                ))
+                .await;
+                attempt += 1;
+            }
+            Err(err) => {
+                log::error!("Failed to initialize the {}: {}", name, err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Runs `job` every `interval_in_secs` until shutdown is signalled. A running job is finished first.
+fn spawn_periodic_job<F, Fut>(
+    interval_in_secs: u64,
+    mut shutdown: watch::Receiver<bool>,
+    job: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(interval_in_secs));
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.changed() => break,
+                _ = interval.tick() => job().await,
+            }
+        }
+    })
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
-    log::info!("starting HTTP server at http://localhost:8080");
-
-    let env_file_path = "./assets/app-secrets.dev.cfg";
+    let env_file_path = std::env::var("MMS_ENV_FILE")
+        .unwrap_or_else(|_| String::from("./assets/app-secrets.dev.cfg"));
     dotenv::from_path(env_file_path).ok();
 
+    // the TOML file is optional, every setting can be provided through the environment
+    let config_file_path =
+        std::env::var("MMS_CONFIG_FILE").unwrap_or_else(|_| String::from("./assets/app.toml"));
+    let config_file = std::path::Path::new(&config_file_path)
+        .is_file()
+        .then_some(config_file_path.as_str());
+    let app_config = services::app_config::AppConfig::load(config_file);
+
+    // an invalid configuration is reported with the default log format
+    let logging_config = match &app_config {
+        Ok(app_config) => app_config.logging.clone(),
+        Err(_) => services::app_config::LoggingConfig::default(),
+    };
+    let _telemetry_guard = match telemetry::init(&logging_config) {
+        Ok(telemetry_guard) => telemetry_guard,
+        Err(err) => {
+            eprintln!("Failed to initialize logging: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let app_config = match app_config {
+        Ok(app_config) => app_config,
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // installed before the services, so metrics recorded during startup are not lost
+    let prometheus_handle = match PrometheusBuilder::new()
+        .set_buckets(&HISTOGRAM_BUCKETS)
+        .and_then(|prometheus_builder| prometheus_builder.install_recorder())
+    {
+        Ok(prometheus_handle) => prometheus_handle,
+        Err(err) => {
+            log::error!("Failed to install the metrics recorder: {}", err);
+            std::process::exit(1);
+        }
+    };
+
     let multi_media_management_service =
-        services::mutimedia_management_service::MutimediaManagementService::new().await;
+        initialize_with_retries("multimedia management service", &app_config.server, || {
+            services::mutimedia_management_service::MutimediaManagementService::new(&app_config)
+        })
+        .await;
+    let authentication_service =
+        initialize_with_retries("authentication service", &app_config.server, || {
+            services::authentication_service::AuthenticationService::new(&app_config)
+        })
+        .await;
+
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
 
-    HttpServer::new(move || {
+    // hard delete soft deleted containers once their retention expired
+    let purge_parameters = app_config.purge.clone();
+    let purge_multi_media_management_service = multi_media_management_service.clone();
+    let purge_job = spawn_periodic_job(
+        purge_parameters.interval_in_secs,
+        shutdown_receiver.clone(),
+        move || {
+            let multi_media_management_service = purge_multi_media_management_service.clone();
+            let purge_parameters = purge_parameters.clone();
+            async move {
+                if let Err(err) = multi_media_management_service
+                    .purge_deleted_containers(&purge_parameters)
+                    .await
+                {
+                    log::error!("Failed to purge deleted containers: {}", err);
+                }
+            }
+        },
+    );
+
+    // recompute file sizes and durations of containers uploaded before they were stored precisely
+    let backfill_parameters = app_config.backfill.clone();
+    let backfill_multi_media_management_service = multi_media_management_service.clone();
+    let backfill_job = spawn_periodic_job(
+        backfill_parameters.interval_in_secs,
+        shutdown_receiver.clone(),
+        move || {
+            let multi_media_management_service = backfill_multi_media_management_service.clone();
+            let backfill_parameters = backfill_parameters.clone();
+            async move {
+                if let Err(err) = multi_media_management_service
+                    .backfill_container_metadata(&backfill_parameters)
+                    .await
+                {
+                    log::error!("Failed to backfill container metadata: {}", err);
+                }
+            }
+        },
+    );
+
+    // encode the rendition ladder of new uploads, an empty ladder disables transcoding
+    let transcoding_parameters = app_config.transcoding.clone();
+    let transcoding_multi_media_management_service = multi_media_management_service.clone();
+    let transcoding_job = match transcoding_parameters.ladder.is_empty() {
+        true => None,
+        false => Some(spawn_periodic_job(
+            transcoding_parameters.interval_in_secs,
+            shutdown_receiver.clone(),
+            move || {
+                let multi_media_management_service =
+                    transcoding_multi_media_management_service.clone();
+                let transcoding_parameters = transcoding_parameters.clone();
+                async move {
+                    if let Err(err) = multi_media_management_service
+                        .transcode_pending_containers(&transcoding_parameters)
+                        .await
+                    {
+                        log::error!("Failed to transcode pending containers: {}", err);
+                    }
+                }
+            },
+        )),
+    };
+
+    // measure the loudness and generate the scrubber waveform of new audio tracks
+    let audio_analysis_parameters = app_config.audio_analysis.clone();
+    let audio_analysis_multi_media_management_service = multi_media_management_service.clone();
+    let audio_analysis_job = spawn_periodic_job(
+        audio_analysis_parameters.interval_in_secs,
+        shutdown_receiver,
+        move || {
+            let multi_media_management_service =
+                audio_analysis_multi_media_management_service.clone();
+            let audio_analysis_parameters = audio_analysis_parameters.clone();
+            async move {
+                if let Err(err) = multi_media_management_service
+                    .analyze_pending_audio_tracks(&audio_analysis_parameters)
+                    .await
+                {
+                    log::error!("Failed to analyze pending audio tracks: {}", err);
+                }
+            }
+        },
+    );
+
+    let upload_config = app_config.upload.clone();
+    // created once rather than per worker, so the limits apply to the server as a whole
+    let rate_limiters = web::Data::new(RateLimiters {
+        upload: RateLimiter::new(&app_config.rate_limit.upload),
+        download: RateLimiter::new(&app_config.rate_limit.download),
+    });
+    log::info!(
+        "starting HTTP server at http://{}",
+        app_config.server.bind_address
+    );
+    let result = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(multi_media_management_service.clone()))
-            // enable logger
-            .wrap(middleware::Logger::default())
+            .app_data(web::Data::new(authentication_service.clone()))
+            .app_data(web::Data::new(upload_config.clone()))
+            .app_data(web::Data::new(prometheus_handle.clone()))
+            .app_data(rate_limiters.clone())
+            .wrap(middleware::from_fn(limit_rate))
+            .wrap(middleware::from_fn(authenticate))
+            .wrap(middleware::from_fn(record_http_metrics))
+            // outermost, so the span and request id cover authentication as well
+            .wrap(middleware::from_fn(trace_request))
+            .service(healthz)
+            .service(get_metrics)
+            .service(readyz)
             .service(upload_blob)
+            .service(list_containers)
+            .service(get_container)
+            .service(update_container)
+            .service(get_container_grants)
+            .service(share_container)
+            .service(unshare_container)
+            .service(download_container_file)
+            .service(replace_container_file)
+            .service(get_container_versions)
+            .service(rollback_container)
+            .service(delete_container)
+            .service(restore_container)
+            .service(get_thumbnails)
+            .service(get_thumbnail)
+            .service(get_renditions)
+            .service(get_rendition)
+            .service(create_clip)
+            .service(export_track)
+            .service(get_chapters)
+            .service(update_chapters)
+            .service(get_chapters_webvtt)
+            .service(get_audio_waveform)
+            .service(get_usage)
+            .service(list_quarantined_uploads)
+            .service(create_api_key)
+            .service(revoke_api_key)
     })
-    .bind(("127.0.0.1", 8080))?
+    .bind(&app_config.server.bind_address)?
+    // stops accepting connections on SIGTERM and SIGINT, awaiting in-flight requests such as uploads
+    .shutdown_timeout(app_config.server.shutdown_timeout_in_secs)
     .run()
+    .await;
+
+    log::info!("Waiting for background jobs to finish");
+    let _ = shutdown_sender.send(true);
+    let shutdown_timeout = Duration::from_secs(app_config.server.shutdown_timeout_in_secs);
+    if actix_web::rt::time::timeout(shutdown_timeout, async {
+        let _ = purge_job.await;
+        let _ = backfill_job.await;
+        if let Some(transcoding_job) = transcoding_job {
+            let _ = transcoding_job.await;
+        }
+        let _ = audio_analysis_job.await;
+    })
     .await
+    .is_err()
+    {
+        log::warn!(
+            "Background jobs did not finish within {}s",
+            app_config.server.shutdown_timeout_in_secs
+        );
+    }
+    result
 }
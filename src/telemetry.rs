@@ -0,0 +1,100 @@
+// The MIT License
+//
+// Copyright (c) 2024 MGTheTrain
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use services::app_config::LoggingConfig;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Keeps the exporter alive and flushes pending spans once dropped at shutdown.
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(tracer_provider) = self.tracer_provider.take() {
+            if let Err(err) = tracer_provider.shutdown() {
+                eprintln!("Failed to flush spans: {}", err);
+            }
+        }
+    }
+}
+
+/// Installs the tracing subscriber writing text or JSON lines, filtered by `RUST_LOG` (default `info`).
+/// `log` records of dependencies and of this binary are forwarded to it, carrying the current span.
+pub fn init(logging_config: &LoggingConfig) -> Result<TelemetryGuard, Box<dyn std::error::Error>> {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt_layer = match logging_config.format.as_str() {
+        "json" => tracing_subscriber::fmt::layer().json().boxed(),
+        _ => tracing_subscriber::fmt::layer().boxed(),
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer);
+
+    #[cfg(feature = "otlp")]
+    {
+        use opentelemetry::trace::TracerProvider as _;
+        use opentelemetry_otlp::WithExportConfig;
+
+        let tracer_provider = match logging_config.otlp_endpoint.is_empty() {
+            true => None,
+            false => {
+                let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(&logging_config.otlp_endpoint)
+                    .build()?;
+                Some(
+                    opentelemetry_sdk::trace::TracerProvider::builder()
+                        .with_batch_exporter(span_exporter, opentelemetry_sdk::runtime::Tokio)
+                        .with_resource(opentelemetry_sdk::Resource::new([
+                            opentelemetry::KeyValue::new(
+                                "service.name",
+                                logging_config.service_name.clone(),
+                            ),
+                        ]))
+                        .build(),
+                )
+            }
+        };
+        let otlp_layer = tracer_provider.as_ref().map(|tracer_provider| {
+            tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("mms"))
+        });
+        subscriber.with(otlp_layer).try_init()?;
+        Ok(TelemetryGuard { tracer_provider })
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    {
+        subscriber.try_init()?;
+        if !logging_config.otlp_endpoint.is_empty() {
+            tracing::warn!(
+                "Ignoring the OTLP endpoint {}, the binary was built without the otlp feature",
+                logging_config.otlp_endpoint
+            );
+        }
+        Ok(TelemetryGuard {})
+    }
+}